@@ -0,0 +1,47 @@
+pub mod post {
+    pub const PATH: &str = "/binance/futures/account/leverage";
+
+    pub mod handler {
+        use crate::api::http::request::Json;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::audit::{self, Action, Entry};
+        use crate::services::binance::futures::{client_with_sign, set_leverage};
+
+        use super::models::{Payload, ResponseBody};
+
+        #[tracing::instrument(skip(c))]
+        pub async fn handler(c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
+            let caller = audit::caller(&p.api_key);
+            let client = client_with_sign(p.api_key, p.secret_key, p.endpoint.as_ref())?;
+
+            let result = set_leverage(&client, &p.symbol, p.leverage).await;
+
+            let payload = serde_json::json!({ "symbol": p.symbol, "leverage": p.leverage });
+            let entry = Entry::new(Action::FuturesLeverage, caller, payload, None, &result);
+            c.audit().record(entry);
+
+            Ok(Response::ok(result?))
+        }
+    }
+
+    pub mod models {
+        use binance::types::Symbol;
+        use serde::{Deserialize, Serialize};
+
+        use crate::secret::Secret;
+        use crate::services::binance::futures::Leverage;
+        use crate::services::binance::Endpoint;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Payload {
+            pub api_key: Secret<String>,
+            pub secret_key: Secret<String>,
+            pub endpoint: Option<Endpoint>,
+            pub symbol: Symbol,
+            pub leverage: u8,
+        }
+
+        pub type ResponseBody = Leverage;
+    }
+}
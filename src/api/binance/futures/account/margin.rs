@@ -0,0 +1,58 @@
+pub mod post {
+    pub const PATH: &str = "/binance/futures/account/margin";
+
+    pub mod handler {
+        use crate::api::http::request::Json;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::audit::{self, Action, Entry};
+        use crate::services::binance::futures::{client_with_sign, set_margin_type};
+
+        use super::models::{Payload, ResponseBody};
+
+        #[tracing::instrument(skip(c))]
+        pub async fn handler(c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
+            let caller = audit::caller(&p.api_key);
+            let client = client_with_sign(p.api_key, p.secret_key, p.endpoint.as_ref())?;
+
+            let result = set_margin_type(&client, &p.symbol, p.margin_type).await;
+
+            let payload = serde_json::json!({ "symbol": p.symbol, "margin_type": p.margin_type });
+            let entry = Entry::new(Action::FuturesMarginType, caller, payload, None, &result);
+            c.audit().record(entry);
+
+            Ok(Response::ok(ResponseBody {
+                changed: result?,
+                symbol: p.symbol,
+                margin_type: p.margin_type,
+            }))
+        }
+    }
+
+    pub mod models {
+        use binance::types::Symbol;
+        use serde::{Deserialize, Serialize};
+
+        use crate::secret::Secret;
+        use crate::services::binance::futures::MarginType;
+        use crate::services::binance::Endpoint;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Payload {
+            pub api_key: Secret<String>,
+            pub secret_key: Secret<String>,
+            pub endpoint: Option<Endpoint>,
+            pub symbol: Symbol,
+            // `ISOLATED` or `CROSSED`
+            pub margin_type: MarginType,
+        }
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct ResponseBody {
+            pub symbol: Symbol,
+            pub margin_type: MarginType,
+            // Unset when the symbol already had this margin type
+            pub changed: bool,
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod leverage;
+pub mod margin;
+pub mod position;
@@ -0,0 +1,41 @@
+pub mod post {
+    pub const PATH: &str = "/binance/futures/account/position";
+
+    pub mod handler {
+        use crate::api::http::request::Json;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::services::binance::futures::{client_with_sign, fetch_positions};
+
+        use super::models::{Payload, ResponseBody};
+
+        #[tracing::instrument(skip(_c))]
+        pub async fn handler(_c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
+            let client = client_with_sign(p.api_key, p.secret_key, p.endpoint.as_ref())?;
+
+            let result = fetch_positions(&client, p.symbol.as_ref()).await?;
+
+            Ok(Response::ok(result))
+        }
+    }
+
+    pub mod models {
+        use binance::types::Symbol;
+        use serde::{Deserialize, Serialize};
+
+        use crate::secret::Secret;
+        use crate::services::binance::futures::Position;
+        use crate::services::binance::Endpoint;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Payload {
+            pub api_key: Secret<String>,
+            pub secret_key: Secret<String>,
+            pub endpoint: Option<Endpoint>,
+            // Every open position when unset
+            pub symbol: Option<Symbol>,
+        }
+
+        pub type ResponseBody = Vec<Position>;
+    }
+}
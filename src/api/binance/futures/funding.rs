@@ -0,0 +1,41 @@
+pub mod get {
+    pub const PATH: &str = "/binance/futures/funding";
+
+    pub mod handler {
+        use crate::api::http::request::Query;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::services::binance::futures::{client, fetch_funding_rates};
+
+        use super::models::{Params, ResponseBody};
+
+        #[tracing::instrument(skip(_c))]
+        pub async fn handler(_c: Trip, Query(p): Query<Params>) -> ResponseResult<ResponseBody> {
+            let client = client()?;
+
+            let result =
+                fetch_funding_rates(&client, &p.symbol, p.start_time, p.end_time, p.limit).await?;
+
+            Ok(Response::ok(result))
+        }
+    }
+
+    pub mod models {
+        use binance::types::Symbol;
+        use serde::{Deserialize, Serialize};
+
+        use crate::services::binance::futures::FundingHistory;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Params {
+            pub symbol: Symbol,
+            // Milliseconds since the epoch
+            pub start_time: Option<u64>,
+            pub end_time: Option<u64>,
+            // 100 when unset, at most 1000
+            pub limit: Option<u16>,
+        }
+
+        pub type ResponseBody = FundingHistory;
+    }
+}
@@ -0,0 +1,4 @@
+pub mod account;
+pub mod funding;
+pub mod normal;
+pub mod order;
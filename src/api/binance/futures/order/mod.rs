@@ -0,0 +1,110 @@
+pub mod buy;
+pub mod sell;
+
+pub mod post {
+    pub const PATH: &str = "/binance/futures/order";
+
+    pub mod handler {
+        use std::error::Error;
+
+        use binance::types::Symbol;
+        use plot::trade::position::Position;
+        use plot::trade::{Executor, Trader};
+        use plot::types::Price;
+
+        use crate::api::http::request::Json;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::audit;
+        use crate::services::binance::futures::{
+            client_with_sign, fetch_norms, fetch_price, BinanceFutures,
+        };
+
+        use super::models::{Order, Payload, ResponseBody};
+
+        #[tracing::instrument(skip(c))]
+        pub async fn handler(c: Trip, Json(mut p): Json<Payload>) -> ResponseResult<ResponseBody> {
+            let caller = audit::caller(&p.api_key);
+            let client = client_with_sign(p.api_key, p.secret_key, p.endpoint.as_ref())?;
+
+            let price = fetch_price(&client, &p.symbol).await?;
+            let normal = fetch_norms(&client, &p.symbol).await?;
+
+            let futures_agent =
+                BinanceFutures::new(normal, client, c.audit(), c.risk(), caller, p.reduce_only);
+            let (order, error) = trap(&mut p.positions, &futures_agent, &p.symbol, &price).await;
+
+            let body = ResponseBody {
+                positions: p.positions,
+                order,
+                price,
+                symbol: p.symbol,
+            };
+
+            match error {
+                Some(e) => Ok(Response::refused(e.as_ref(), body)),
+                None => Ok(Response::ok(body)),
+            }
+        }
+
+        async fn trap<T: Trader>(
+            positions: &mut [Position],
+            agent: &T,
+            symbol: &Symbol,
+            price: &Price,
+        ) -> (Vec<Order>, Option<Box<dyn Error>>) {
+            let mut order = Vec::new();
+            for position in positions.iter_mut() {
+                // Stop at the first refusal, the positions before it have
+                // already traded and are kept in the reply.
+                let trades = match position.trap(agent, price).await {
+                    Ok(v) => v,
+                    Err(e) => return (order, Some(e)),
+                };
+                order.push(Order {
+                    order_id: 1,
+                    symbol: symbol.clone(),
+                    trades,
+                });
+            }
+
+            (order, None)
+        }
+    }
+
+    pub mod models {
+        use binance::types::Symbol;
+        use plot::{trade::position::Position, trade::Trade, types::Price};
+        use serde::{Deserialize, Serialize};
+
+        use crate::secret::Secret;
+        use crate::services::binance::Endpoint;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Payload {
+            pub api_key: Secret<String>,
+            pub secret_key: Secret<String>,
+            pub endpoint: Option<Endpoint>,
+            pub symbol: Symbol,
+            pub positions: Vec<Position>,
+            // Every order only reduces the open position
+            #[serde(default)]
+            pub reduce_only: bool,
+        }
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct ResponseBody {
+            pub symbol: Symbol,
+            pub price: Price,
+            pub positions: Vec<Position>,
+            pub order: Vec<Order>,
+        }
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Order {
+            pub order_id: i64,
+            pub symbol: Symbol,
+            pub trades: Vec<Trade>,
+        }
+    }
+}
@@ -0,0 +1,81 @@
+pub mod post {
+    pub const PATH: &str = "/binance/futures/order/sell";
+
+    pub mod handler {
+        use crate::api::http::request::Json;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::audit;
+        use crate::services::binance::futures::{
+            client_with_sign, fetch_norms, fetch_price, BinanceFutures,
+        };
+
+        use super::models::{DryRun, Payload, ResponseBody};
+
+        #[tracing::instrument(skip(c))]
+        pub async fn handler(c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
+            let caller = audit::caller(&p.api_key);
+            let client = client_with_sign(p.api_key, p.secret_key, p.endpoint.as_ref())?;
+
+            let price = fetch_price(&client, &p.symbol).await?;
+            let normal = fetch_norms(&client, &p.symbol).await?;
+            let agent =
+                BinanceFutures::new(normal, client, c.audit(), c.risk(), caller, p.reduce_only);
+
+            if p.dry_run {
+                return Ok(Response::ok(ResponseBody::DryRun(DryRun {
+                    quantity: agent.prepare(&price, &p.base_quantity)?,
+                    symbol: p.symbol,
+                    price,
+                    reduce_only: p.reduce_only,
+                })));
+            }
+
+            let order = agent.market_sell(&price, &p.base_quantity).await?;
+
+            Ok(Response::ok(ResponseBody::Order(order)))
+        }
+    }
+
+    pub mod models {
+        use binance::types::Symbol;
+        use plot::types::{Price, Quantity};
+        use serde::{Deserialize, Serialize};
+
+        use crate::secret::Secret;
+        use crate::services::binance::futures::FuturesOrder;
+        use crate::services::binance::Endpoint;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Payload {
+            pub api_key: Secret<String>,
+            pub secret_key: Secret<String>,
+            pub endpoint: Option<Endpoint>,
+            pub symbol: Symbol,
+            // Contracts
+            pub base_quantity: Quantity,
+            // Only closes a long, exempt from the minimum notional
+            #[serde(default)]
+            pub reduce_only: bool,
+            #[serde(default)]
+            pub dry_run: bool,
+        }
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        #[serde(untagged)]
+        pub enum ResponseBody {
+            Order(FuturesOrder),
+            DryRun(DryRun),
+        }
+
+        /// The order that would be placed.
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct DryRun {
+            pub symbol: Symbol,
+            pub price: Price,
+            // Contracts, corrected to the filters
+            pub quantity: Quantity,
+            pub reduce_only: bool,
+        }
+    }
+}
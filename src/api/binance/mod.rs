@@ -1,13 +1,19 @@
+mod futures;
 mod spot;
 
 pub fn router(state: std::sync::Arc<crate::api::State>) -> axum::Router {
     use axum::routing::{get, post};
     use axum::Router;
 
-    let router_plot = Router::new().route(
-        spot::plot::post::PATH,
-        post(spot::plot::post::handler::handler),
-    );
+    let router_plot = Router::new()
+        .route(
+            spot::plot::post::PATH,
+            post(spot::plot::post::handler::handler),
+        )
+        .route(
+            spot::suggest::post::PATH,
+            post(spot::suggest::post::handler::handler),
+        );
 
     let router_track = Router::new().route(
         spot::track::post::PATH,
@@ -19,6 +25,21 @@ pub fn router(state: std::sync::Arc<crate::api::State>) -> axum::Router {
         get(spot::price::get::handler::handler),
     );
 
+    let router_depth = Router::new().route(
+        spot::depth::get::PATH,
+        get(spot::depth::get::handler::handler),
+    );
+
+    let router_klines = Router::new().route(
+        spot::klines::get::PATH,
+        get(spot::klines::get::handler::handler),
+    );
+
+    let router_filter = Router::new().route(
+        spot::filter::check::get::PATH,
+        get(spot::filter::check::get::handler::handler),
+    );
+
     let router_normal = Router::new().route(
         spot::normal::get::PATH,
         get(spot::normal::get::handler::handler),
@@ -32,6 +53,22 @@ pub fn router(state: std::sync::Arc<crate::api::State>) -> axum::Router {
         .route(
             spot::account::commission::post::PATH,
             post(spot::account::commission::post::handler::handler),
+        )
+        .route(
+            spot::account::dust::post::PATH,
+            post(spot::account::dust::post::handler::handler),
+        )
+        .route(
+            spot::account::pnl::post::PATH,
+            post(spot::account::pnl::post::handler::handler),
+        )
+        .route(
+            spot::account::portfolio::post::PATH,
+            post(spot::account::portfolio::post::handler::handler),
+        )
+        .route(
+            spot::account::tax::post::PATH,
+            post(spot::account::tax::post::handler::handler),
         );
 
     let router_order = Router::new()
@@ -54,10 +91,76 @@ pub fn router(state: std::sync::Arc<crate::api::State>) -> axum::Router {
         .route(
             spot::order::trades::post::PATH,
             post(spot::order::trades::post::handler::handler),
+        )
+        .route(
+            spot::order::sync::post::PATH,
+            post(spot::order::sync::post::handler::handler),
+        )
+        .route(
+            spot::order::history::post::PATH,
+            post(spot::order::history::post::handler::handler),
+        )
+        .route(
+            spot::order::execution::post::PATH,
+            post(spot::order::execution::post::handler::handler),
+        )
+        .route(
+            spot::order::execution::status::PATH,
+            post(spot::order::execution::status::handler::handler),
+        )
+        .route(
+            spot::order::execution::cancel::PATH,
+            post(spot::order::execution::cancel::handler::handler),
+        );
+
+    let router_halt = Router::new().route(
+        spot::halt::PATH,
+        get(spot::halt::get::handler::handler)
+            .post(spot::halt::post::handler::handler)
+            .delete(spot::halt::delete::handler::handler),
+    );
+
+    let router_futures = Router::new()
+        .route(
+            futures::normal::get::PATH,
+            get(futures::normal::get::handler::handler),
+        )
+        .route(
+            futures::funding::get::PATH,
+            get(futures::funding::get::handler::handler),
+        )
+        .route(
+            futures::account::leverage::post::PATH,
+            post(futures::account::leverage::post::handler::handler),
+        )
+        .route(
+            futures::account::margin::post::PATH,
+            post(futures::account::margin::post::handler::handler),
+        )
+        .route(
+            futures::account::position::post::PATH,
+            post(futures::account::position::post::handler::handler),
+        )
+        .route(
+            futures::order::post::PATH,
+            post(futures::order::post::handler::handler),
+        )
+        .route(
+            futures::order::buy::post::PATH,
+            post(futures::order::buy::post::handler::handler),
+        )
+        .route(
+            futures::order::sell::post::PATH,
+            post(futures::order::sell::post::handler::handler),
         );
 
     Router::new()
         .merge(router_account)
+        .merge(router_depth)
+        .merge(router_filter)
+        .merge(router_futures)
+        .merge(router_halt)
+        .merge(router_klines)
         .merge(router_normal)
         .merge(router_track)
         .merge(router_order)
@@ -5,20 +5,21 @@ pub mod post {
         use crate::api::http::request::Json;
         use crate::api::http::response::{Response, ResponseResult};
         use crate::api::http::trip::Trip;
+        use crate::retry;
         use crate::services::binance::client_with_sign;
 
         use super::models::{Payload, ResponseBody};
 
         #[tracing::instrument(skip(_c))]
         pub async fn handler(_c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
-            let client = client_with_sign(p.api_key, p.secret_key)?;
+            let client = client_with_sign(p.api_key, p.secret_key, p.endpoint.as_ref())?;
 
             let asset = match &p.asset {
                 Some(v) => Some(v),
                 None => None,
             };
 
-            let result = client.user_asset(asset, Some(false), None).await?;
+            let result = retry::read(|| client.user_asset(asset, Some(false), None)).await?;
 
             Ok(Response::ok(result))
         }
@@ -28,10 +29,14 @@ pub mod post {
         use binance::types::{Asset, UserAsset};
         use serde::{Deserialize, Serialize};
 
+        use crate::secret::Secret;
+        use crate::services::binance::Endpoint;
+
         #[derive(Debug, Clone, Serialize, Deserialize)]
         pub struct Payload {
-            pub api_key: String,
-            pub secret_key: String,
+            pub api_key: Secret<String>,
+            pub secret_key: Secret<String>,
+            pub endpoint: Option<Endpoint>,
             pub asset: Option<Asset>,
         }
 
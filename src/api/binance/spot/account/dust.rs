@@ -0,0 +1,105 @@
+pub mod post {
+    pub const PATH: &str = "/binance/spot/account/dust";
+
+    /// Quote asset whose pairs decide what is dust when none is given.
+    const DEFAULT_QUOTE: &str = "USDT";
+
+    pub mod handler {
+        use crate::api::http::request::Json;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::audit::{self, Action, Entry};
+        use crate::services::binance::client_with_sign;
+        use crate::services::binance::dust::{convert, DustReport};
+
+        use super::models::{Payload, ResponseBody};
+        use super::DEFAULT_QUOTE;
+
+        #[tracing::instrument(skip(c))]
+        pub async fn handler(c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
+            let caller = audit::caller(&p.api_key);
+            let client = client_with_sign(p.api_key, p.secret_key, p.endpoint.as_ref())?;
+
+            let quote = p.quote.unwrap_or(DEFAULT_QUOTE.into()).to_uppercase();
+            let report = DustReport::fetch(&client, &quote).await?;
+
+            // Only balances found to be dust are ever converted
+            let mut assets = report.assets();
+            if let Some(only) = &p.assets {
+                if let Some(v) = only.iter().find(|v| !assets.contains(v)) {
+                    return Err(Response::bad_request(format!("{} is not dust", v)));
+                }
+                assets.retain(|v| only.contains(v));
+            }
+
+            if !p.convert || p.dry_run || assets.is_empty() {
+                let converting = match p.convert {
+                    true => assets,
+                    false => Vec::new(),
+                };
+
+                return Ok(Response::ok(ResponseBody {
+                    report,
+                    converting,
+                    transfer: None,
+                    dry_run: p.dry_run,
+                }));
+            }
+
+            // A halt of the account or of a dust pair refuses the conversion
+            // as it would refuse selling the balance
+            for v in report.balances.iter().filter(|v| assets.contains(&v.asset)) {
+                if let Err(e) = c.risk().halts().check(&caller, &v.symbol) {
+                    return Err(Response::forbidden(e.to_string()));
+                }
+            }
+
+            let result = convert(&client, &assets).await;
+
+            let payload = serde_json::json!({ "quote": quote, "assets": assets });
+            let entry = Entry::new(Action::DustTransfer, caller, payload, None, &result);
+            c.audit().record(entry);
+
+            Ok(Response::ok(ResponseBody {
+                report,
+                converting: assets,
+                transfer: Some(result?),
+                dry_run: false,
+            }))
+        }
+    }
+
+    pub mod models {
+        use binance::types::Asset;
+        use serde::{Deserialize, Serialize};
+
+        use crate::secret::Secret;
+        use crate::services::binance::dust::DustReport;
+        use crate::services::binance::Endpoint;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Payload {
+            pub api_key: Secret<String>,
+            pub secret_key: Secret<String>,
+            pub endpoint: Option<Endpoint>,
+            pub quote: Option<Asset>,
+            // Converts the dust into BNB, only listing it when unset
+            #[serde(default)]
+            pub convert: bool,
+            // Converts only these of the dust balances
+            pub assets: Option<Vec<Asset>>,
+            #[serde(default)]
+            pub dry_run: bool,
+        }
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct ResponseBody {
+            pub report: DustReport,
+            // Assets converted, or that would be in a dry run
+            pub converting: Vec<Asset>,
+            // The exchange's answer to the dust transfer
+            pub transfer: Option<serde_json::Value>,
+            pub dry_run: bool,
+        }
+    }
+}
@@ -1,2 +1,6 @@
 pub mod asset;
 pub mod commission;
+pub mod dust;
+pub mod pnl;
+pub mod portfolio;
+pub mod tax;
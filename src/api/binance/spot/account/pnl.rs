@@ -0,0 +1,49 @@
+pub mod post {
+    pub const PATH: &str = "/binance/spot/account/pnl";
+
+    pub mod handler {
+        use crate::api::http::request::Json;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::audit;
+        use crate::services::binance::client_with_sign;
+        use crate::services::binance::market::fetch_normal;
+        use crate::services::binance::pnl::PnlLedger;
+
+        use super::models::{Payload, ResponseBody};
+
+        #[tracing::instrument(skip(c))]
+        pub async fn handler(c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
+            let caller = audit::caller(&p.api_key);
+            let client = client_with_sign(p.api_key, p.secret_key, p.endpoint.as_ref())?;
+
+            let normal = fetch_normal(&client, &p.symbol).await?;
+
+            let result =
+                PnlLedger::fetch(&client, &c.history(), &caller, &normal, p.method).await?;
+
+            Ok(Response::ok(result))
+        }
+    }
+
+    pub mod models {
+        use binance::types::Symbol;
+        use serde::{Deserialize, Serialize};
+
+        use crate::secret::Secret;
+        use crate::services::binance::pnl::{CostMethod, PnlReport};
+        use crate::services::binance::Endpoint;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Payload {
+            pub api_key: Secret<String>,
+            pub secret_key: Secret<String>,
+            pub endpoint: Option<Endpoint>,
+            pub symbol: Symbol,
+            #[serde(default)]
+            pub method: CostMethod,
+        }
+
+        pub type ResponseBody = PnlReport;
+    }
+}
@@ -0,0 +1,46 @@
+pub mod post {
+    pub const PATH: &str = "/binance/spot/account/portfolio";
+
+    /// Quote asset of the valuation when none is given.
+    const DEFAULT_QUOTE: &str = "USDT";
+
+    pub mod handler {
+        use crate::api::http::request::Json;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::services::binance::client_with_sign;
+        use crate::services::binance::portfolio::Portfolio;
+
+        use super::models::{Payload, ResponseBody};
+        use super::DEFAULT_QUOTE;
+
+        #[tracing::instrument(skip(_c))]
+        pub async fn handler(_c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
+            let client = client_with_sign(p.api_key, p.secret_key, p.endpoint.as_ref())?;
+
+            let quote = p.quote.unwrap_or(DEFAULT_QUOTE.into()).to_uppercase();
+            let result = Portfolio::fetch(&client, &quote).await?;
+
+            Ok(Response::ok(result))
+        }
+    }
+
+    pub mod models {
+        use binance::types::Asset;
+        use serde::{Deserialize, Serialize};
+
+        use crate::secret::Secret;
+        use crate::services::binance::portfolio::Portfolio;
+        use crate::services::binance::Endpoint;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Payload {
+            pub api_key: Secret<String>,
+            pub secret_key: Secret<String>,
+            pub endpoint: Option<Endpoint>,
+            pub quote: Option<Asset>,
+        }
+
+        pub type ResponseBody = Portfolio;
+    }
+}
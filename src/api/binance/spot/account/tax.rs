@@ -0,0 +1,68 @@
+pub mod post {
+    pub const PATH: &str = "/binance/spot/account/tax";
+
+    pub mod handler {
+        use crate::api::http::request::Json;
+        use crate::api::http::response::{Csv, Response};
+        use crate::api::http::trip::Trip;
+        use crate::audit;
+        use crate::retry;
+        use crate::services::binance::client_with_sign;
+        use crate::services::binance::tax::{fetch_disposals, to_csv};
+
+        use super::models::Payload;
+
+        #[tracing::instrument(skip(c))]
+        pub async fn handler(c: Trip, Json(p): Json<Payload>) -> Result<Csv, Response<()>> {
+            let caller = audit::caller(&p.api_key);
+            let client = client_with_sign(p.api_key, p.secret_key, p.endpoint.as_ref())?;
+
+            let mut disposals = Vec::new();
+            for symbol in p.symbols.iter() {
+                let normal = {
+                    let mut info = retry::read(|| client.exchange_info(symbol)).await?;
+                    match info.symbols.pop() {
+                        Some(v) => v,
+                        None => {
+                            return Err(Response::bad_request("exchange info not found".into()))
+                        }
+                    }
+                };
+
+                disposals.extend(
+                    fetch_disposals(&client, &c.history(), &caller, &normal, p.method).await?,
+                );
+            }
+
+            // Lots are matched over the whole history, only the range is exported
+            disposals.retain(|v| {
+                p.start_time.is_none_or(|t| v.disposed_time >= t)
+                    && p.end_time.is_none_or(|t| v.disposed_time <= t)
+            });
+            disposals.sort_by_key(|v| v.disposed_time);
+
+            Ok(Csv::new("tax_lots.csv", to_csv(&disposals)))
+        }
+    }
+
+    pub mod models {
+        use binance::types::Symbol;
+        use serde::{Deserialize, Serialize};
+
+        use crate::secret::Secret;
+        use crate::services::binance::tax::LotMethod;
+        use crate::services::binance::Endpoint;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Payload {
+            pub api_key: Secret<String>,
+            pub secret_key: Secret<String>,
+            pub endpoint: Option<Endpoint>,
+            pub symbols: Vec<Symbol>,
+            #[serde(default)]
+            pub method: LotMethod,
+            pub start_time: Option<u128>,
+            pub end_time: Option<u128>,
+        }
+    }
+}
@@ -0,0 +1,56 @@
+pub mod get {
+    pub const PATH: &str = "/binance/spot/depth";
+
+    pub mod handler {
+        use crate::api::http::request::Query;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::services::binance::client;
+        use crate::services::binance::depth::{OrderBook, DEFAULT_DEPTH_LIMIT};
+
+        use super::models::{Params, ResponseBody};
+
+        #[tracing::instrument(skip(_c))]
+        pub async fn handler(_c: Trip, Query(q): Query<Params>) -> ResponseResult<ResponseBody> {
+            let client = client()?;
+
+            let limit = q.limit.unwrap_or(DEFAULT_DEPTH_LIMIT);
+            let book = OrderBook::fetch(&client, &q.symbol, limit).await?;
+
+            let estimate = match (q.side, q.quantity) {
+                (Some(side), Some(quantity)) => Some(book.estimate(side, &quantity)),
+                (None, None) => None,
+                _ => {
+                    return Err(Response::bad_request(
+                        "side and quantity must be given together".into(),
+                    ))
+                }
+            };
+
+            Ok(Response::ok(ResponseBody { book, estimate }))
+        }
+    }
+
+    pub mod models {
+        use binance::types::{OrderSide, Symbol};
+        use plot::types::Quantity;
+        use serde::{Deserialize, Serialize};
+
+        use crate::services::binance::depth::{OrderBook, SlippageEstimate};
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Params {
+            pub symbol: Symbol,
+            pub limit: Option<u16>,
+            // Estimates a market order when given, quote for a buy and base for a sell
+            pub side: Option<OrderSide>,
+            pub quantity: Option<Quantity>,
+        }
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct ResponseBody {
+            pub book: OrderBook,
+            pub estimate: Option<SlippageEstimate>,
+        }
+    }
+}
@@ -0,0 +1,56 @@
+pub mod get {
+    pub const PATH: &str = "/binance/spot/filter/check";
+
+    pub mod handler {
+        use crate::api::http::request::Query;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::services::binance::client;
+        use crate::services::binance::filter::check::{check, QuantityKind};
+        use crate::services::binance::market::{fetch_normal, fetch_price};
+
+        use super::models::{Params, ResponseBody};
+
+        #[tracing::instrument(skip(_c))]
+        pub async fn handler(_c: Trip, Query(q): Query<Params>) -> ResponseResult<ResponseBody> {
+            let (quantity, kind) = match (q.base_quantity, q.quote_quantity) {
+                (Some(v), None) => (v, QuantityKind::Base),
+                (None, Some(v)) => (v, QuantityKind::Quote),
+                _ => {
+                    return Err(Response::bad_request(
+                        "exactly one of base_quantity and quote_quantity must be given".into(),
+                    ))
+                }
+            };
+
+            let client = client()?;
+
+            let price = match q.price {
+                Some(v) => v,
+                None => fetch_price(&client, &q.symbol).await?,
+            };
+            let normal = fetch_normal(&client, &q.symbol).await?;
+
+            Ok(Response::ok(check(&normal, &price, &quantity, kind)))
+        }
+    }
+
+    pub mod models {
+        use binance::types::Symbol;
+        use plot::types::{Price, Quantity};
+        use serde::{Deserialize, Serialize};
+
+        use crate::services::binance::filter::check::FilterReport;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Params {
+            pub symbol: Symbol,
+            // The current price when unset
+            pub price: Option<Price>,
+            pub base_quantity: Option<Quantity>,
+            pub quote_quantity: Option<Quantity>,
+        }
+
+        pub type ResponseBody = FilterReport;
+    }
+}
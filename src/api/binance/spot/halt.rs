@@ -0,0 +1,282 @@
+pub const PATH: &str = "/binance/spot/halt";
+
+const ADMIN_CALLER: &str = "admin";
+
+pub mod get {
+    pub mod handler {
+        use crate::api::http::request::Admin;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+
+        use super::models::ResponseBody;
+
+        #[tracing::instrument(skip(c, _admin))]
+        pub async fn handler(c: Trip, _admin: Admin) -> ResponseResult<ResponseBody> {
+            Ok(Response::ok(c.risk().halts().list()))
+        }
+    }
+
+    pub mod models {
+        use crate::services::binance::halt::Halt;
+
+        pub type ResponseBody = Vec<Halt>;
+    }
+}
+
+pub mod post {
+    pub mod handler {
+        use std::error::Error;
+        use std::str::FromStr;
+
+        use binance::prelude::Client;
+        use binance::types::{OrderInfo, OrderResponseFull, OrderSide, Symbol};
+        use plot::types::Decimal;
+
+        use crate::api::http::request::{Admin, Json};
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::audit::{self, Action, Entry};
+        use crate::retry;
+        use crate::services::binance::dry_run::SimulatedBalance;
+        use crate::services::binance::halt::Halt;
+        use crate::services::binance::{client_with_sign, ConvertFilter};
+        use crate::time::timestamp;
+
+        use super::super::ADMIN_CALLER;
+        use super::models::{Payload, ResponseBody};
+
+        #[tracing::instrument(skip(c, _admin))]
+        pub async fn handler(
+            c: Trip,
+            _admin: Admin,
+            Json(p): Json<Payload>,
+        ) -> ResponseResult<ResponseBody> {
+            let payload = serde_json::to_value(&p).unwrap_or_default();
+            let account = match &p.api_key {
+                Some(v) => Some(audit::caller(v)),
+                None => p.account,
+            };
+
+            if p.flatten && p.symbol.is_none() {
+                return Err(Response::bad_request("flatten requires a symbol".into()));
+            }
+
+            let client = match (p.api_key, p.secret_key) {
+                (Some(api_key), Some(secret_key)) => {
+                    Some(client_with_sign(api_key, secret_key, p.endpoint.as_ref())?)
+                }
+                _ if p.cancel_orders || p.flatten => {
+                    return Err(Response::bad_request(
+                        "api_key and secret_key are required to cancel orders or flatten".into(),
+                    ))
+                }
+                _ => None,
+            };
+
+            // Halt first, so nothing else trades while orders are being unwound
+            let halt = Halt {
+                account,
+                symbol: p.symbol,
+                reason: p.reason,
+                since: timestamp().as_millis(),
+            };
+            let result = c.risk().halts().halt(halt.clone());
+            let entry = Entry::new(Action::Halt, ADMIN_CALLER.into(), payload, None, &result);
+            c.audit().record(entry);
+            result.map_err(|e| Response::bad_request(e.to_string()))?;
+
+            let mut cancelled = Vec::new();
+            let mut flattened = None;
+            if let Some(client) = client {
+                if p.cancel_orders {
+                    cancelled = cancel_open_orders(&c, &client, halt.symbol.as_ref()).await?;
+                }
+
+                if let (true, Some(symbol)) = (p.flatten, &halt.symbol) {
+                    flattened = flatten(&c, &client, symbol).await?;
+                }
+            }
+
+            Ok(Response::ok(ResponseBody {
+                halt,
+                cancelled,
+                flattened,
+            }))
+        }
+
+        async fn cancel_open_orders(
+            c: &Trip,
+            client: &Client,
+            symbol: Option<&Symbol>,
+        ) -> Result<Vec<OrderInfo>, Box<dyn Error>> {
+            let symbols = match symbol {
+                Some(v) => vec![v.clone()],
+                None => {
+                    let mut symbols: Vec<Symbol> =
+                        retry::read(|| client.spot_open_orders(None, None))
+                            .await?
+                            .into_iter()
+                            .map(|v| v.symbol)
+                            .collect();
+                    symbols.sort();
+                    symbols.dedup();
+
+                    symbols
+                }
+            };
+
+            let mut cancelled = Vec::new();
+            for symbol in symbols.iter() {
+                let result = client.spot_cancel_open_orders(symbol, None).await;
+
+                let payload = serde_json::json!({ "symbol": symbol, "halt": true });
+                let entry = Entry::new(
+                    Action::SpotCancel,
+                    ADMIN_CALLER.into(),
+                    payload,
+                    None,
+                    &result,
+                );
+                c.audit().record(entry);
+
+                cancelled.extend(result?);
+            }
+
+            Ok(cancelled)
+        }
+
+        /// Sells the whole free base balance of `symbol` at market.
+        async fn flatten(
+            c: &Trip,
+            client: &Client,
+            symbol: &Symbol,
+        ) -> Result<Option<OrderResponseFull>, Box<dyn Error>> {
+            let price = Decimal::from_str(&retry::read(|| client.price(symbol)).await?.price)?;
+            let normal = match retry::read(|| client.exchange_info(symbol))
+                .await?
+                .symbols
+                .pop()
+            {
+                Some(v) => v,
+                None => return Err("exchange info not found".into()),
+            };
+
+            let balance = SimulatedBalance::fetch(client, &normal).await?;
+            if balance.base.is_zero() {
+                return Ok(None);
+            }
+
+            let quantity = normal.correct_base_quantity(&price, &balance.base)?;
+            normal.filter_base_quantity(&price, &quantity)?;
+
+            // Bypasses the risk checks on purpose, the halt would refuse it
+            let client_order_id = crate::trace::client_order_id();
+            let result = client
+                .spot_market_order_with_base(
+                    symbol,
+                    OrderSide::Sell,
+                    &quantity.to_string(),
+                    client_order_id.as_deref(),
+                )
+                .await;
+
+            let payload = serde_json::json!({
+                "symbol": symbol,
+                "price": price,
+                "base_quantity": balance.base,
+                "flatten": true,
+            });
+            let corrected_quantity = Some(quantity.to_string());
+            let entry = Entry::new(
+                Action::SpotSell,
+                ADMIN_CALLER.into(),
+                payload,
+                corrected_quantity,
+                &result,
+            );
+            c.audit().record(entry);
+
+            Ok(Some(result?))
+        }
+    }
+
+    pub mod models {
+        use binance::types::{OrderInfo, OrderResponseFull, Symbol};
+        use serde::{Deserialize, Serialize};
+
+        use crate::secret::Secret;
+        use crate::services::binance::halt::Halt;
+        use crate::services::binance::Endpoint;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Payload {
+            // Account to halt, as the caller id of the audit log. Derived
+            // from `api_key` when given, both unset halts every account
+            pub account: Option<String>,
+            pub api_key: Option<Secret<String>>,
+            pub secret_key: Option<Secret<String>>,
+            pub endpoint: Option<Endpoint>,
+            pub symbol: Option<Symbol>,
+            pub reason: Option<String>,
+            #[serde(default)]
+            pub cancel_orders: bool,
+            #[serde(default)]
+            pub flatten: bool,
+        }
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct ResponseBody {
+            pub halt: Halt,
+            pub cancelled: Vec<OrderInfo>,
+            pub flattened: Option<OrderResponseFull>,
+        }
+    }
+}
+
+pub mod delete {
+    pub mod handler {
+        use crate::api::http::request::{Admin, Query};
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::audit::{Action, Entry};
+
+        use super::super::ADMIN_CALLER;
+        use super::models::{Params, ResponseBody};
+
+        #[tracing::instrument(skip(c, _admin))]
+        pub async fn handler(
+            c: Trip,
+            _admin: Admin,
+            Query(q): Query<Params>,
+        ) -> ResponseResult<ResponseBody> {
+            let payload = serde_json::to_value(&q).unwrap_or_default();
+            let result = c
+                .risk()
+                .halts()
+                .resume(q.account.as_deref(), q.symbol.as_ref());
+
+            let entry = Entry::new(Action::Resume, ADMIN_CALLER.into(), payload, None, &result);
+            c.audit().record(entry);
+
+            match result {
+                Ok(v) => Ok(Response::ok(v)),
+                Err(e) => Err(Response::bad_request(e.to_string())),
+            }
+        }
+    }
+
+    pub mod models {
+        use binance::types::Symbol;
+        use serde::{Deserialize, Serialize};
+
+        use crate::services::binance::halt::Halt;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Params {
+            pub account: Option<String>,
+            pub symbol: Option<Symbol>,
+        }
+
+        pub type ResponseBody = Vec<Halt>;
+    }
+}
@@ -0,0 +1,73 @@
+pub mod get {
+    pub const PATH: &str = "/binance/spot/klines";
+
+    pub mod handler {
+        use crate::api::http::request::Query;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::services::binance::client;
+        use crate::services::binance::indicator::Indicator;
+        use crate::services::binance::klines::fetch_candles;
+
+        use super::models::{Params, ResponseBody};
+
+        #[tracing::instrument(skip(_c))]
+        pub async fn handler(_c: Trip, Query(q): Query<Params>) -> ResponseResult<ResponseBody> {
+            let indicators = match q.indicators.as_deref().map(Indicator::parse_list) {
+                Some(Ok(v)) => v,
+                Some(Err(e)) => return Err(Response::bad_request(e)),
+                None => Vec::new(),
+            };
+
+            let client = client()?;
+            let candles = fetch_candles(
+                &client,
+                &q.symbol,
+                &q.interval,
+                q.start_time,
+                q.end_time,
+                q.limit,
+            )
+            .await?;
+
+            let indicators = indicators
+                .iter()
+                .flat_map(|v| v.compute(&candles))
+                .collect();
+
+            Ok(Response::ok(ResponseBody {
+                symbol: q.symbol,
+                interval: q.interval,
+                candles,
+                indicators,
+            }))
+        }
+    }
+
+    pub mod models {
+        use binance::types::Symbol;
+        use serde::{Deserialize, Serialize};
+
+        use crate::services::binance::indicator::Series;
+        use crate::services::binance::klines::Candle;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Params {
+            pub symbol: Symbol,
+            pub interval: String,
+            pub start_time: Option<u128>,
+            pub end_time: Option<u128>,
+            pub limit: Option<u16>,
+            // Comma separated, such as `sma:20,rsi:14,bollinger:20:2`
+            pub indicators: Option<String>,
+        }
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct ResponseBody {
+            pub symbol: Symbol,
+            pub interval: String,
+            pub candles: Vec<Candle>,
+            pub indicators: Vec<Series>,
+        }
+    }
+}
@@ -1,6 +1,11 @@
 pub mod account;
+pub mod depth;
+pub mod filter;
+pub mod halt;
+pub mod klines;
 pub mod normal;
 pub mod order;
 pub mod plot;
 pub mod price;
+pub mod suggest;
 pub mod track;
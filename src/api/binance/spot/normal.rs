@@ -5,26 +5,37 @@ pub mod get {
         use crate::api::http::request::Query;
         use crate::api::http::response::{Response, ResponseResult};
         use crate::api::http::trip::Trip;
+        use crate::retry;
         use crate::services::binance::client;
+        use crate::services::binance::market::{fetch_exchange_info, Selection};
 
         use super::models::{Params, ResponseBody};
 
         #[tracing::instrument(skip(_c))]
         pub async fn handler(_c: Trip, Query(p): Query<Params>) -> ResponseResult<ResponseBody> {
             let client = client()?;
-            let result = client.exchange_info(&p.symbol).await?;
+
+            let selection = Selection::new(p.symbol, p.symbols.as_deref(), p.quote);
+            let result = match (selection.symbols.as_slice(), &selection.quote) {
+                ([symbol], None) => retry::read(|| client.exchange_info(symbol)).await?,
+                _ => fetch_exchange_info(&client, &selection).await?,
+            };
 
             Ok(Response::ok(result))
         }
     }
 
     pub mod models {
-        use binance::types::{ExchangeInfo, Symbol};
+        use binance::types::{Asset, ExchangeInfo, Symbol};
         use serde::{Deserialize, Serialize};
 
         #[derive(Debug, Clone, Serialize, Deserialize)]
         pub struct Params {
-            pub symbol: Symbol,
+            pub symbol: Option<Symbol>,
+            // Comma separated, e.g. `BTCUSDT,ETHUSDT`
+            pub symbols: Option<String>,
+            // Every pair quoted in this asset, e.g. `USDT`
+            pub quote: Option<Asset>,
         }
 
         pub type ResponseBody = ExchangeInfo;
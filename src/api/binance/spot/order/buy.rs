@@ -2,45 +2,127 @@ pub mod post {
     pub const PATH: &str = "/binance/spot/order/buy";
 
     pub mod handler {
+        use axum::http::HeaderMap;
         use binance::types::OrderSide;
+        use plot::trade::Trader;
 
         use crate::api::http::request::Json;
         use crate::api::http::response::{Response, ResponseResult};
         use crate::api::http::trip::Trip;
-        use crate::services::binance::client_with_sign;
+        use crate::audit;
+        use crate::idempotency::{self, Begin};
+        use crate::services::binance::depth::check_slippage;
+        use crate::services::binance::dry_run::{
+            default_commission, BinanceSpotDryRun, SimulatedBalance,
+        };
+        use crate::services::binance::market::{fetch_normal_with_limits, fetch_price};
+        use crate::services::binance::{client_with_sign, find_order, BinanceSpot};
 
         use super::models::{Payload, ResponseBody};
 
-        #[tracing::instrument(skip(_c))]
-        pub async fn handler(_c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
-            let client = client_with_sign(p.api_key, p.secret_key)?;
+        #[tracing::instrument(skip(c, headers))]
+        pub async fn handler(
+            c: Trip,
+            headers: HeaderMap,
+            Json(p): Json<Payload>,
+        ) -> ResponseResult<ResponseBody> {
+            let caller = audit::caller(&p.api_key);
+            let payload = serde_json::to_value(&p).unwrap_or_default();
+            let client = client_with_sign(p.api_key, p.secret_key, p.endpoint.as_ref())?;
 
-            let result = client
-                .spot_market_order_with_quote(
-                    &p.symbol,
-                    OrderSide::Buy,
-                    &p.quote_quantity.to_string(),
-                    None,
-                )
-                .await?;
+            let price = fetch_price(&client, &p.symbol).await?;
+            let (normal, limits) = fetch_normal_with_limits(&client, &p.symbol).await?;
 
-            Ok(Response::ok(result))
+            if p.dry_run {
+                if let Some(max) = &p.max_slippage {
+                    check_slippage(&client, &p.symbol, OrderSide::Buy, &p.quote_quantity, max)
+                        .await?;
+                }
+
+                let commission = p.commission.unwrap_or_else(default_commission);
+                let balance = SimulatedBalance::fetch(&client, &normal).await?;
+                let agent = BinanceSpotDryRun::new(normal, commission, balance);
+                agent.buy(&price, &p.quote_quantity).await?;
+
+                return Ok(Response::ok(ResponseBody::DryRun(agent.report())));
+            }
+
+            let key = idempotency::key(&headers)?;
+
+            let mut attempt = None;
+            let client_order_id = match &key {
+                Some(key) => {
+                    let fingerprint = idempotency::fingerprint(&payload);
+                    let claim = match c.idempotency().begin(&caller, key, &fingerprint)? {
+                        Begin::Attempt(v) => v,
+                        // Only orders placed or found are kept, never refusals
+                        Begin::Done(v) => return Ok(Response::ok(ResponseBody::Replay(v.body))),
+                    };
+
+                    // An earlier attempt may have reached the exchange and lost the response
+                    let client_order_id = idempotency::client_order_id(&caller, key);
+                    if let Some(order) = find_order(&client, &p.symbol, &client_order_id).await? {
+                        let response = Response::ok(ResponseBody::Status(order));
+                        claim.finish(response.outcome());
+                        return Ok(response);
+                    }
+                    attempt = Some(claim);
+
+                    Some(client_order_id)
+                }
+                None => crate::trace::client_order_id(),
+            };
+
+            let agent =
+                BinanceSpot::new(normal, limits, client, c.audit(), c.risk(), caller.clone())
+                    .with_max_slippage(p.max_slippage);
+            let result = agent
+                .market_buy_with_client_order_id(&price, &p.quote_quantity, client_order_id)
+                .await;
+
+            // A failed attempt is released unfinished and may be retried
+            let response = Response::ok(ResponseBody::Order(result?));
+            if let Some(attempt) = attempt {
+                attempt.finish(response.outcome());
+            }
+
+            Ok(response)
         }
     }
 
     pub mod models {
-        use binance::types::{OrderResponseFull, Symbol};
-        use plot::types::Quantity;
+        use binance::types::{OrderInfo, OrderResponseFull, Symbol};
+        use plot::types::{Decimal, Quantity};
         use serde::{Deserialize, Serialize};
 
+        use crate::secret::Secret;
+        use crate::services::binance::dry_run::DryRunReport;
+        use crate::services::binance::Endpoint;
+
         #[derive(Debug, Clone, Serialize, Deserialize)]
         pub struct Payload {
-            pub api_key: String,
-            pub secret_key: String,
+            pub api_key: Secret<String>,
+            pub secret_key: Secret<String>,
+            pub endpoint: Option<Endpoint>,
             pub symbol: Symbol,
             pub quote_quantity: Quantity,
+            // Largest estimated price impact in percent, unchecked when unset
+            pub max_slippage: Option<Decimal>,
+            #[serde(default)]
+            pub dry_run: bool,
+            // Only used to simulate fills in a dry run
+            pub commission: Option<Decimal>,
         }
 
-        pub type ResponseBody = OrderResponseFull;
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        #[serde(untagged)]
+        pub enum ResponseBody {
+            Order(OrderResponseFull),
+            // An order found by its idempotency key instead of placed again
+            Status(OrderInfo),
+            DryRun(DryRunReport),
+            // The stored response of an earlier attempt with the same idempotency key
+            Replay(serde_json::Value),
+        }
     }
 }
@@ -0,0 +1,147 @@
+pub mod post {
+    pub const PATH: &str = "/binance/spot/order/execution";
+
+    pub mod handler {
+        use crate::api::http::request::Json;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::audit;
+        use crate::services::binance::market::fetch_normal_with_limits;
+        use crate::services::binance::{client_with_sign, BinanceSpot};
+
+        use super::models::{Payload, ResponseBody};
+
+        #[tracing::instrument(skip(c))]
+        pub async fn handler(c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
+            let caller = audit::caller(&p.api_key);
+            let client = client_with_sign(p.api_key, p.secret_key, p.endpoint.as_ref())?;
+
+            let (normal, limits) = fetch_normal_with_limits(&client, &p.symbol).await?;
+
+            let agent =
+                BinanceSpot::new(normal, limits, client, c.audit(), c.risk(), caller.clone());
+            match c
+                .executions()
+                .start(agent, caller, p.side, p.quantity, p.schedule)
+            {
+                Ok(v) => Ok(Response::ok(v)),
+                Err(e) => Err(Response::bad_request(e)),
+            }
+        }
+    }
+
+    pub mod models {
+        use binance::types::{OrderSide, Symbol};
+        use plot::types::Quantity;
+        use serde::{Deserialize, Serialize};
+
+        use crate::secret::Secret;
+        use crate::services::binance::execution::{Execution, Schedule};
+        use crate::services::binance::Endpoint;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Payload {
+            pub api_key: Secret<String>,
+            pub secret_key: Secret<String>,
+            pub endpoint: Option<Endpoint>,
+            pub symbol: Symbol,
+            pub side: OrderSide,
+            // Quote quantity to spend on a buy, base quantity to sell on a sell
+            pub quantity: Quantity,
+            pub schedule: Schedule,
+        }
+
+        pub type ResponseBody = Execution;
+    }
+}
+
+pub mod status {
+    pub const PATH: &str = "/binance/spot/order/execution/status";
+
+    pub mod handler {
+        use crate::api::http::request::Json;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::audit;
+        use crate::services::binance::{authenticate, client_with_sign};
+
+        use super::models::{Payload, ResponseBody};
+
+        #[tracing::instrument(skip(c))]
+        pub async fn handler(c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
+            let caller = audit::caller(&p.api_key);
+            let client = client_with_sign(p.api_key, p.secret_key, p.endpoint.as_ref())?;
+            // The API key alone is not secret, the caller must also sign
+            authenticate(&client).await?;
+
+            match c.executions().get(&caller, &p.id) {
+                Some(v) => Ok(Response::ok(v)),
+                None => Err(Response::bad_request("execution not found".into())),
+            }
+        }
+    }
+
+    pub mod models {
+        use serde::{Deserialize, Serialize};
+
+        use crate::secret::Secret;
+        use crate::services::binance::execution::Execution;
+        use crate::services::binance::Endpoint;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Payload {
+            pub api_key: Secret<String>,
+            pub secret_key: Secret<String>,
+            pub endpoint: Option<Endpoint>,
+            pub id: String,
+        }
+
+        pub type ResponseBody = Execution;
+    }
+}
+
+pub mod cancel {
+    pub const PATH: &str = "/binance/spot/order/execution/cancel";
+
+    pub mod handler {
+        use crate::api::http::request::Json;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::audit;
+        use crate::services::binance::{authenticate, client_with_sign};
+
+        use super::models::{Payload, ResponseBody};
+
+        #[tracing::instrument(skip(c))]
+        pub async fn handler(c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
+            let caller = audit::caller(&p.api_key);
+            let client = client_with_sign(p.api_key, p.secret_key, p.endpoint.as_ref())?;
+            // The API key alone is not secret, the caller must also sign
+            authenticate(&client).await?;
+
+            // Stops before the next child order, a child already sent still fills
+            match c.executions().cancel(&caller, &p.id) {
+                Some(v) => Ok(Response::ok(v)),
+                None => Err(Response::bad_request("execution not found".into())),
+            }
+        }
+    }
+
+    pub mod models {
+        use serde::{Deserialize, Serialize};
+
+        use crate::secret::Secret;
+        use crate::services::binance::execution::Execution;
+        use crate::services::binance::Endpoint;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Payload {
+            pub api_key: Secret<String>,
+            pub secret_key: Secret<String>,
+            pub endpoint: Option<Endpoint>,
+            pub id: String,
+        }
+
+        pub type ResponseBody = Execution;
+    }
+}
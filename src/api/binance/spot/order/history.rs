@@ -0,0 +1,62 @@
+pub mod post {
+    pub const PATH: &str = "/binance/spot/order/history";
+
+    pub mod handler {
+        use crate::api::http::request::Json;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::audit;
+        use crate::services::binance::client_with_sign;
+        use crate::services::binance::history::TradeQuery;
+
+        use super::models::{Payload, ResponseBody};
+
+        #[tracing::instrument(skip(c))]
+        pub async fn handler(c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
+            let caller = audit::caller(&p.api_key);
+            let client = client_with_sign(p.api_key, p.secret_key, p.endpoint.as_ref())?;
+
+            let history = c.history();
+            if !p.cached_only {
+                history.sync(&client, &caller, &p.symbol).await?;
+            }
+
+            let query = TradeQuery {
+                order_id: p.order_id,
+                start_time: p.start_time,
+                end_time: p.end_time,
+                from_id: p.from_id,
+                limit: p.limit,
+            };
+            let result = history.query(&caller, &p.symbol, &query).await?;
+
+            Ok(Response::ok(result))
+        }
+    }
+
+    pub mod models {
+        use binance::types::{Symbol, Trade};
+        use serde::{Deserialize, Serialize};
+
+        use crate::secret::Secret;
+        use crate::services::binance::Endpoint;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Payload {
+            pub api_key: Secret<String>,
+            pub secret_key: Secret<String>,
+            pub endpoint: Option<Endpoint>,
+            pub symbol: Symbol,
+            pub order_id: Option<i64>,
+            pub start_time: Option<u128>,
+            pub end_time: Option<u128>,
+            pub from_id: Option<i64>,
+            pub limit: Option<usize>,
+            // Skips pulling new trades before the query
+            #[serde(default)]
+            pub cached_only: bool,
+        }
+
+        pub type ResponseBody = Vec<Trade>;
+    }
+}
@@ -5,15 +5,16 @@ pub mod post {
         use crate::api::http::request::Json;
         use crate::api::http::response::{Response, ResponseResult};
         use crate::api::http::trip::Trip;
+        use crate::retry;
         use crate::services::binance::client_with_sign;
 
         use super::models::{Payload, ResponseBody};
 
         #[tracing::instrument(skip(_c))]
         pub async fn handler(_c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
-            let client = client_with_sign(p.api_key, p.secret_key)?;
-            let order = client.spot_order_info(&p.symbol, p.order_id, None).await?;
-            let trades = client.spot_trade(&p.symbol, p.order_id, None).await?;
+            let client = client_with_sign(p.api_key, p.secret_key, p.endpoint.as_ref())?;
+            let order = retry::read(|| client.spot_order_info(&p.symbol, p.order_id, None)).await?;
+            let trades = retry::read(|| client.spot_trade(&p.symbol, p.order_id, None)).await?;
 
             Ok(Response::ok(ResponseBody { order, trades }))
         }
@@ -23,10 +24,14 @@ pub mod post {
         use binance::types::{OrderInfo, Symbol, Trade};
         use serde::{Deserialize, Serialize};
 
+        use crate::secret::Secret;
+        use crate::services::binance::Endpoint;
+
         #[derive(Debug, Clone, Serialize, Deserialize)]
         pub struct Payload {
-            pub api_key: String,
-            pub secret_key: String,
+            pub api_key: Secret<String>,
+            pub secret_key: Secret<String>,
+            pub endpoint: Option<Endpoint>,
             pub order_id: i64,
             pub symbol: Symbol,
         }
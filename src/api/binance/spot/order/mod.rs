@@ -1,72 +1,192 @@
 pub mod buy;
+pub mod execution;
+pub mod history;
 pub mod info;
 pub mod sell;
+pub mod sync;
 pub mod trades;
 
 pub mod post {
     pub const PATH: &str = "/binance/spot/order";
 
     pub mod handler {
-        use std::str::FromStr;
+        use std::error::Error;
 
-        use plot::trade::Executor;
-        use plot::types::Decimal;
+        use axum::http::HeaderMap;
+        use binance::types::Symbol;
+        use plot::trade::position::Position;
+        use plot::trade::{Executor, Trader};
+        use plot::types::Price;
 
         use crate::api::http::request::Json;
         use crate::api::http::response::{Response, ResponseResult};
         use crate::api::http::trip::Trip;
-        use crate::services::binance::{client_with_sign, BinanceSpot};
+        use crate::audit;
+        use crate::idempotency::{self, Begin};
+        use crate::services::binance::dry_run::{
+            default_commission, BinanceSpotDryRun, SimulatedBalance,
+        };
+        use crate::services::binance::market::{fetch_normal_with_limits, fetch_price};
+        use crate::services::binance::{client_with_sign, find_order, BinanceSpot};
 
         use super::models::{Order, Payload, ResponseBody};
 
-        #[tracing::instrument(skip(_c))]
-        pub async fn handler(_c: Trip, Json(mut p): Json<Payload>) -> ResponseResult<ResponseBody> {
-            let client = client_with_sign(p.api_key, p.secret_key)?;
+        #[tracing::instrument(skip(c, headers))]
+        pub async fn handler(
+            c: Trip,
+            headers: HeaderMap,
+            Json(mut p): Json<Payload>,
+        ) -> ResponseResult<ResponseBody> {
+            let caller = audit::caller(&p.api_key);
+            let payload = serde_json::to_value(&p).unwrap_or_default();
+            let client = client_with_sign(p.api_key, p.secret_key, p.endpoint.as_ref())?;
 
-            let price = {
-                let price = client.price(&p.symbol).await?.price;
-                Decimal::from_str(&price).unwrap()
-            };
+            let price = fetch_price(&client, &p.symbol).await?;
+            let (normal, limits) = fetch_normal_with_limits(&client, &p.symbol).await?;
+
+            if p.dry_run {
+                let commission = p.commission.unwrap_or_else(default_commission);
+                let balance = SimulatedBalance::fetch(&client, &normal).await?;
+                let dry_run_agent = BinanceSpotDryRun::new(normal, commission, balance);
+                let (order, error) =
+                    trap(&mut p.positions, &dry_run_agent, &p.symbol, &price).await;
+
+                let body = ResponseBody {
+                    dry_run: Some(dry_run_agent.report()),
+                    positions: p.positions,
+                    order,
+                    placed: Vec::new(),
+                    price,
+                    symbol: p.symbol,
+                };
+
+                return Ok(respond(body, error));
+            }
+
+            let key = idempotency::key(&headers)?;
+            let attempt = match &key {
+                Some(key) => {
+                    let fingerprint = idempotency::fingerprint(&payload);
+                    let attempt = match c.idempotency().begin(&caller, key, &fingerprint)? {
+                        Begin::Attempt(v) => v,
+                        Begin::Done(v) => {
+                            return Response::replay(v)
+                                .map_err(|e| Response::bad_request(e.to_string()));
+                        }
+                    };
+
+                    // An earlier attempt may have traded and lost the response.
+                    // Its orders are numbered from zero and stop at the first
+                    // refusal, so the first one missing ends the search.
+                    let mut placed = Vec::new();
+                    for sequence in 0.. {
+                        let client_order_id = idempotency::child_order_id(&caller, key, sequence);
+                        match find_order(&client, &p.symbol, &client_order_id).await? {
+                            Some(order) => placed.push(order),
+                            None => break,
+                        }
+                    }
 
-            let normal = {
-                let mut info = client.exchange_info(&p.symbol).await?;
-                match info.symbols.pop() {
-                    Some(v) => v,
-                    None => return Err(Response::bad_request("exchange info not found".into())),
+                    if !placed.is_empty() {
+                        let response = Response::ok(ResponseBody {
+                            dry_run: None,
+                            positions: p.positions,
+                            order: Vec::new(),
+                            placed,
+                            price,
+                            symbol: p.symbol,
+                        });
+                        attempt.finish(response.outcome());
+                        return Ok(response);
+                    }
+
+                    Some(attempt)
                 }
+                None => None,
             };
 
+            let spot_agent =
+                BinanceSpot::new(normal, limits, client, c.audit(), c.risk(), caller.clone())
+                    .with_idempotency_key(key);
+            let (order, error) = trap(&mut p.positions, &spot_agent, &p.symbol, &price).await;
+
+            let body = ResponseBody {
+                dry_run: None,
+                positions: p.positions,
+                order,
+                placed: Vec::new(),
+                price,
+                symbol: p.symbol,
+            };
+            // Once a position has traded a retry would trade it again, so the
+            // positions are kept, with the refusal, even when a later one failed
+            let traded = body.order.iter().any(|v| !v.trades.is_empty());
+            let keep = error.is_none() || traded;
+            let response = respond(body, error);
+
+            if let Some(attempt) = attempt {
+                attempt.finish(response.outcome().filter(|_| keep));
+            }
+
+            Ok(response)
+        }
+
+        fn respond(body: ResponseBody, error: Option<Box<dyn Error>>) -> Response<ResponseBody> {
+            match error {
+                Some(e) => Response::refused(e.as_ref(), body),
+                None => Response::ok(body),
+            }
+        }
+
+        async fn trap<T: Trader>(
+            positions: &mut [Position],
+            agent: &T,
+            symbol: &Symbol,
+            price: &Price,
+        ) -> (Vec<Order>, Option<Box<dyn Error>>) {
             let mut order = Vec::new();
-            let spot_agent = BinanceSpot::new(normal, client);
-            for position in p.positions.iter_mut() {
-                let trades = position.trap(&spot_agent, &price).await.unwrap_or_default();
+            for position in positions.iter_mut() {
+                // Stop at the first refusal, the positions before it have
+                // already traded and are kept in the reply.
+                let trades = match position.trap(agent, price).await {
+                    Ok(v) => v,
+                    Err(e) => return (order, Some(e)),
+                };
                 order.push(Order {
                     order_id: 1,
-                    symbol: p.symbol.clone(),
+                    symbol: symbol.clone(),
                     trades,
                 });
             }
 
-            Ok(Response::ok(ResponseBody {
-                positions: p.positions,
-                order,
-                price,
-                symbol: p.symbol,
-            }))
+            (order, None)
         }
     }
 
     pub mod models {
-        use binance::types::Symbol;
-        use plot::{trade::position::Position, trade::Trade, types::Price};
+        use binance::types::{OrderInfo, Symbol};
+        use plot::{
+            trade::position::Position,
+            trade::Trade,
+            types::{Decimal, Price},
+        };
         use serde::{Deserialize, Serialize};
 
+        use crate::secret::Secret;
+        use crate::services::binance::dry_run::DryRunReport;
+        use crate::services::binance::Endpoint;
+
         #[derive(Debug, Clone, Serialize, Deserialize)]
         pub struct Payload {
-            pub api_key: String,
-            pub secret_key: String,
+            pub api_key: Secret<String>,
+            pub secret_key: Secret<String>,
+            pub endpoint: Option<Endpoint>,
             pub symbol: Symbol,
             pub positions: Vec<Position>,
+            #[serde(default)]
+            pub dry_run: bool,
+            // Only used to simulate fills in a dry run
+            pub commission: Option<Decimal>,
         }
 
         #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,6 +195,12 @@ pub mod post {
             pub price: Price,
             pub positions: Vec<Position>,
             pub order: Vec<Order>,
+            // Orders an earlier attempt with the same idempotency key placed,
+            // found instead of trading again. The positions are then returned
+            // as sent and have to be reconciled against them.
+            #[serde(default, skip_serializing_if = "Vec::is_empty")]
+            pub placed: Vec<OrderInfo>,
+            pub dry_run: Option<DryRunReport>,
         }
 
         #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -2,16 +2,18 @@ pub mod post {
     pub const PATH: &str = "/binance/spot/plot";
 
     pub mod handler {
-        use std::str::FromStr;
+        use std::error::Error;
 
         use plot::strategy::Strategy;
         use plot::trade::evaluate::Evaluater;
-        use plot::types::Decimal;
+        use plot::trade::position::Position;
 
         use crate::api::http::request::Json;
         use crate::api::http::response::{Response, ResponseResult};
         use crate::api::http::trip::Trip;
 
+        use crate::services::binance::dry_run::default_commission;
+        use crate::services::binance::market::fetch_normal;
         use crate::services::binance::{client, BinanceSpotTest};
 
         use super::models::{Analyzer, Payload, ResponseBody};
@@ -19,15 +21,9 @@ pub mod post {
         #[tracing::instrument(skip(_c))]
         pub async fn handler(_c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
             let client = client()?;
-            let normal = {
-                let mut info = client.exchange_info(&p.symbol).await?;
-                match info.symbols.pop() {
-                    Some(v) => v,
-                    None => return Err(Response::bad_request("exchange info not found".into())),
-                }
-            };
+            let normal = fetch_normal(&client, &p.symbol).await?;
 
-            let commission = p.commission.unwrap_or(Decimal::from_str("0.001").unwrap());
+            let commission = p.commission.unwrap_or_else(default_commission);
 
             let positions = {
                 let mut positions = Vec::new();
@@ -42,28 +38,32 @@ pub mod post {
                 positions
             };
 
-            let analyzer = {
-                let mut analyzer = Vec::new();
-                let positions = positions.clone();
-                let spot_agent = BinanceSpotTest::new(normal, commission);
-
-                for mut position in positions.into_iter() {
-                    let trades = position.min_profit_trades(&spot_agent).await?;
-                    analyzer.push(Analyzer {
-                        evaluate: trades.evaluate().await,
-                        trades,
-                        position,
-                    });
-                }
-
-                analyzer
-            };
+            let agent = BinanceSpotTest::new(normal, commission);
+            let analyzer = analyze(&agent, positions.clone()).await?;
 
             Ok(Response::ok(ResponseBody {
                 positions,
                 analyzer,
             }))
         }
+
+        /// Runs every position through its cheapest profitable round trip.
+        pub async fn analyze(
+            agent: &BinanceSpotTest,
+            positions: Vec<Position>,
+        ) -> Result<Vec<Analyzer>, Box<dyn Error>> {
+            let mut analyzer = Vec::new();
+            for mut position in positions.into_iter() {
+                let trades = position.min_profit_trades(agent).await?;
+                analyzer.push(Analyzer {
+                    evaluate: trades.evaluate().await,
+                    trades,
+                    position,
+                });
+            }
+
+            Ok(analyzer)
+        }
     }
 
     pub mod models {
@@ -6,6 +6,7 @@ pub mod get {
         use crate::api::http::response::{Response, ResponseResult};
         use crate::api::http::trip::Trip;
         use crate::services::binance::client;
+        use crate::services::binance::market::{fetch_prices, Selection};
 
         use super::models::{Params, ResponseBody};
 
@@ -13,24 +14,24 @@ pub mod get {
         pub async fn handler(_c: Trip, Query(q): Query<Params>) -> ResponseResult<ResponseBody> {
             let client = client()?;
 
-            let result = match q.symbol {
-                Some(v) => {
-                    vec![client.price(&v).await?]
-                }
-                None => client.prices(None).await?,
-            };
+            let selection = Selection::new(q.symbol, q.symbols.as_deref(), q.quote);
+            let result = fetch_prices(&client, &selection).await?;
 
             Ok(Response::ok(result))
         }
     }
 
     pub mod models {
-        use binance::types::{Symbol, SymbolPrice};
+        use binance::types::{Asset, Symbol, SymbolPrice};
         use serde::{Deserialize, Serialize};
 
         #[derive(Debug, Clone, Serialize, Deserialize)]
         pub struct Params {
             pub symbol: Option<Symbol>,
+            // Comma separated, e.g. `BTCUSDT,ETHUSDT`
+            pub symbols: Option<String>,
+            // Every pair quoted in this asset, e.g. `USDT`
+            pub quote: Option<Asset>,
         }
 
         pub type ResponseBody = Vec<SymbolPrice>;
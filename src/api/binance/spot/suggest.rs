@@ -0,0 +1,86 @@
+pub mod post {
+    pub const PATH: &str = "/binance/spot/plot/suggest";
+
+    /// Candle interval analysed when none is given.
+    const DEFAULT_INTERVAL: &str = "1h";
+
+    /// Candles analysed when no limit is given, about three weeks of hours.
+    const DEFAULT_LIMIT: u16 = 500;
+
+    pub mod handler {
+        use plot::strategy::Strategy;
+
+        use crate::api::binance::spot::plot::post::handler::analyze;
+        use crate::api::http::request::Json;
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+        use crate::services::binance::dry_run::default_commission;
+        use crate::services::binance::klines::fetch_candles;
+        use crate::services::binance::market::{fetch_normal, fetch_price};
+        use crate::services::binance::suggest::suggest;
+        use crate::services::binance::{client, BinanceSpotTest};
+
+        use super::models::{Payload, ResponseBody};
+        use super::{DEFAULT_INTERVAL, DEFAULT_LIMIT};
+
+        #[tracing::instrument(skip(_c))]
+        pub async fn handler(_c: Trip, Json(p): Json<Payload>) -> ResponseResult<ResponseBody> {
+            let client = client()?;
+            let normal = fetch_normal(&client, &p.symbol).await?;
+            let price = fetch_price(&client, &p.symbol).await?;
+
+            let interval = p.interval.unwrap_or(DEFAULT_INTERVAL.into());
+            let limit = p.limit.unwrap_or(DEFAULT_LIMIT);
+            let candles =
+                fetch_candles(&client, &p.symbol, &interval, None, None, Some(limit)).await?;
+
+            let commission = p.commission.unwrap_or_else(default_commission);
+            let agent = BinanceSpotTest::new(normal, commission);
+
+            let suggestion = match suggest(&candles, &price, &p.investment, &commission, &agent) {
+                Ok(v) => v,
+                Err(e) => return Err(Response::bad_request(e)),
+            };
+
+            let grid = suggestion.grid();
+            let positions = grid.assign_position();
+            let analyzer = analyze(&agent, positions.clone()).await?;
+
+            Ok(Response::ok(ResponseBody {
+                suggestion,
+                grid,
+                positions,
+                analyzer,
+            }))
+        }
+    }
+
+    pub mod models {
+        use binance::types::Symbol;
+        use plot::strategy::grid::Grid;
+        use plot::trade::position::Position;
+        use plot::types::{Decimal, QuoteQuantity};
+        use serde::{Deserialize, Serialize};
+
+        use crate::api::binance::spot::plot::post::models::Analyzer;
+        use crate::services::binance::suggest::GridSuggestion;
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Payload {
+            pub symbol: Symbol,
+            pub investment: QuoteQuantity,
+            pub commission: Option<Decimal>,
+            pub interval: Option<String>,
+            pub limit: Option<u16>,
+        }
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct ResponseBody {
+            pub suggestion: GridSuggestion,
+            // Ready to send as `grid` to the plot endpoint
+            pub grid: Grid,
+            pub positions: Vec<Position>,
+            pub analyzer: Vec<Analyzer>,
+        }
+    }
+}
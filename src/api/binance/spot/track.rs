@@ -2,16 +2,15 @@ pub mod post {
     pub const PATH: &str = "/binance/spot/track";
 
     pub mod handler {
-        use std::str::FromStr;
-
         use plot::trade::evaluate::Evaluater;
         use plot::trade::Executor;
-        use plot::types::Decimal;
 
         use crate::api::http::request::Json;
         use crate::api::http::response::{Response, ResponseResult};
         use crate::api::http::trip::Trip;
 
+        use crate::services::binance::dry_run::default_commission;
+        use crate::services::binance::market::fetch_normal;
         use crate::services::binance::{client, BinanceSpotTest};
 
         use super::models::{Payload, ResponseBody};
@@ -19,20 +18,9 @@ pub mod post {
         #[tracing::instrument(skip(_c))]
         pub async fn handler(_c: Trip, Json(mut p): Json<Payload>) -> ResponseResult<ResponseBody> {
             let client = client()?;
-            let normal = {
-                let mut info = client.exchange_info(&p.symbol).await?;
-                match info.symbols.pop() {
-                    Some(v) => v,
-                    None => {
-                        return Err(Response::bad_request(format!(
-                            "symbol {} exchange info not found",
-                            p.symbol
-                        )))
-                    }
-                }
-            };
+            let normal = fetch_normal(&client, &p.symbol).await?;
 
-            let commission = p.commission.unwrap_or(Decimal::from_str("0.001").unwrap());
+            let commission = p.commission.unwrap_or_else(default_commission);
 
             let agent = BinanceSpotTest::new(normal, commission);
 
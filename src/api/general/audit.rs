@@ -0,0 +1,67 @@
+pub mod get {
+    pub const PATH: &str = "/audit";
+
+    /// Records served when the request sets no `limit`.
+    const DEFAULT_LIMIT: usize = 100;
+    const MAX_LIMIT: usize = 1000;
+
+    pub mod handler {
+        use crate::api::http::request::{Admin, Query};
+        use crate::api::http::response::{Response, ResponseResult};
+        use crate::api::http::trip::Trip;
+
+        use super::models::{Params, ResponseBody};
+        use super::{DEFAULT_LIMIT, MAX_LIMIT};
+
+        #[tracing::instrument(skip(c))]
+        pub async fn handler(
+            _: Admin,
+            c: Trip,
+            Query(p): Query<Params>,
+        ) -> ResponseResult<ResponseBody> {
+            let from = p.from.unwrap_or(0);
+            let limit = p.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+
+            // Only the requested page is read and verified against the
+            // record before it
+            let audit = c.audit();
+            let page = tokio::task::spawn_blocking(move || audit.page(from, limit)).await;
+            let (records, verification) = match page {
+                Ok(Ok(v)) => v,
+                Ok(Err(e)) => return Err(Response::bad_request(e.to_string())),
+                Err(e) => return Err(Response::bad_request(e.to_string())),
+            };
+
+            let next = match records.len() == limit {
+                true => records.last().map(|v| v.sequence + 1),
+                false => None,
+            };
+
+            Ok(Response::ok(ResponseBody {
+                verification,
+                records,
+                next,
+            }))
+        }
+    }
+
+    pub mod models {
+        use serde::{Deserialize, Serialize};
+
+        use crate::audit::{Record, Verification};
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct Params {
+            pub from: Option<u64>,
+            pub limit: Option<usize>,
+        }
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct ResponseBody {
+            pub verification: Verification,
+            pub records: Vec<Record>,
+            /// `from` of the next page, while there may be more records.
+            pub next: Option<u64>,
+        }
+    }
+}
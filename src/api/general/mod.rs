@@ -1,3 +1,4 @@
+mod audit;
 mod health;
 
 mod get {
@@ -35,5 +36,6 @@ pub fn router(state: std::sync::Arc<crate::api::State>) -> axum::Router {
     Router::new()
         .route(get::PATH, get(get::handler::handler))
         .route(health::get::PATH, get(health::get::handler::handler))
+        .route(audit::get::PATH, get(audit::get::handler::handler))
         .with_state(state)
 }
@@ -1,15 +1,83 @@
 pub mod trip {
+    use std::env;
     use std::sync::Arc;
 
+    use crate::audit::AuditLog;
+    use crate::idempotency::IdempotencyStore;
     use crate::time::timestamp;
 
     pub(crate) type Trip = axum::extract::State<Arc<State>>;
 
-    pub struct State {}
+    pub struct State {
+        audit: Arc<AuditLog>,
+        idempotency: Arc<IdempotencyStore>,
+        #[cfg(feature = "service-binance")]
+        risk: Arc<crate::services::binance::risk::RiskManager>,
+        #[cfg(feature = "service-binance")]
+        history: Arc<crate::services::binance::history::TradeHistory>,
+        #[cfg(feature = "service-binance")]
+        executions: Arc<crate::services::binance::execution::Executions>,
+    }
 
     impl State {
         pub async fn new() -> Self {
-            Self {}
+            // Fail at startup rather than in the first handler to use it
+            #[cfg(feature = "service-binance")]
+            let _ = crate::services::binance::Endpoint::global();
+
+            let audit_path = env::var("AUDIT_LOG_PATH").unwrap_or("audit.jsonl".into());
+            let audit = AuditLog::open(audit_path).expect("open audit log error");
+
+            #[cfg(feature = "service-binance")]
+            let risk = {
+                use crate::services::binance::halt::Halts;
+                use crate::services::binance::risk::{RiskConfig, RiskManager};
+
+                let config = RiskConfig::from_env().expect("load risk limits error");
+                let halts = Halts::from_env().expect("open halt state error");
+                RiskManager::new(config, halts)
+            };
+
+            #[cfg(feature = "service-binance")]
+            let history = {
+                use crate::services::binance::history::TradeHistory;
+
+                TradeHistory::from_env().expect("open trade cache error")
+            };
+
+            Self {
+                audit: Arc::new(audit),
+                idempotency: Arc::new(IdempotencyStore::new()),
+                #[cfg(feature = "service-binance")]
+                risk: Arc::new(risk),
+                #[cfg(feature = "service-binance")]
+                history: Arc::new(history),
+                #[cfg(feature = "service-binance")]
+                executions: Arc::new(crate::services::binance::execution::Executions::new()),
+            }
+        }
+
+        pub fn audit(&self) -> Arc<AuditLog> {
+            self.audit.clone()
+        }
+
+        pub fn idempotency(&self) -> Arc<IdempotencyStore> {
+            self.idempotency.clone()
+        }
+
+        #[cfg(feature = "service-binance")]
+        pub fn risk(&self) -> Arc<crate::services::binance::risk::RiskManager> {
+            self.risk.clone()
+        }
+
+        #[cfg(feature = "service-binance")]
+        pub fn history(&self) -> Arc<crate::services::binance::history::TradeHistory> {
+            self.history.clone()
+        }
+
+        #[cfg(feature = "service-binance")]
+        pub fn executions(&self) -> Arc<crate::services::binance::execution::Executions> {
+            self.executions.clone()
         }
 
         pub fn timestamp_millis(&self) -> u128 {
@@ -20,9 +88,12 @@ pub mod trip {
 
 pub mod response {
     use axum::{http::StatusCode, response::IntoResponse, Json};
+    use serde::de::DeserializeOwned;
     use serde::Serialize;
     use serde_json::json;
 
+    use crate::idempotency::Outcome;
+
     pub type ResponseResult<T> = Result<Response<T>, Response<()>>;
 
     #[derive(Serialize)]
@@ -34,6 +105,7 @@ pub mod response {
         pub(crate) code: u16,
         pub(crate) data: Option<T>,
         pub(crate) message: Option<String>,
+        pub(crate) request_id: Option<String>,
     }
 
     impl<T> Response<T>
@@ -46,6 +118,7 @@ pub mod response {
                 code: 200,
                 data: None,
                 message: None,
+                request_id: crate::trace::current(),
             }
         }
 
@@ -56,13 +129,30 @@ pub mod response {
             response
         }
 
-        // pub fn forbidden(message: String) -> Self {
-        //     let mut response = Self::new();
-        //     response.code = 403;
-        //     response.message = Some(message);
+        pub fn forbidden(message: String) -> Self {
+            let mut response = Self::new();
+            response.ok = false;
+            response.code = 403;
+            response.message = Some(message);
 
-        //     response
-        // }
+            response
+        }
+
+        /// Refuses a trade with `data` still in the body, so the caller sees
+        /// what happened before it. A halt is forbidden, anything else a bad
+        /// request.
+        #[cfg(feature = "service-binance")]
+        pub fn refused(error: &(dyn std::error::Error + 'static), data: T) -> Self {
+            use crate::services::binance::risk::RiskError;
+
+            let mut response = match error.downcast_ref::<RiskError>() {
+                Some(RiskError::Halted(_)) => Self::forbidden(error.to_string()),
+                _ => Self::bad_request(error.to_string()),
+            };
+            response.data = Some(data);
+
+            response
+        }
 
         pub fn bad_request(message: String) -> Self {
             let mut response = Self::new();
@@ -73,6 +163,31 @@ pub mod response {
             response
         }
 
+        /// What to replay to a retry of this request, none when there is no
+        /// body to keep.
+        pub fn outcome(&self) -> Option<Outcome> {
+            let body = serde_json::to_value(self.data.as_ref()?).ok()?;
+
+            Some(Outcome {
+                status: self.code,
+                message: self.message.clone(),
+                body,
+            })
+        }
+
+        /// The response of an earlier attempt, with its status.
+        pub fn replay(outcome: Outcome) -> Result<Self, serde_json::Error>
+        where
+            T: DeserializeOwned,
+        {
+            let mut response = Self::ok(serde_json::from_value(outcome.body)?);
+            response.ok = (200..300).contains(&outcome.status);
+            response.code = outcome.status;
+            response.message = outcome.message;
+
+            Ok(response)
+        }
+
         // pub fn internal_error(message: String) -> Self {
         //     let mut response = Self::new();
         //     response.ok = false;
@@ -95,6 +210,38 @@ pub mod response {
         }
     }
 
+    /// A CSV download, sent in place of the JSON envelope.
+    #[cfg(feature = "service-binance")]
+    pub struct Csv {
+        pub(crate) filename: String,
+        pub(crate) body: String,
+    }
+
+    #[cfg(feature = "service-binance")]
+    impl Csv {
+        pub fn new(filename: impl Into<String>, body: String) -> Self {
+            Self {
+                filename: filename.into(),
+                body,
+            }
+        }
+    }
+
+    #[cfg(feature = "service-binance")]
+    impl IntoResponse for Csv {
+        fn into_response(self) -> axum::response::Response {
+            use axum::http::header;
+
+            let disposition = format!("attachment; filename=\"{}\"", self.filename);
+            let headers = [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (header::CONTENT_DISPOSITION, disposition),
+            ];
+
+            (StatusCode::OK, headers, self.body).into_response()
+        }
+    }
+
     mod from_general_error {
         use std::error::Error;
 
@@ -110,6 +257,21 @@ pub mod response {
         }
     }
 
+    mod from_idempotency_error {
+        use crate::idempotency::IdempotencyError;
+
+        use super::{Response, Serialize};
+
+        impl<T> From<IdempotencyError> for Response<T>
+        where
+            T: Serialize,
+        {
+            fn from(value: IdempotencyError) -> Self {
+                Self::bad_request(value.to_string())
+            }
+        }
+    }
+
     #[cfg(feature = "service-crypto")]
     mod from_contract_error {
         use crate::services::crypto::contract::ContractError;
@@ -157,6 +319,22 @@ pub mod response {
             }
         }
     }
+
+    #[cfg(feature = "service-binance")]
+    mod from_binance_risk_error {
+        use crate::services::binance::risk::RiskError;
+
+        use super::{Response, Serialize};
+
+        impl<T> From<RiskError> for Response<T>
+        where
+            T: Serialize,
+        {
+            fn from(value: RiskError) -> Self {
+                Self::bad_request(value.to_string())
+            }
+        }
+    }
 }
 
 pub mod request {
@@ -194,6 +372,51 @@ pub mod request {
         }
     }
 
+    // ===== Admin =====
+    /// Guards a route with `Authorization: Bearer <ADMIN_TOKEN>`.
+    ///
+    /// Every request is refused while `ADMIN_TOKEN` is unset.
+    #[derive(Debug, Clone)]
+    pub struct Admin;
+
+    #[async_trait]
+    impl<S> FromRequestParts<S> for Admin
+    where
+        S: Send + Sync,
+    {
+        type Rejection = super::response::Response<()>;
+
+        async fn from_request_parts(
+            parts: &mut Parts,
+            _state: &S,
+        ) -> Result<Self, Self::Rejection> {
+            use sha2::{Digest, Sha256};
+
+            use super::response::Response;
+
+            let token = match std::env::var("ADMIN_TOKEN") {
+                Ok(v) if !v.is_empty() => v,
+                _ => return Err(Response::forbidden("admin routes are disabled".into())),
+            };
+
+            let bearer = parts
+                .headers
+                .get(axum::http::header::AUTHORIZATION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.strip_prefix("Bearer "));
+
+            // Compared as digests, whose length is fixed and whose first
+            // differing byte says nothing of the token's
+            let digest = |v: &str| Sha256::digest(v.as_bytes());
+            let matches = bearer.is_some_and(|v| digest(v) == digest(&token));
+
+            match matches {
+                true => Ok(Self),
+                false => Err(Response::forbidden("invalid admin token".into())),
+            }
+        }
+    }
+
     // ===== JSON =====
     #[derive(Debug, Clone)]
     pub struct Json<T>(pub T);
@@ -218,3 +441,77 @@ pub mod request {
         }
     }
 }
+
+pub mod middleware {
+    use std::time::Instant;
+
+    use axum::extract::{MatchedPath, Request};
+    use axum::http::HeaderValue;
+    use axum::middleware::Next;
+    use axum::response::Response;
+    use tracing::Instrument;
+
+    use crate::api::version;
+    use crate::trace;
+
+    pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+    /// Assigns every request an id, honouring an incoming `X-Request-Id`,
+    /// and records route, status and latency on the request span.
+    pub async fn request_id(request: Request, next: Next) -> Response {
+        let incoming = request
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|v| v.to_str().ok());
+        let id = trace::accept_or_generate(incoming);
+
+        let route = match request.extensions().get::<MatchedPath>() {
+            Some(v) => v.as_str().to_string(),
+            None => request.uri().path().to_string(),
+        };
+
+        let span = tracing::info_span!(
+            "request",
+            request_id = %id,
+            method = %request.method(),
+            route = %route,
+            status = tracing::field::Empty,
+            latency_ms = tracing::field::Empty,
+        );
+
+        let start = Instant::now();
+        let mut response = trace::scope(id.clone(), next.run(request))
+            .instrument(span.clone())
+            .await;
+
+        span.record("status", response.status().as_u16());
+        span.record("latency_ms", start.elapsed().as_millis() as u64);
+        span.in_scope(|| tracing::info!("request finished"));
+
+        if let Ok(value) = HeaderValue::from_str(&id) {
+            response.headers_mut().insert(REQUEST_ID_HEADER, value);
+        }
+
+        response
+    }
+
+    /// Marks a response served from an unversioned path as deprecated and
+    /// links to its `/v1` successor.
+    pub async fn deprecated(request: Request, next: Next) -> Response {
+        let successor = format!(
+            "<{}{}>; rel=\"successor-version\"",
+            version::V1,
+            request.uri().path()
+        );
+
+        let mut response = next.run(request).await;
+        let headers = response.headers_mut();
+
+        headers.insert("deprecation", HeaderValue::from_static("true"));
+        if let Ok(value) = HeaderValue::from_str(&successor) {
+            headers.insert("link", value);
+        }
+
+        response
+    }
+}
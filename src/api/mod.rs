@@ -8,3 +8,50 @@ pub mod crypto;
 
 #[cfg(feature = "service-binance")]
 pub mod binance;
+
+pub mod version {
+    pub const V1: &str = "/v1";
+    pub const V2: &str = "/v2";
+}
+
+/// Assemble every service router.
+///
+/// Service routes are served under [`version::V1`] and [`version::V2`]. A
+/// `/v2` route only needs to be registered when its models changed, every
+/// other `/v2` request falls back to the `/v1` handler. The bare paths are
+/// kept as deprecated aliases of `/v1`.
+pub fn router(state: std::sync::Arc<State>) -> axum::Router {
+    use axum::{middleware, Router};
+
+    let v1 = router_v1(state.clone());
+    let v2 = router_v2(state.clone()).fallback_service(v1.clone());
+
+    let legacy = v1
+        .clone()
+        .layer(middleware::from_fn(http::middleware::deprecated));
+
+    Router::new()
+        .merge(general::router(state))
+        .nest(version::V1, v1)
+        .nest(version::V2, v2)
+        .merge(legacy)
+        .layer(middleware::from_fn(http::middleware::request_id))
+}
+
+#[allow(unused_variables)]
+fn router_v1(state: std::sync::Arc<State>) -> axum::Router {
+    let router = axum::Router::new();
+
+    #[cfg(feature = "service-crypto")]
+    let router = router.merge(crypto::router(state.clone()));
+
+    #[cfg(feature = "service-binance")]
+    let router = router.merge(binance::router(state.clone()));
+
+    router
+}
+
+fn router_v2(_state: std::sync::Arc<State>) -> axum::Router {
+    // Routes whose models changed in v2 are registered here
+    axum::Router::new()
+}
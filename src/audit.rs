@@ -0,0 +1,400 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::extension::LockResultExt;
+use crate::secret::Secret;
+use crate::time::timestamp;
+
+/// `previous_hash` of the first record in a chain.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+pub type AuditResult<T> = Result<T, std::io::Error>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    SpotBuy,
+    SpotSell,
+    SpotCancel,
+    DustTransfer,
+    FuturesBuy,
+    FuturesSell,
+    FuturesLeverage,
+    FuturesMarginType,
+    Halt,
+    Resume,
+}
+
+/// What happened, as reported by the caller of [`AuditLog::append`].
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub action: Action,
+    pub caller: String,
+    pub payload: Value,
+    pub corrected_quantity: Option<String>,
+    pub response: Option<Value>,
+    pub error: Option<String>,
+    /// The request served, taken when the entry is made as the append may
+    /// run outside of it.
+    pub request_id: Option<String>,
+}
+
+impl Entry {
+    pub fn new<T, E>(
+        action: Action,
+        caller: String,
+        payload: Value,
+        corrected_quantity: Option<String>,
+        result: &Result<T, E>,
+    ) -> Self
+    where
+        T: Serialize,
+        E: std::fmt::Display,
+    {
+        let (response, error) = match result {
+            Ok(v) => (serde_json::to_value(v).ok(), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+
+        Self {
+            action,
+            caller,
+            payload,
+            corrected_quantity,
+            response,
+            error,
+            request_id: crate::trace::current(),
+        }
+    }
+}
+
+/// A persisted entry, linked to its predecessor by `previous_hash`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    pub sequence: u64,
+    pub timestamp: u128,
+    pub request_id: Option<String>,
+    pub action: Action,
+    pub caller: String,
+    pub payload: Value,
+    pub corrected_quantity: Option<String>,
+    pub response: Option<Value>,
+    pub ok: bool,
+    pub error: Option<String>,
+    pub previous_hash: String,
+    pub hash: String,
+}
+
+impl Record {
+    fn digest(&self) -> String {
+        #[derive(Serialize)]
+        struct Content<'a> {
+            sequence: u64,
+            timestamp: u128,
+            request_id: &'a Option<String>,
+            action: Action,
+            caller: &'a str,
+            payload: &'a Value,
+            corrected_quantity: &'a Option<String>,
+            response: &'a Option<Value>,
+            ok: bool,
+            error: &'a Option<String>,
+            previous_hash: &'a str,
+        }
+
+        let content = Content {
+            sequence: self.sequence,
+            timestamp: self.timestamp,
+            request_id: &self.request_id,
+            action: self.action,
+            caller: &self.caller,
+            payload: &self.payload,
+            corrected_quantity: &self.corrected_quantity,
+            response: &self.response,
+            ok: self.ok,
+            error: &self.error,
+            previous_hash: &self.previous_hash,
+        };
+
+        // Serializing plain structs and `Value` maps is deterministic
+        let bytes = serde_json::to_vec(&content).unwrap_or_default();
+
+        hex(&Sha256::digest(bytes))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Verification {
+    pub length: u64,
+    pub verified: bool,
+    pub head: String,
+    /// Sequence of the first record that does not match the chain.
+    pub broken_at: Option<u64>,
+}
+
+/// Append-only, hash-chained log of trading actions, one JSON record per line.
+pub struct AuditLog {
+    path: PathBuf,
+    head: Mutex<Head>,
+    appended: Condvar,
+}
+
+struct Head {
+    sequence: u64,
+    hash: String,
+    /// Entries recorded but not appended yet.
+    pending: usize,
+}
+
+impl AuditLog {
+    /// Open the log at `path`, continuing the chain already stored there.
+    pub fn open(path: impl Into<PathBuf>) -> AuditResult<Self> {
+        let path = path.into();
+
+        let mut head = Head {
+            sequence: 0,
+            hash: GENESIS_HASH.into(),
+            pending: 0,
+        };
+
+        if path.exists() {
+            for record in Self::read(&path)? {
+                head.sequence = record.sequence + 1;
+                head.hash = record.hash;
+            }
+        }
+
+        Ok(Self {
+            path,
+            head: Mutex::new(head),
+            appended: Condvar::new(),
+        })
+    }
+
+    pub fn append(&self, entry: Entry) -> AuditResult<Record> {
+        let mut head = self.head.lock().ignore_poison();
+
+        let mut record = Record {
+            sequence: head.sequence,
+            timestamp: timestamp().as_millis(),
+            request_id: entry.request_id,
+            action: entry.action,
+            caller: entry.caller,
+            payload: entry.payload,
+            corrected_quantity: entry.corrected_quantity,
+            response: entry.response,
+            ok: entry.error.is_none(),
+            error: entry.error,
+            previous_hash: head.hash.clone(),
+            hash: String::new(),
+        };
+        record.hash = record.digest();
+
+        let mut line = serde_json::to_vec(&record)?;
+        line.push(b'\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(&line)?;
+        file.sync_data()?;
+
+        head.sequence = record.sequence + 1;
+        head.hash = record.hash.clone();
+
+        Ok(record)
+    }
+
+    /// Append `entry`, reporting a failure to persist it instead of returning
+    /// it, the action being audited has already happened at this point.
+    ///
+    /// The append waits for the disk, so it runs on the blocking pool and
+    /// readers wait for it instead of the caller.
+    pub fn record(self: &Arc<Self>, entry: Entry) {
+        self.head.lock().ignore_poison().pending += 1;
+
+        let log = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let result = log.append(entry);
+            log.head.lock().ignore_poison().pending -= 1;
+            log.appended.notify_all();
+
+            if let Err(e) = result {
+                tracing::error!(error = %e, path = ?log.path, "audit log append failed");
+            }
+        });
+    }
+
+    /// Holds the head once every recorded entry has been appended.
+    fn settled(&self) -> MutexGuard<'_, Head> {
+        self.appended
+            .wait_while(self.head.lock().ignore_poison(), |v| v.pending > 0)
+            .ignore_poison()
+    }
+
+    pub fn export(&self) -> AuditResult<Vec<Record>> {
+        // Hold the head so no record is half written while reading
+        let _head = self.settled();
+
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        Self::read(&self.path)
+    }
+
+    /// Reads at most `limit` records from sequence `from` on and verifies
+    /// they continue the chain, parsing only them and their predecessor.
+    pub fn page(&self, from: u64, limit: usize) -> AuditResult<(Vec<Record>, Verification)> {
+        let _head = self.settled();
+
+        let mut previous_hash = None;
+        let mut records = Vec::new();
+        if self.path.exists() && limit > 0 {
+            let reader = BufReader::new(File::open(&self.path)?);
+
+            let mut sequence = 0;
+            for line in reader.lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                if sequence + 1 == from {
+                    previous_hash = Some(serde_json::from_str::<Record>(&line)?.hash);
+                } else if sequence >= from {
+                    records.push(serde_json::from_str(&line)?);
+                    if records.len() == limit {
+                        break;
+                    }
+                }
+                sequence += 1;
+            }
+        }
+
+        let previous_hash = previous_hash.as_deref().unwrap_or(GENESIS_HASH);
+        let verification = Self::verify_from(&records, from, previous_hash);
+
+        Ok((records, verification))
+    }
+
+    pub fn verify(records: &[Record]) -> Verification {
+        Self::verify_from(records, 0, GENESIS_HASH)
+    }
+
+    /// Verifies `records` as the part of a chain starting at `sequence`,
+    /// after the record hashed `previous_hash`.
+    fn verify_from(records: &[Record], sequence: u64, previous_hash: &str) -> Verification {
+        let mut previous_hash = previous_hash;
+
+        for (index, record) in records.iter().enumerate() {
+            let expected = sequence + index as u64;
+            let linked = record.sequence == expected && record.previous_hash == previous_hash;
+
+            if !linked || record.digest() != record.hash {
+                return Verification {
+                    length: records.len() as u64,
+                    verified: false,
+                    head: previous_hash.to_string(),
+                    broken_at: Some(expected),
+                };
+            }
+
+            previous_hash = &record.hash;
+        }
+
+        Verification {
+            length: records.len() as u64,
+            verified: true,
+            head: previous_hash.to_string(),
+            broken_at: None,
+        }
+    }
+
+    fn read(path: &Path) -> AuditResult<Vec<Record>> {
+        let reader = BufReader::new(File::open(path)?);
+
+        let mut records = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            records.push(serde_json::from_str(&line)?);
+        }
+
+        Ok(records)
+    }
+}
+
+/// Stable identity of the holder of an API key that does not reveal the key.
+pub fn caller(api_key: &Secret<String>) -> String {
+    let digest = Sha256::digest(api_key.expose().as_bytes());
+
+    format!("key:{}", &hex(&digest)[..16])
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{Action, AuditLog, Entry};
+
+    fn entry(quantity: &str) -> Entry {
+        let result: Result<(), String> = Ok(());
+
+        Entry::new(
+            Action::SpotBuy,
+            "key:0123456789abcdef".into(),
+            json!({ "symbol": "ETHUSDT", "quote_quantity": quantity }),
+            Some(quantity.into()),
+            &result,
+        )
+    }
+
+    #[test]
+    fn test_chain_verify_and_tamper() {
+        let path = std::env::temp_dir().join(format!("harmony-audit-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let log = AuditLog::open(&path).unwrap();
+        log.append(entry("10")).unwrap();
+        log.append(entry("20")).unwrap();
+
+        // Reopening continues the chain
+        let log = AuditLog::open(&path).unwrap();
+        log.append(entry("30")).unwrap();
+
+        let mut records = log.export().unwrap();
+        assert_eq!(records.len(), 3);
+        assert!(AuditLog::verify(&records).verified);
+
+        records[1].payload = json!({ "symbol": "ETHUSDT", "quote_quantity": "2000" });
+        let verification = AuditLog::verify(&records);
+        assert!(!verification.verified);
+        assert_eq!(verification.broken_at, Some(1));
+
+        let (page, verification) = log.page(1, 1).unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].sequence, 1);
+        assert!(verification.verified);
+        assert_eq!(verification.head, page[0].hash);
+
+        let (page, _) = log.page(2, 10).unwrap();
+        assert_eq!(page.len(), 1);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,334 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::http::HeaderMap;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::audit::hex;
+use crate::extension::LockResultExt;
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Longest `Idempotency-Key` accepted.
+const MAX_KEY_LENGTH: usize = 255;
+
+/// How long an outcome is kept for retries.
+const RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How long an unfinished attempt holds its key before a retry may take
+/// it over.
+const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Prefix of every client order id derived from an idempotency key.
+const CLIENT_ORDER_ID_PREFIX: &str = "idem-";
+
+/// Binance limits `newClientOrderId` to 36 characters.
+const CLIENT_ORDER_ID_LENGTH: usize = 36;
+
+#[derive(Debug)]
+pub enum IdempotencyError {
+    InvalidKey(String),
+    Conflict(String),
+    InProgress(String),
+}
+
+impl std::error::Error for IdempotencyError {}
+impl std::fmt::Display for IdempotencyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            Self::InvalidKey(e) => format!("INVALID_KEY {}", e),
+            Self::Conflict(e) => format!("CONFLICT {}", e),
+            Self::InProgress(e) => format!("IN_PROGRESS {}", e),
+        };
+
+        write!(f, "IDEMPOTENCY {}", message)
+    }
+}
+
+/// What to do with a request carrying an idempotency key.
+#[derive(Debug)]
+pub enum Begin {
+    /// No earlier attempt finished, the order may or may not have been placed.
+    Attempt(Attempt),
+    /// An earlier attempt finished with this response.
+    Done(Outcome),
+}
+
+/// The response of a finished attempt, replayed to its retries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome {
+    pub status: u16,
+    pub message: Option<String>,
+    pub body: Value,
+}
+
+#[derive(Debug)]
+struct Slot {
+    fingerprint: String,
+    created: Instant,
+    /// The attempt holding the key and when it claimed it.
+    active: Option<(u64, Instant)>,
+    outcome: Option<Outcome>,
+}
+
+impl Slot {
+    fn is_active(&self) -> bool {
+        self.active
+            .is_some_and(|(_, started)| started.elapsed() < ATTEMPT_TIMEOUT)
+    }
+}
+
+type Slots = Arc<Mutex<HashMap<(String, String), Slot>>>;
+
+/// A claimed idempotency key. Finishing it keeps the outcome for retries,
+/// dropping it unfinished, because the request failed or was abandoned,
+/// releases the key.
+#[derive(Debug)]
+#[must_use]
+pub struct Attempt {
+    caller: String,
+    key: String,
+    id: u64,
+    slots: Slots,
+}
+
+impl Attempt {
+    /// Releases the key, keeping `outcome` for later retries when there is
+    /// one.
+    pub fn finish(self, outcome: Option<Outcome>) {
+        self.release(outcome);
+    }
+
+    fn release(&self, outcome: Option<Outcome>) {
+        let mut slots = self.slots.lock().ignore_poison();
+        let Some(slot) = slots.get_mut(&(self.caller.clone(), self.key.clone())) else {
+            return;
+        };
+
+        // A timed out attempt may have been superseded by a retry
+        if slot.active.is_some_and(|(id, _)| id == self.id) {
+            slot.active = None;
+            if outcome.is_some() {
+                slot.outcome = outcome;
+            }
+        }
+    }
+}
+
+impl Drop for Attempt {
+    fn drop(&mut self) {
+        self.release(None);
+    }
+}
+
+/// Outcomes of requests by account and idempotency key.
+#[derive(Debug, Default)]
+pub struct IdempotencyStore {
+    slots: Slots,
+    attempts: AtomicU64,
+}
+
+impl IdempotencyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Claims `key` for a request of `caller` whose payload hashes to
+    /// `fingerprint`, until the returned [`Attempt`] is finished or dropped.
+    pub fn begin(
+        &self,
+        caller: &str,
+        key: &str,
+        fingerprint: &str,
+    ) -> Result<Begin, IdempotencyError> {
+        let mut slots = self.slots.lock().ignore_poison();
+        slots.retain(|_, v| v.is_active() || v.created.elapsed() < RETENTION);
+
+        let slot = slots
+            .entry((caller.to_string(), key.to_string()))
+            .or_insert_with(|| Slot {
+                fingerprint: fingerprint.to_string(),
+                created: Instant::now(),
+                active: None,
+                outcome: None,
+            });
+
+        if slot.fingerprint != fingerprint {
+            return Err(IdempotencyError::Conflict(format!(
+                "key {} was used with a different payload",
+                key
+            )));
+        }
+
+        if let Some(v) = &slot.outcome {
+            return Ok(Begin::Done(v.clone()));
+        }
+
+        if slot.is_active() {
+            return Err(IdempotencyError::InProgress(format!(
+                "a request with key {} is still being served",
+                key
+            )));
+        }
+
+        let id = self.attempts.fetch_add(1, Ordering::Relaxed);
+        slot.active = Some((id, Instant::now()));
+
+        Ok(Begin::Attempt(Attempt {
+            caller: caller.to_string(),
+            key: key.to_string(),
+            id,
+            slots: self.slots.clone(),
+        }))
+    }
+}
+
+pub fn validate_key(key: &str) -> Result<(), IdempotencyError> {
+    match !key.is_empty() && key.len() <= MAX_KEY_LENGTH {
+        true => Ok(()),
+        false => Err(IdempotencyError::InvalidKey(format!(
+            "must be 1 to {} characters",
+            MAX_KEY_LENGTH
+        ))),
+    }
+}
+
+/// The validated `Idempotency-Key` of a request, if it carries one.
+pub fn key(headers: &HeaderMap) -> Result<Option<String>, IdempotencyError> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+
+    let key = value
+        .to_str()
+        .map_err(|e| IdempotencyError::InvalidKey(e.to_string()))?;
+    validate_key(key)?;
+
+    Ok(Some(key.to_string()))
+}
+
+/// The Binance `newClientOrderId` of the order placed for `key`, the same on
+/// every retry and distinct between accounts.
+pub fn client_order_id(caller: &str, key: &str) -> String {
+    let digest = hex(&Sha256::new()
+        .chain_update(caller)
+        .chain_update("\n")
+        .chain_update(key)
+        .finalize());
+    let length = CLIENT_ORDER_ID_LENGTH - CLIENT_ORDER_ID_PREFIX.len();
+
+    format!("{}{}", CLIENT_ORDER_ID_PREFIX, &digest[..length])
+}
+
+/// The client order id of the `sequence`th order placed for `key`, for
+/// requests that may place several.
+pub fn child_order_id(caller: &str, key: &str, sequence: u32) -> String {
+    client_order_id(caller, &format!("{}\n{}", key, sequence))
+}
+
+/// Hash of a request payload, to tell a retry from a reused key.
+pub fn fingerprint(payload: &Value) -> String {
+    hex(&Sha256::digest(payload.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn attempt(begin: Result<Begin, IdempotencyError>) -> Attempt {
+        match begin.unwrap() {
+            Begin::Attempt(v) => v,
+            v => panic!("expected an attempt, got {:?}", v),
+        }
+    }
+
+    fn outcome(status: u16, body: Value) -> Option<Outcome> {
+        Some(Outcome {
+            status,
+            message: None,
+            body,
+        })
+    }
+
+    #[test]
+    fn test_begin_and_finish() {
+        let store = IdempotencyStore::new();
+        let (a, b) = (fingerprint(&json!({"q": 1})), fingerprint(&json!({"q": 2})));
+
+        let first = attempt(store.begin("key:a", "k1", &a));
+        assert!(matches!(
+            store.begin("key:a", "k1", &a),
+            Err(IdempotencyError::InProgress(_))
+        ));
+        assert!(matches!(
+            store.begin("key:a", "k1", &b),
+            Err(IdempotencyError::Conflict(_))
+        ));
+
+        // A failed attempt may be retried
+        first.finish(None);
+        let second = attempt(store.begin("key:a", "k1", &a));
+
+        second.finish(outcome(200, json!({"orderId": 7})));
+        assert!(matches!(
+            store.begin("key:a", "k1", &a).unwrap(),
+            Begin::Done(v) if v.status == 200 && v.body == json!({"orderId": 7})
+        ));
+
+        // Keys are scoped by account
+        let _other = attempt(store.begin("key:b", "k1", &b));
+    }
+
+    #[test]
+    fn test_dropped_attempt() {
+        let store = IdempotencyStore::new();
+        let a = fingerprint(&json!({"q": 1}));
+
+        // A request abandoned mid-flight releases its key
+        let abandoned = attempt(store.begin("key:a", "k1", &a));
+        drop(abandoned);
+        let retry = attempt(store.begin("key:a", "k1", &a));
+
+        // An attempt that timed out must not release the retry that took
+        // its key over
+        let stale = Attempt {
+            caller: "key:a".into(),
+            key: "k1".into(),
+            id: u64::MAX,
+            slots: store.slots.clone(),
+        };
+        drop(stale);
+        assert!(matches!(
+            store.begin("key:a", "k1", &a),
+            Err(IdempotencyError::InProgress(_))
+        ));
+
+        // A refusal is replayed with its status
+        retry.finish(outcome(400, json!({"orderId": 8})));
+        assert!(matches!(
+            store.begin("key:a", "k1", &a).unwrap(),
+            Begin::Done(v) if v.status == 400
+        ));
+        assert_eq!(store.slots.lock().ignore_poison().len(), 1);
+    }
+
+    #[test]
+    fn test_client_order_id() {
+        let id = client_order_id("key:a", "k1");
+
+        assert_eq!(id.len(), CLIENT_ORDER_ID_LENGTH);
+        assert!(id.starts_with(CLIENT_ORDER_ID_PREFIX));
+        assert_eq!(id, client_order_id("key:a", "k1"));
+        assert_ne!(id, client_order_id("key:b", "k1"));
+
+        let child = child_order_id("key:a", "k1", 0);
+        assert_eq!(child.len(), CLIENT_ORDER_ID_LENGTH);
+        assert_ne!(child, id);
+        assert_ne!(child, child_order_id("key:a", "k1", 1));
+    }
+}
@@ -1,29 +1,39 @@
 pub mod api;
 
+pub mod audit;
+
+pub mod idempotency;
+
+pub mod retry;
+
+pub mod secret;
+
 pub mod services;
 
 mod time;
 
+pub mod trace;
+
 mod extension {
-    // use std::sync::LockResult;
-
-    // /// Extension methods for [`LockResult`].
-    // ///
-    // /// [`LockResult`]: https://doc.rust-lang.org/stable/std/sync/type.LockResult.html
-    // pub trait LockResultExt {
-    //     type Guard;
-
-    //     /// Returns the lock guard even if the mutex is [poisoned].
-    //     ///
-    //     /// [poisoned]: https://doc.rust-lang.org/stable/std/sync/struct.Mutex.html#poisoning
-    //     fn ignore_poison(self) -> Self::Guard;
-    // }
-
-    // impl<Guard> LockResultExt for LockResult<Guard> {
-    //     type Guard = Guard;
-
-    //     fn ignore_poison(self) -> Guard {
-    //         self.unwrap_or_else(|e| e.into_inner())
-    //     }
-    // }
+    use std::sync::LockResult;
+
+    /// Extension methods for [`LockResult`].
+    ///
+    /// [`LockResult`]: https://doc.rust-lang.org/stable/std/sync/type.LockResult.html
+    pub trait LockResultExt {
+        type Guard;
+
+        /// Returns the lock guard even if the mutex is [poisoned].
+        ///
+        /// [poisoned]: https://doc.rust-lang.org/stable/std/sync/struct.Mutex.html#poisoning
+        fn ignore_poison(self) -> Self::Guard;
+    }
+
+    impl<Guard> LockResultExt for LockResult<Guard> {
+        type Guard = Guard;
+
+        fn ignore_poison(self) -> Guard {
+            self.unwrap_or_else(|e| e.into_inner())
+        }
+    }
 }
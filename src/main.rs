@@ -1,13 +1,15 @@
 use std::{env, net::SocketAddr, sync::Arc};
 
-use axum::Router;
 use axum_server::tls_rustls::RustlsConfig;
 use harmony::api;
 use tracing_subscriber;
 
 #[tokio::main]
 async fn main() {
-    tracing_subscriber::fmt::init();
+    tracing_subscriber::fmt()
+        .json()
+        .with_current_span(true)
+        .init();
 
     let address = env::var("ADDRESS").unwrap_or("[::]:2053".into());
 
@@ -17,16 +19,7 @@ async fn main() {
     let router = {
         let state = Arc::new(api::State::new().await);
 
-        let router = Router::new();
-        let router = router.merge(api::general::router(state.clone()));
-
-        #[cfg(feature = "service-crypto")]
-        let router = router.merge(api::crypto::router(state.clone()));
-
-        #[cfg(feature = "service-binance")]
-        let router = router.merge(api::binance::router(state.clone()));
-
-        router
+        api::router(state)
     };
 
     let addr: SocketAddr = address.parse().unwrap();
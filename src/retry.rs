@@ -0,0 +1,302 @@
+use std::fmt::Display;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Shortest wait after a rate limit response without a `Retry-After`.
+const RATE_LIMIT_FLOOR: Duration = Duration::from_secs(1);
+
+/// How read-only upstream calls are retried.
+///
+/// Order placement must never go through here, a timed out order may still
+/// have been filled.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first.
+    pub attempts: u32,
+    pub base_delay: Duration,
+    /// Longest wait between attempts. A call asked to wait longer by its
+    /// `Retry-After` fails instead.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Whether a failed call is worth repeating.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verdict {
+    /// Retry, after the given delay if the upstream asked for one.
+    Retry(Option<Duration>),
+    Fail,
+}
+
+impl RetryPolicy {
+    /// The policy read from `UPSTREAM_RETRY_ATTEMPTS` once, otherwise the default.
+    pub fn global() -> &'static Self {
+        static POLICY: OnceLock<RetryPolicy> = OnceLock::new();
+
+        POLICY.get_or_init(|| {
+            let mut policy = Self::default();
+            if let Some(v) = std::env::var("UPSTREAM_RETRY_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+            {
+                policy.attempts = v;
+            }
+
+            policy
+        })
+    }
+
+    /// Runs `call` until it succeeds, fails for good or runs out of attempts.
+    pub async fn run<T, E, F, Fut>(&self, call: F) -> Result<T, E>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: Display,
+    {
+        let mut attempt = 1;
+        loop {
+            let error = match call().await {
+                Ok(v) => return Ok(v),
+                Err(e) => e,
+            };
+
+            let message = error.to_string();
+            let delay = match classify(&message) {
+                Verdict::Retry(_) if attempt >= self.attempts => return Err(error),
+                // Retrying any sooner than asked would only extend the limit
+                Verdict::Retry(Some(v)) if v > self.max_delay => return Err(error),
+                Verdict::Retry(Some(v)) => v,
+                Verdict::Retry(None) if is_rate_limit(&message) => {
+                    self.backoff(attempt).max(RATE_LIMIT_FLOOR)
+                }
+                Verdict::Retry(None) => self.backoff(attempt),
+                Verdict::Fail => return Err(error),
+            };
+
+            tracing::warn!(
+                attempt,
+                delay_ms = delay.as_millis() as u64,
+                error = %message,
+                "retrying upstream call"
+            );
+
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Exponential backoff with full jitter.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(self.max_delay);
+
+        ceiling.mul_f64(jitter())
+    }
+}
+
+/// Runs a read-only upstream call with the [global](RetryPolicy::global) policy.
+pub async fn read<T, E, F, Fut>(call: F) -> Result<T, E>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: Display,
+{
+    RetryPolicy::global().run(call).await
+}
+
+/// Decides from an upstream error message whether to retry.
+///
+/// Clients only hand back a message, so the HTTP status, Binance error code
+/// and `Retry-After` are read from its text.
+pub fn classify(message: &str) -> Verdict {
+    let status = status(message);
+
+    // A 418 is an IP ban, and every request made during it extends the ban
+    if status == Some(418) {
+        return Verdict::Fail;
+    }
+
+    if is_rate_limit(message) || matches!(status, Some(500..=599)) || is_network(message) {
+        return Verdict::Retry(retry_after(message));
+    }
+
+    Verdict::Fail
+}
+
+fn is_rate_limit(message: &str) -> bool {
+    // -1003 is the Binance code for too many requests
+    status(message) == Some(429) || error_code(message) == Some(-1003)
+}
+
+fn is_network(message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+
+    [
+        "timed out",
+        "timeout",
+        "connection",
+        "error sending request",
+        "broken pipe",
+    ]
+    .iter()
+    .any(|v| message.contains(v))
+}
+
+/// The HTTP error status the client puts at the start of the message.
+///
+/// Numbers further in belong to the body, such as a quantity or an order id,
+/// and are never read as the status.
+fn status(message: &str) -> Option<u16> {
+    message
+        .split_whitespace()
+        .next()?
+        .trim_end_matches(':')
+        .parse()
+        .ok()
+        .filter(|v| (400..600).contains(v))
+}
+
+/// The Binance error code in the JSON body the client puts after the status.
+pub fn error_code(message: &str) -> Option<i64> {
+    // Only the first JSON value, whatever follows it
+    let body = &message[message.find('{')?..];
+    let body = serde_json::Deserializer::from_str(body)
+        .into_iter::<serde_json::Value>()
+        .next()?
+        .ok()?;
+
+    body["code"].as_i64()
+}
+
+fn retry_after(message: &str) -> Option<Duration> {
+    let lower = message.to_ascii_lowercase();
+    let rest = &lower[lower.find("retry-after")? + "retry-after".len()..];
+
+    let seconds: String = rest
+        .trim_start_matches(|c: char| !c.is_ascii_digit())
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+
+    seconds.parse().ok().map(Duration::from_secs)
+}
+
+/// A number in `[0, 1)` from the randomly seeded std hasher.
+fn jitter() -> f64 {
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(crate::time::timestamp().as_nanos());
+
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let retry = |v| matches!(classify(v), Verdict::Retry(_));
+
+        assert!(retry("503 Service Unavailable"));
+        assert!(retry("error sending request: operation timed out"));
+        assert!(retry(
+            r#"400 Bad Request {"code":-1003,"msg":"Too many requests"}"#
+        ));
+        assert_eq!(
+            classify("429 Too Many Requests retry-after: 7"),
+            Verdict::Retry(Some(Duration::from_secs(7)))
+        );
+
+        assert_eq!(classify("418 I'm a teapot retry-after: 120"), Verdict::Fail);
+        assert_eq!(status("503: Service Unavailable"), Some(503));
+        assert_eq!(
+            classify(r#"400 Bad Request {"code":-1013,"msg":"Filter failure: 500 over 429"}"#),
+            Verdict::Fail
+        );
+        assert_eq!(classify("order 503 not found"), Verdict::Fail);
+        assert_eq!(
+            classify(r#"400 Bad Request {"code":-2010,"msg":"order 77-1003 rejected"}"#),
+            Verdict::Fail
+        );
+        assert_eq!(classify(r#"400 Bad Request {"code":-2013}"#), Verdict::Fail);
+        assert_eq!(
+            error_code(r#"400 Bad Request {"code":-2013,"msg":"Order does not exist."}"#),
+            Some(-2013)
+        );
+        assert_eq!(
+            error_code(r#"400 Bad Request {"code":-1013,"msg":"order -2013 failed"}"#),
+            Some(-1013)
+        );
+        assert_eq!(error_code("error sending request"), None);
+        assert_eq!(
+            classify(r#"400 Bad Request {"code":-1121,"msg":"Invalid symbol."}"#),
+            Verdict::Fail
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run() {
+        let policy = RetryPolicy {
+            attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+        };
+        let calls = AtomicU32::new(0);
+
+        let result: Result<u32, String> = policy
+            .run(|| async {
+                match calls.fetch_add(1, Ordering::Relaxed) {
+                    0 => Err("502 Bad Gateway".to_string()),
+                    v => Ok(v),
+                }
+            })
+            .await;
+        assert_eq!(result, Ok(1));
+
+        calls.store(0, Ordering::Relaxed);
+        let result: Result<u32, String> = policy
+            .run(|| async {
+                calls.fetch_add(1, Ordering::Relaxed);
+                Err("504 Gateway Timeout".to_string())
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::Relaxed), 3);
+
+        calls.store(0, Ordering::Relaxed);
+        let result: Result<u32, String> = policy
+            .run(|| async {
+                calls.fetch_add(1, Ordering::Relaxed);
+                Err("400 Bad Request".to_string())
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+        // Waiting two minutes does not fit in the policy, so it is not cut short
+        calls.store(0, Ordering::Relaxed);
+        let result: Result<u32, String> = policy
+            .run(|| async {
+                calls.fetch_add(1, Ordering::Relaxed);
+                Err("429 Too Many Requests retry-after: 120".to_string())
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,137 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+const REDACTED: &str = "[REDACTED]";
+
+/// A value that must never reach logs or serialized output.
+///
+/// `Debug` and `Serialize` both print a placeholder, the inner value is only
+/// reachable through [`Secret::expose`] or [`Secret::into_inner`].
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Secret<T>(T);
+
+impl<T> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> std::fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", REDACTED)
+    }
+}
+
+impl<T> Serialize for Secret<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(REDACTED)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Secret<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    use serde::{Deserialize, Serialize};
+
+    use super::Secret;
+
+    const API_KEY: &str = "vmPUZE6mv9SD5VNHk4HlWFsOr6aKE2zvsw0MuIgwCIPy6utIco14y7Ju91duEh8A";
+    const SECRET_KEY: &str = "NhqPtmdSJYdKjVHjA7PZj4Mge3R5YNiP1e3UZjInClVN65XAbvqqM6A7H5fATj0j";
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Payload {
+        api_key: Secret<String>,
+        secret_key: Secret<String>,
+        symbol: String,
+    }
+
+    fn payload() -> Payload {
+        let body = format!(
+            r#"{{"api_key":"{}","secret_key":"{}","symbol":"ETHUSDT"}}"#,
+            API_KEY, SECRET_KEY
+        );
+
+        serde_json::from_str(&body).unwrap()
+    }
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_deserialize_keeps_value() {
+        let p = payload();
+        assert_eq!(p.api_key.expose(), API_KEY);
+        assert_eq!(p.secret_key.into_inner(), SECRET_KEY);
+    }
+
+    #[test]
+    fn test_debug_and_serialize_redact() {
+        let p = payload();
+
+        let debug = format!("{:?}", p);
+        assert!(!debug.contains(API_KEY));
+        assert!(!debug.contains(SECRET_KEY));
+        assert!(debug.contains("ETHUSDT"));
+
+        let json = serde_json::to_string(&p).unwrap();
+        assert!(!json.contains(API_KEY));
+        assert!(!json.contains(SECRET_KEY));
+    }
+
+    #[test]
+    fn test_tracing_span_redacts() {
+        #[tracing::instrument]
+        fn handler(p: Payload) {
+            tracing::debug!(?p, "handling");
+        }
+
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::TRACE)
+            .with_writer(move || writer.clone())
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || handler(payload()));
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("ETHUSDT"));
+        assert!(!output.contains(API_KEY));
+        assert!(!output.contains(SECRET_KEY));
+    }
+}
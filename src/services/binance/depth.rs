@@ -0,0 +1,215 @@
+use std::error::Error;
+use std::str::FromStr;
+
+use binance::prelude::Client;
+use binance::types::{OrderSide, Symbol};
+use plot::types::{Decimal, Price, Quantity};
+use serde::{Deserialize, Serialize};
+
+use crate::retry;
+
+use super::risk::RiskError;
+
+/// Levels per side fetched when the caller does not ask for a depth.
+pub const DEFAULT_DEPTH_LIMIT: u16 = 100;
+
+/// Decimal places of the price impact percentage.
+const IMPACT_SCALE: u32 = 4;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Level {
+    pub price: Price,
+    pub quantity: Quantity,
+}
+
+/// Resting orders of a symbol, best price first on both sides.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderBook {
+    pub symbol: Symbol,
+    pub last_update_id: i64,
+    pub bids: Vec<Level>,
+    pub asks: Vec<Level>,
+}
+
+/// How much of the book a market order would consume.
+///
+/// `quantity` is in quote asset for a buy and base asset for a sell, the
+/// same as the order endpoints.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlippageEstimate {
+    pub side: OrderSide,
+    pub quantity: Quantity,
+    pub best_price: Option<Price>,
+    pub average_price: Option<Price>,
+    pub worst_price: Option<Price>,
+    pub base_quantity: Quantity,
+    pub quote_quantity: Quantity,
+    /// Distance of the average price from the best price, in percent.
+    pub impact: Option<Decimal>,
+    /// Whether the fetched levels hold the whole quantity.
+    pub filled: bool,
+}
+
+impl OrderBook {
+    pub async fn fetch(
+        client: &Client,
+        symbol: &Symbol,
+        limit: u16,
+    ) -> Result<Self, Box<dyn Error>> {
+        let depth = retry::read(|| client.depth(symbol, Some(limit))).await?;
+
+        let levels = |v: &[(String, String)]| -> Result<Vec<Level>, Box<dyn Error>> {
+            v.iter()
+                .map(|(price, quantity)| {
+                    Ok(Level {
+                        price: Decimal::from_str(price)?,
+                        quantity: Decimal::from_str(quantity)?,
+                    })
+                })
+                .collect()
+        };
+
+        Ok(Self {
+            symbol: symbol.clone(),
+            last_update_id: depth.last_update_id,
+            bids: levels(&depth.bids)?,
+            asks: levels(&depth.asks)?,
+        })
+    }
+
+    /// Walks the asks for a buy spending `quantity` quote, or the bids for a
+    /// sell of `quantity` base.
+    pub fn estimate(&self, side: OrderSide, quantity: &Quantity) -> SlippageEstimate {
+        let levels = match side {
+            OrderSide::Buy => &self.asks,
+            OrderSide::Sell => &self.bids,
+        };
+
+        let mut remaining = *quantity;
+        let mut base = Decimal::ZERO;
+        let mut quote = Decimal::ZERO;
+        let mut worst = None;
+
+        for level in levels.iter() {
+            if remaining <= Decimal::ZERO {
+                break;
+            }
+
+            let (level_base, level_quote) = match side {
+                OrderSide::Buy => {
+                    let level_quote = remaining.min(level.price * level.quantity);
+                    (level_quote / level.price, level_quote)
+                }
+                OrderSide::Sell => {
+                    let level_base = remaining.min(level.quantity);
+                    (level_base, level_base * level.price)
+                }
+            };
+
+            base += level_base;
+            quote += level_quote;
+            remaining -= match side {
+                OrderSide::Buy => level_quote,
+                OrderSide::Sell => level_base,
+            };
+            worst = Some(level.price);
+        }
+
+        let best = levels.first().map(|v| v.price);
+        let average = match base.is_zero() {
+            true => None,
+            false => Some(quote / base),
+        };
+        let impact = match (best, average) {
+            (Some(best), Some(average)) => {
+                let distance = match side {
+                    OrderSide::Buy => average - best,
+                    OrderSide::Sell => best - average,
+                };
+                Some((distance / best * Decimal::ONE_HUNDRED).round_dp(IMPACT_SCALE))
+            }
+            _ => None,
+        };
+
+        SlippageEstimate {
+            side,
+            quantity: *quantity,
+            best_price: best,
+            average_price: average,
+            worst_price: worst,
+            base_quantity: base,
+            quote_quantity: quote,
+            impact,
+            filled: remaining <= Decimal::ZERO,
+        }
+    }
+}
+
+/// Refuses a market order whose estimated price impact exceeds
+/// `max_slippage` percent, or that the book cannot fill at all.
+pub async fn check_slippage(
+    client: &Client,
+    symbol: &Symbol,
+    side: OrderSide,
+    quantity: &Quantity,
+    max_slippage: &Decimal,
+) -> Result<SlippageEstimate, Box<dyn Error>> {
+    let book = OrderBook::fetch(client, symbol, DEFAULT_DEPTH_LIMIT).await?;
+    let estimate = book.estimate(side, quantity);
+
+    match (estimate.filled, estimate.impact) {
+        (true, Some(impact)) if impact <= *max_slippage => Ok(estimate),
+        (true, Some(impact)) => Err(RiskError::MaxSlippage(format!(
+            "estimated impact {}% exceeds {}%",
+            impact, max_slippage
+        ))
+        .into()),
+        _ => Err(RiskError::MaxSlippage(format!(
+            "order book of {} too thin for {}",
+            symbol, quantity
+        ))
+        .into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book() -> OrderBook {
+        let level = |price: i64, quantity: i64| Level {
+            price: Decimal::from(price),
+            quantity: Decimal::from(quantity),
+        };
+
+        OrderBook {
+            symbol: "ETHUSDT".into(),
+            last_update_id: 1,
+            bids: vec![level(99, 1), level(98, 1), level(96, 2)],
+            asks: vec![level(100, 1), level(102, 1), level(105, 2)],
+        }
+    }
+
+    #[test]
+    fn test_estimate_buy() {
+        let estimate = book().estimate(OrderSide::Buy, &Decimal::from(202));
+
+        assert_eq!(estimate.base_quantity, Decimal::from(2));
+        assert_eq!(estimate.average_price, Some(Decimal::from(101)));
+        assert_eq!(estimate.worst_price, Some(Decimal::from(102)));
+        assert_eq!(estimate.impact, Some(Decimal::from(1)));
+        assert!(estimate.filled);
+    }
+
+    #[test]
+    fn test_estimate_sell() {
+        let estimate = book().estimate(OrderSide::Sell, &Decimal::from(2));
+        assert_eq!(estimate.quote_quantity, Decimal::from(197));
+        assert_eq!(estimate.worst_price, Some(Decimal::from(98)));
+        assert!(estimate.filled);
+
+        let estimate = book().estimate(OrderSide::Sell, &Decimal::from(5));
+        assert_eq!(estimate.base_quantity, Decimal::from(4));
+        assert!(!estimate.filled);
+    }
+}
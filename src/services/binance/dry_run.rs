@@ -0,0 +1,190 @@
+use std::error::Error;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use binance::prelude::Client;
+use binance::types::{Asset, OrderSide, Symbol, SymbolInfo};
+use plot::trade::{Trade, Trader};
+use plot::types::{BaseQuantity, Decimal, Price, Quantity, QuoteQuantity};
+use serde::{Deserialize, Serialize};
+
+use crate::extension::LockResultExt;
+use crate::retry;
+
+use super::filter::spot::trading;
+use super::BinanceSpotTest;
+
+/// Taker commission of a simulated fill when the request sets none, the
+/// standard spot rate before any discount.
+pub fn default_commission() -> Decimal {
+    Decimal::new(1, 3)
+}
+
+/// An order that would have been sent to the exchange.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulatedOrder {
+    pub symbol: Symbol,
+    pub side: OrderSide,
+    pub price: Price,
+    pub quantity: Quantity,
+    pub corrected_quantity: Quantity,
+    pub trades: Vec<Trade>,
+}
+
+/// Free balances of the two assets of a symbol.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulatedBalance {
+    pub base_asset: Asset,
+    pub base: BaseQuantity,
+    pub quote_asset: Asset,
+    pub quote: QuoteQuantity,
+}
+
+impl SimulatedBalance {
+    /// Current free balances of the account behind `client`.
+    pub async fn fetch(client: &Client, normal: &SymbolInfo) -> Result<Self, Box<dyn Error>> {
+        Ok(Self::fetch_with_held(client, normal).await?.0)
+    }
+
+    /// Current free balances, and the base asset held free and locked, from
+    /// a single request.
+    pub async fn fetch_with_held(
+        client: &Client,
+        normal: &SymbolInfo,
+    ) -> Result<(Self, BaseQuantity), Box<dyn Error>> {
+        let assets = retry::read(|| client.user_asset(None, Some(false), None)).await?;
+
+        let free = |asset: &Asset| -> Result<Decimal, Box<dyn Error>> {
+            match assets.iter().find(|v| &v.asset == asset) {
+                Some(v) => Ok(Decimal::from_str(&v.free)?),
+                None => Ok(Decimal::ZERO),
+            }
+        };
+
+        let mut held = Decimal::ZERO;
+        for v in assets.iter().filter(|v| v.asset == normal.base_asset) {
+            held += Decimal::from_str(&v.free)? + Decimal::from_str(&v.locked)?;
+        }
+
+        let balance = Self {
+            base: free(&normal.base_asset)?,
+            base_asset: normal.base_asset.clone(),
+            quote: free(&normal.quote_asset)?,
+            quote_asset: normal.quote_asset.clone(),
+        };
+
+        Ok((balance, held))
+    }
+}
+
+/// Everything a dry run would have done to the account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DryRunReport {
+    pub orders: Vec<SimulatedOrder>,
+    pub balance_before: SimulatedBalance,
+    pub balance_after: SimulatedBalance,
+}
+
+/// Executor that runs the same corrections and filters as [`super::BinanceSpot`]
+/// against a simulated balance instead of placing orders.
+pub struct BinanceSpotDryRun {
+    agent: BinanceSpotTest,
+    initial: SimulatedBalance,
+    balance: Mutex<SimulatedBalance>,
+    orders: Mutex<Vec<SimulatedOrder>>,
+}
+
+impl BinanceSpotDryRun {
+    pub fn new(normal: SymbolInfo, commission: Decimal, balance: SimulatedBalance) -> Self {
+        Self {
+            agent: BinanceSpotTest::new(normal, commission),
+            initial: balance.clone(),
+            balance: Mutex::new(balance),
+            orders: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn report(&self) -> DryRunReport {
+        DryRunReport {
+            orders: self.orders.lock().ignore_poison().clone(),
+            balance_before: self.initial.clone(),
+            balance_after: self.balance.lock().ignore_poison().clone(),
+        }
+    }
+
+    fn push_order(
+        &self,
+        side: OrderSide,
+        price: &Price,
+        quantity: &Quantity,
+        corrected_quantity: Quantity,
+        trades: &[Trade],
+    ) {
+        self.orders.lock().ignore_poison().push(SimulatedOrder {
+            symbol: self.agent.normal().symbol.clone(),
+            side,
+            price: *price,
+            quantity: *quantity,
+            corrected_quantity,
+            trades: trades.to_vec(),
+        });
+    }
+}
+
+impl Trader for BinanceSpotDryRun {
+    async fn buy(
+        &self,
+        price: &Price,
+        quantity: &QuoteQuantity,
+    ) -> Result<Vec<Trade>, Box<dyn Error>> {
+        trading::filter(self.agent.normal(), trading::MARKET)?;
+        let (quote_quantity, base_quantity) = self.agent.fill_buy(price, quantity)?;
+
+        {
+            let mut balance = self.balance.lock().ignore_poison();
+            if balance.quote < quote_quantity {
+                return Err(format!(
+                    "insufficient {} balance {} for quote quantity {}",
+                    balance.quote_asset, balance.quote, quote_quantity
+                )
+                .into());
+            }
+
+            balance.quote -= quote_quantity;
+            balance.base += base_quantity;
+        }
+
+        let trades = vec![Trade::with_buy(*price, base_quantity, quote_quantity)];
+        self.push_order(OrderSide::Buy, price, quantity, quote_quantity, &trades);
+
+        Ok(trades)
+    }
+
+    async fn sell(
+        &self,
+        price: &Price,
+        quantity: &BaseQuantity,
+    ) -> Result<Vec<Trade>, Box<dyn Error>> {
+        trading::filter(self.agent.normal(), trading::MARKET)?;
+        let (base_quantity, quote_quantity) = self.agent.fill_sell(price, quantity)?;
+
+        {
+            let mut balance = self.balance.lock().ignore_poison();
+            if balance.base < base_quantity {
+                return Err(format!(
+                    "insufficient {} balance {} for base quantity {}",
+                    balance.base_asset, balance.base, base_quantity
+                )
+                .into());
+            }
+
+            balance.base -= base_quantity;
+            balance.quote += quote_quantity;
+        }
+
+        let trades = vec![Trade::with_sell(*price, base_quantity, quote_quantity)];
+        self.push_order(OrderSide::Sell, price, quantity, base_quantity, &trades);
+
+        Ok(trades)
+    }
+}
@@ -0,0 +1,194 @@
+use std::error::Error;
+use std::str::FromStr;
+
+use binance::prelude::Client;
+use binance::types::{Asset, Symbol, SymbolInfo, SymbolPrice, UserAsset};
+use plot::types::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::retry;
+
+use super::portfolio::{fetch_normals, Portfolio};
+use super::ConvertFilter;
+
+/// Asset the exchange converts dust into.
+pub const DUST_TARGET: &str = "BNB";
+
+/// Decimal places of values in the quote asset and in BNB.
+const VALUE_SCALE: u32 = 8;
+
+/// A free balance too small to be sold on its pair with the quote asset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DustBalance {
+    pub asset: Asset,
+    pub free: Decimal,
+    pub symbol: Symbol,
+    pub price: Decimal,
+    /// Value in the quote asset.
+    pub value: Decimal,
+    /// Estimated BNB received before the exchange's conversion fee, unset
+    /// when there is no route to BNB.
+    pub bnb_value: Option<Decimal>,
+    /// The filter that refuses to sell the balance.
+    pub reason: String,
+}
+
+/// Every dust balance of an account.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DustReport {
+    pub quote: Asset,
+    pub balances: Vec<DustBalance>,
+    pub total_value: Decimal,
+    pub total_bnb_value: Decimal,
+    /// Assets with a free balance but no pair with the quote asset to
+    /// check the filters of.
+    pub unchecked: Vec<Asset>,
+}
+
+impl DustReport {
+    /// Finds the dust of the account behind `client`, judged by the
+    /// filters of each asset's pair with `quote`.
+    pub async fn fetch(client: &Client, quote: &Asset) -> Result<Self, Box<dyn Error>> {
+        let assets = retry::read(|| client.user_asset(None, Some(false), None)).await?;
+        let prices = retry::read(|| client.prices(None)).await?;
+
+        // The pairs with the quote are checked, those on the way to BNB valued
+        let quotes = [quote.clone(), DUST_TARGET.into()];
+        let normals = fetch_normals(client, &assets, &prices, &quotes).await?;
+
+        Self::find(&assets, &prices, &normals, quote)
+    }
+
+    pub fn find(
+        assets: &[UserAsset],
+        prices: &[SymbolPrice],
+        normals: &[SymbolInfo],
+        quote: &Asset,
+    ) -> Result<Self, Box<dyn Error>> {
+        let bnb = Portfolio::value(assets, prices, normals, &DUST_TARGET.into())?;
+
+        let mut balances = Vec::new();
+        let mut unchecked = Vec::new();
+        for v in assets.iter().filter(|v| is_candidate(v, quote)) {
+            let free = Decimal::from_str(&v.free)?;
+            let normal = normals
+                .iter()
+                .find(|n| n.base_asset == v.asset && n.quote_asset == *quote);
+            let price = normal.and_then(|n| prices.iter().find(|p| p.symbol == n.symbol));
+            let (Some(price), Some(normal)) = (price, normal) else {
+                unchecked.push(v.asset.clone());
+                continue;
+            };
+            let price = Decimal::from_str(&price.price)?;
+
+            let corrected = normal
+                .correct_base_quantity(&price, &free)
+                .and_then(|q| normal.filter_base_quantity(&price, &q));
+            let Err(e) = corrected else {
+                continue;
+            };
+
+            let bnb_price = bnb
+                .holdings
+                .iter()
+                .find(|h| h.asset == v.asset)
+                .and_then(|h| h.price);
+
+            balances.push(DustBalance {
+                asset: v.asset.clone(),
+                free,
+                symbol: normal.symbol.clone(),
+                price,
+                value: (free * price).round_dp(VALUE_SCALE),
+                bnb_value: bnb_price.map(|p| (free * p).round_dp(VALUE_SCALE)),
+                reason: e.to_string(),
+            });
+        }
+
+        Ok(Self {
+            quote: quote.clone(),
+            total_value: balances.iter().map(|v| v.value).sum(),
+            total_bnb_value: balances.iter().filter_map(|v| v.bnb_value).sum(),
+            balances,
+            unchecked,
+        })
+    }
+
+    pub fn assets(&self) -> Vec<Asset> {
+        self.balances.iter().map(|v| v.asset.clone()).collect()
+    }
+}
+
+/// Converts `assets` into BNB through the dust transfer endpoint. Never
+/// retried, the exchange may have converted them when the response is lost.
+pub async fn convert(
+    client: &Client,
+    assets: &[Asset],
+) -> Result<serde_json::Value, Box<dyn Error>> {
+    let result = client.dust_transfer(assets, None).await?;
+
+    Ok(serde_json::to_value(result)?)
+}
+
+/// A free balance that is neither the quote asset nor BNB itself.
+fn is_candidate(asset: &UserAsset, quote: &Asset) -> bool {
+    &asset.asset != quote
+        && asset.asset != DUST_TARGET
+        && Decimal::from_str(&asset.free).is_ok_and(|v| v > Decimal::ZERO)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::binance::filter::spot::tests::pair_norms;
+
+    fn asset(asset: &str, free: &str) -> UserAsset {
+        UserAsset {
+            asset: asset.into(),
+            free: free.into(),
+            locked: "0".into(),
+            freeze: "0".into(),
+            withdrawing: "0".into(),
+            ipoable: "0".into(),
+            btc_valuation: "0".into(),
+        }
+    }
+
+    fn price(symbol: &str, price: &str) -> SymbolPrice {
+        SymbolPrice {
+            symbol: symbol.into(),
+            price: price.into(),
+        }
+    }
+
+    #[test]
+    fn test_find() {
+        let assets = vec![
+            asset("USDT", "100"),
+            asset("BNB", "0.001"),
+            asset("ETH", "0.001"),
+            asset("SOL", "1"),
+            asset("XYZ", "3"),
+        ];
+        let prices = vec![
+            price("ETHUSDT", "3000"),
+            price("SOLUSDT", "150"),
+            price("BNBUSDT", "600"),
+        ];
+        let normals = vec![
+            pair_norms("ETHUSDT", "ETH", "USDT"),
+            pair_norms("SOLUSDT", "SOL", "USDT"),
+            pair_norms("BNBUSDT", "BNB", "USDT"),
+        ];
+
+        let report = DustReport::find(&assets, &prices, &normals, &"USDT".into()).unwrap();
+        assert_eq!(report.assets(), vec!["ETH"]);
+        assert_eq!(report.unchecked, vec!["XYZ"]);
+
+        let eth = &report.balances[0];
+        assert_eq!(eth.value, Decimal::from(3));
+        assert_eq!(eth.bnb_value, Some(Decimal::new(5, 3)));
+        assert!(eth.reason.starts_with("FILTER NOTIONAL"));
+        assert_eq!(report.total_value, Decimal::from(3));
+    }
+}
@@ -0,0 +1,156 @@
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+const PRODUCTION_BASE_URL: &str = "https://api.binance.com";
+const TESTNET_BASE_URL: &str = "https://testnet.binance.vision";
+const FUTURES_PRODUCTION_BASE_URL: &str = "https://fapi.binance.com";
+const FUTURES_TESTNET_BASE_URL: &str = "https://testnet.binancefuture.com";
+
+/// REST endpoint the Binance client talks to.
+///
+/// Written as `"production"`, `"testnet"` or their base urls. Any other base
+/// url, such as `"http://127.0.0.1:8080"` for a local mock, is only accepted
+/// from `BINANCE_ENDPOINT`, and a request may then name the same url again.
+/// Requests can not point a client holding their keys anywhere else.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Endpoint {
+    #[default]
+    Production,
+    Testnet,
+    Url(String),
+}
+
+impl Endpoint {
+    pub fn base_url(&self) -> &str {
+        match self {
+            Self::Production => PRODUCTION_BASE_URL,
+            Self::Testnet => TESTNET_BASE_URL,
+            Self::Url(v) => v.as_str(),
+        }
+    }
+
+    /// Base url of the USDⓈ-M futures API, a url endpoint serves both.
+    pub fn futures_base_url(&self) -> &str {
+        match self {
+            Self::Production => FUTURES_PRODUCTION_BASE_URL,
+            Self::Testnet => FUTURES_TESTNET_BASE_URL,
+            Self::Url(v) => v.as_str(),
+        }
+    }
+
+    /// Reads `BINANCE_ENDPOINT`, production when unset. Checked once at
+    /// startup so [`global`](Self::global) can not fail later.
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var("BINANCE_ENDPOINT") {
+            Ok(v) => match Self::named(&v) {
+                Some(v) => Ok(v),
+                None => Ok(Self::Url(url(&v)?)),
+            },
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// Endpoint configured by `BINANCE_ENDPOINT`, production when unset.
+    pub fn global() -> &'static Endpoint {
+        static GLOBAL: OnceLock<Endpoint> = OnceLock::new();
+
+        GLOBAL.get_or_init(|| Self::from_env().expect("invalid BINANCE_ENDPOINT"))
+    }
+
+    fn named(value: &str) -> Option<Self> {
+        match value.trim_end_matches('/') {
+            "production" | PRODUCTION_BASE_URL => Some(Self::Production),
+            "testnet" | TESTNET_BASE_URL => Some(Self::Testnet),
+            _ => None,
+        }
+    }
+
+    /// Parses an endpoint named by a request, a url only when it is the
+    /// `configured` one.
+    fn allow(value: &str, configured: &Endpoint) -> Result<Self, String> {
+        if let Some(v) = Self::named(value) {
+            return Ok(v);
+        }
+
+        let url = url(value)?;
+        match configured {
+            Self::Url(v) if *v == url => Ok(Self::Url(url)),
+            _ => Err(format!(
+                "binance endpoint {} is not allowed, use production, testnet or the configured endpoint",
+                url
+            )),
+        }
+    }
+}
+
+fn url(value: &str) -> Result<String, String> {
+    match value.starts_with("http://") || value.starts_with("https://") {
+        true => Ok(value.trim_end_matches('/').to_string()),
+        false => Err(format!(
+            "binance endpoint {} is neither production, testnet nor a http(s) url",
+            value
+        )),
+    }
+}
+
+impl TryFrom<String> for Endpoint {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::allow(&value, Self::global())
+    }
+}
+
+impl From<Endpoint> for String {
+    fn from(value: Endpoint) -> Self {
+        match value {
+            Endpoint::Production => "production".into(),
+            Endpoint::Testnet => "testnet".into(),
+            Endpoint::Url(v) => v,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allow() {
+        let production = Endpoint::Production;
+        assert_eq!(
+            Endpoint::allow("production", &production),
+            Ok(Endpoint::Production)
+        );
+        assert_eq!(
+            Endpoint::allow("testnet", &production),
+            Ok(Endpoint::Testnet)
+        );
+        assert_eq!(
+            Endpoint::allow("https://testnet.binance.vision/", &production),
+            Ok(Endpoint::Testnet)
+        );
+        assert!(Endpoint::allow("https://attacker.example", &production).is_err());
+        assert!(Endpoint::allow("http://169.254.169.254/latest", &production).is_err());
+        assert!(Endpoint::allow("ftp://127.0.0.1", &production).is_err());
+        assert!(Endpoint::allow("staging", &production).is_err());
+
+        // The operator's own endpoint may be named again, and only that one
+        let mock = Endpoint::Url("http://127.0.0.1:8080".into());
+        assert_eq!(
+            Endpoint::allow("http://127.0.0.1:8080/", &mock),
+            Ok(mock.clone())
+        );
+        assert!(Endpoint::allow("http://127.0.0.1:8081", &mock).is_err());
+    }
+
+    #[test]
+    fn test_serde() {
+        let endpoint: Endpoint = serde_json::from_str(r#""testnet""#).unwrap();
+        assert_eq!(endpoint, Endpoint::Testnet);
+        assert_eq!(serde_json::to_string(&endpoint).unwrap(), r#""testnet""#);
+        assert!(serde_json::from_str::<Endpoint>(r#""https://attacker.example""#).is_err());
+    }
+}
@@ -0,0 +1,457 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use binance::types::{OrderResponseFull, OrderSide, Symbol};
+use plot::types::{Decimal, Price, Quantity};
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+use crate::extension::LockResultExt;
+use crate::retry;
+use crate::time::timestamp;
+
+use super::{BinanceSpot, ConvertFilter};
+
+/// Longest pause between child orders accepted.
+const MAX_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Most child orders one execution may place.
+const MAX_CHILDREN: u32 = 1000;
+
+/// Most stopped executions kept for their report, the oldest are dropped
+/// first.
+const MAX_FINISHED: usize = 1000;
+
+/// How a parent quantity is split into child market orders.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "algorithm", rename_all = "lowercase")]
+pub enum Schedule {
+    /// Equal slices spread evenly over time.
+    Twap { slices: u32, interval_secs: u64 },
+    /// Slices of at most `visible` quantity, one after the other.
+    Iceberg {
+        visible: Quantity,
+        #[serde(default)]
+        interval_secs: u64,
+    },
+}
+
+impl Schedule {
+    fn validate(&self, quantity: &Quantity) -> Result<(), String> {
+        let (interval, children) = match self {
+            Self::Twap {
+                slices,
+                interval_secs,
+            } => (*interval_secs, Decimal::from(*slices)),
+            Self::Iceberg {
+                visible,
+                interval_secs,
+            } if *visible > Decimal::ZERO => (*interval_secs, (quantity / visible).ceil()),
+            Self::Iceberg { .. } => return Err("visible quantity must be positive".into()),
+        };
+
+        if Duration::from_secs(interval) > MAX_INTERVAL {
+            return Err(format!(
+                "interval exceeds {} seconds",
+                MAX_INTERVAL.as_secs()
+            ));
+        }
+        if children < Decimal::ONE || children > Decimal::from(MAX_CHILDREN) {
+            return Err(format!(
+                "between 1 and {} child orders are allowed",
+                MAX_CHILDREN
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn interval(&self) -> Duration {
+        match self {
+            Self::Twap { interval_secs, .. } | Self::Iceberg { interval_secs, .. } => {
+                Duration::from_secs(*interval_secs)
+            }
+        }
+    }
+
+    /// Quantity of the next child, given what is left and how many were placed.
+    fn child(&self, quantity: &Quantity, remaining: &Quantity, placed: u32) -> Quantity {
+        match self {
+            Self::Twap { slices, .. } if placed + 1 >= *slices => *remaining,
+            Self::Twap { slices, .. } => (quantity / Decimal::from(*slices)).min(*remaining),
+            Self::Iceberg { visible, .. } => visible.min(remaining).to_owned(),
+        }
+    }
+
+    fn done(&self, placed: u32) -> bool {
+        match self {
+            Self::Twap { slices, .. } => placed >= *slices,
+            Self::Iceberg { .. } => placed >= MAX_CHILDREN,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExecutionStatus {
+    Running,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChildOrder {
+    pub order_id: Option<i64>,
+    /// Quantity asked for before the symbol filters corrected it.
+    pub quantity: Quantity,
+    pub base_quantity: Quantity,
+    pub quote_quantity: Quantity,
+    pub error: Option<String>,
+    pub time: u128,
+}
+
+/// Progress of one parent order, and its fill report once it stopped.
+///
+/// `quantity` and `remaining` are in quote asset for a buy and base asset
+/// for a sell, the same as the order endpoints.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Execution {
+    pub id: String,
+    pub caller: String,
+    pub symbol: Symbol,
+    pub side: OrderSide,
+    pub quantity: Quantity,
+    pub schedule: Schedule,
+    pub status: ExecutionStatus,
+    pub remaining: Quantity,
+    pub base_quantity: Quantity,
+    pub quote_quantity: Quantity,
+    pub average_price: Option<Price>,
+    pub children: Vec<ChildOrder>,
+    pub started: u128,
+    pub finished: Option<u128>,
+}
+
+impl Execution {
+    fn record(&mut self, child: ChildOrder) {
+        self.base_quantity += child.base_quantity;
+        self.quote_quantity += child.quote_quantity;
+        self.remaining -= match self.side {
+            OrderSide::Buy => child.quote_quantity,
+            OrderSide::Sell => child.base_quantity,
+        };
+        self.remaining = self.remaining.max(Decimal::ZERO);
+        self.average_price = match self.base_quantity.is_zero() {
+            true => None,
+            false => Some(self.quote_quantity / self.base_quantity),
+        };
+        self.children.push(child);
+    }
+}
+
+/// An execution and the signal that stops it.
+#[derive(Debug)]
+struct Slot {
+    execution: Execution,
+    cancel: watch::Sender<bool>,
+}
+
+/// Executions started by this process, the last [`MAX_FINISHED`] stopped
+/// ones kept for their report.
+#[derive(Debug, Default)]
+pub struct Executions {
+    executions: Mutex<HashMap<String, Slot>>,
+}
+
+impl Executions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts splitting `quantity` into child orders placed through `agent`
+    /// in the background, returning the execution as it starts.
+    pub fn start(
+        self: &Arc<Self>,
+        agent: BinanceSpot,
+        caller: String,
+        side: OrderSide,
+        quantity: Quantity,
+        schedule: Schedule,
+    ) -> Result<Execution, String> {
+        if quantity <= Decimal::ZERO {
+            return Err("quantity must be positive".into());
+        }
+        schedule.validate(&quantity)?;
+
+        let execution = Execution {
+            id: format!("exec-{}", crate::trace::generate()),
+            caller,
+            symbol: agent.normal.symbol.clone(),
+            side,
+            quantity,
+            schedule,
+            status: ExecutionStatus::Running,
+            remaining: quantity,
+            base_quantity: Decimal::ZERO,
+            quote_quantity: Decimal::ZERO,
+            average_price: None,
+            children: Vec::new(),
+            started: timestamp().as_millis(),
+            finished: None,
+        };
+        let (cancel, cancelled) = watch::channel(false);
+
+        {
+            let mut executions = self.executions.lock().ignore_poison();
+            prune(&mut executions);
+
+            let slot = Slot {
+                execution: execution.clone(),
+                cancel,
+            };
+            executions.insert(execution.id.clone(), slot);
+        }
+
+        // Child client order ids derive from the execution id
+        let task = crate::trace::scope(
+            execution.id.clone(),
+            self.clone().run(execution.clone(), agent, cancelled),
+        );
+        tokio::spawn(task);
+
+        Ok(execution)
+    }
+
+    /// The execution `id` of `caller`, if there is one.
+    pub fn get(&self, caller: &str, id: &str) -> Option<Execution> {
+        let executions = self.executions.lock().ignore_poison();
+
+        executions
+            .get(id)
+            .map(|v| &v.execution)
+            .filter(|v| v.caller == caller)
+            .cloned()
+    }
+
+    /// Asks a running execution to stop before its next child order.
+    pub fn cancel(&self, caller: &str, id: &str) -> Option<Execution> {
+        let executions = self.executions.lock().ignore_poison();
+        let slot = executions
+            .get(id)
+            .filter(|v| v.execution.caller == caller)?;
+        if slot.execution.status == ExecutionStatus::Running {
+            slot.cancel.send_replace(true);
+        }
+
+        Some(slot.execution.clone())
+    }
+
+    fn update<T>(&self, id: &str, f: impl FnOnce(&mut Execution) -> T) -> Option<T> {
+        let mut executions = self.executions.lock().ignore_poison();
+
+        executions.get_mut(id).map(|v| f(&mut v.execution))
+    }
+
+    fn finish(&self, id: &str, status: ExecutionStatus, cancelled: bool) {
+        self.update(id, |v| {
+            v.status = match cancelled {
+                true if status == ExecutionStatus::Completed => ExecutionStatus::Cancelled,
+                _ => status,
+            };
+            v.finished = Some(timestamp().as_millis());
+        });
+    }
+
+    async fn run(
+        self: Arc<Self>,
+        execution: Execution,
+        agent: BinanceSpot,
+        mut cancelled: watch::Receiver<bool>,
+    ) {
+        let id = execution.id;
+
+        let mut placed = 0;
+        loop {
+            let remaining = self.update(&id, |v| v.remaining).unwrap_or_default();
+            if remaining <= Decimal::ZERO || execution.schedule.done(placed) {
+                let cancelled = *cancelled.borrow();
+                return self.finish(&id, ExecutionStatus::Completed, cancelled);
+            }
+            if *cancelled.borrow() {
+                return self.finish(&id, ExecutionStatus::Cancelled, true);
+            }
+
+            if placed > 0 && !wait(&mut cancelled, execution.schedule.interval()).await {
+                return self.finish(&id, ExecutionStatus::Cancelled, true);
+            }
+
+            let quantity = execution
+                .schedule
+                .child(&execution.quantity, &remaining, placed);
+            let child = place(&agent, &execution.side, quantity).await;
+            placed += 1;
+
+            let status = match &child {
+                // What is left after the filters corrected the children is too small to send
+                Err(Rejected::Filter(_)) if placed > 1 && quantity == remaining => {
+                    Some(ExecutionStatus::Completed)
+                }
+                Err(_) => Some(ExecutionStatus::Failed),
+                Ok(_) => None,
+            };
+
+            let child = match child {
+                Ok(v) => v,
+                Err(Rejected::Filter(e) | Rejected::Order(e)) => ChildOrder {
+                    order_id: None,
+                    quantity,
+                    base_quantity: Decimal::ZERO,
+                    quote_quantity: Decimal::ZERO,
+                    error: Some(e),
+                    time: timestamp().as_millis(),
+                },
+            };
+            self.update(&id, |v| v.record(child));
+
+            if let Some(status) = status {
+                let cancelled = *cancelled.borrow();
+                return self.finish(&id, status, cancelled);
+            }
+        }
+    }
+}
+
+/// Sleeps for `duration`, returning false as soon as a cancel arrives.
+async fn wait(cancelled: &mut watch::Receiver<bool>, duration: Duration) -> bool {
+    // A dropped sender means the execution is gone, which stops it too
+    tokio::time::timeout(duration, cancelled.wait_for(|v| *v))
+        .await
+        .is_err()
+}
+
+/// Drops the oldest stopped executions so that, once the one about to
+/// start stops too, at most [`MAX_FINISHED`] are kept.
+fn prune(executions: &mut HashMap<String, Slot>) {
+    let mut finished: Vec<(u128, String)> = executions
+        .values()
+        .filter_map(|v| Some((v.execution.finished?, v.execution.id.clone())))
+        .collect();
+    if finished.len() < MAX_FINISHED {
+        return;
+    }
+
+    finished.sort();
+    for (_, id) in finished.iter().take(finished.len() + 1 - MAX_FINISHED) {
+        executions.remove(id);
+    }
+}
+
+enum Rejected {
+    /// The child does not pass the symbol filters after correction.
+    Filter(String),
+    Order(String),
+}
+
+async fn place(
+    agent: &BinanceSpot,
+    side: &OrderSide,
+    quantity: Quantity,
+) -> Result<ChildOrder, Rejected> {
+    let price = match retry::read(|| agent.client.price(&agent.normal.symbol)).await {
+        Ok(v) => Decimal::from_str(&v.price).map_err(|e| Rejected::Order(e.to_string()))?,
+        Err(e) => return Err(Rejected::Order(e.to_string())),
+    };
+
+    let normal = &agent.normal;
+    let corrected = match side {
+        OrderSide::Buy => normal
+            .correct_quote_quantity(&price, &quantity)
+            .and_then(|v| normal.filter_quote_quantity(&price, &v)),
+        OrderSide::Sell => normal
+            .correct_base_quantity(&price, &quantity)
+            .and_then(|v| normal.filter_base_quantity(&price, &v)),
+    };
+    if let Err(e) = corrected {
+        return Err(Rejected::Filter(e.to_string()));
+    }
+
+    let order = match side {
+        OrderSide::Buy => agent.market_buy(&price, &quantity).await,
+        OrderSide::Sell => agent.market_sell(&price, &quantity).await,
+    };
+
+    match order {
+        Ok(v) => Ok(child_order(&v, quantity)),
+        Err(e) => Err(Rejected::Order(e.to_string())),
+    }
+}
+
+fn child_order(order: &OrderResponseFull, quantity: Quantity) -> ChildOrder {
+    let dec = |v: &str| Decimal::from_str(v).unwrap_or_default();
+
+    let mut base = Decimal::ZERO;
+    let mut quote = Decimal::ZERO;
+    for fill in order.fills.iter() {
+        base += dec(&fill.qty);
+        quote += dec(&fill.price) * dec(&fill.qty);
+    }
+
+    ChildOrder {
+        order_id: Some(order.order_id),
+        quantity,
+        base_quantity: base,
+        quote_quantity: quote,
+        error: None,
+        time: timestamp().as_millis(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn twap(slices: u32) -> Schedule {
+        Schedule::Twap {
+            slices,
+            interval_secs: 0,
+        }
+    }
+
+    #[test]
+    fn test_schedule_children() {
+        let (ten, three) = (Decimal::from(10), Decimal::from(3));
+
+        let schedule = twap(4);
+        assert_eq!(schedule.child(&ten, &ten, 0), Decimal::new(25, 1));
+        assert_eq!(schedule.child(&ten, &three, 3), three);
+        assert!(schedule.done(4));
+
+        let schedule = Schedule::Iceberg {
+            visible: three,
+            interval_secs: 0,
+        };
+        assert_eq!(schedule.child(&ten, &ten, 0), three);
+        assert_eq!(schedule.child(&ten, &Decimal::ONE, 3), Decimal::ONE);
+    }
+
+    #[test]
+    fn test_schedule_validate() {
+        let ten = Decimal::from(10);
+
+        assert!(twap(4).validate(&ten).is_ok());
+        assert!(twap(0).validate(&ten).is_err());
+        assert!(twap(MAX_CHILDREN + 1).validate(&ten).is_err());
+
+        let iceberg = |visible: i64| Schedule::Iceberg {
+            visible: Decimal::from(visible),
+            interval_secs: 0,
+        };
+        assert!(iceberg(0).validate(&ten).is_err());
+        assert!(iceberg(1)
+            .validate(&Decimal::from(MAX_CHILDREN + 1))
+            .is_err());
+    }
+}
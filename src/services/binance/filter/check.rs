@@ -0,0 +1,280 @@
+use binance::types::{SymbolFilter, SymbolInfo};
+use plot::types::{Decimal, Price, Quantity};
+use serde::{Deserialize, Serialize};
+
+use super::current::filter_precision;
+use super::spot::{base_quantity, quote_quantity};
+use super::{dec, SymbolFilterResult};
+use crate::services::binance::ConvertFilter;
+
+/// Which side of the pair a checked quantity is in.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuantityKind {
+    Base,
+    Quote,
+}
+
+/// Outcome of one filter against the corrected quantity.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilterCheck {
+    pub filter: String,
+    pub passed: bool,
+    pub message: Option<String>,
+}
+
+/// Why a quantity would be accepted or rejected by the symbol filters.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilterReport {
+    pub kind: QuantityKind,
+    pub price: Price,
+    pub quantity: Quantity,
+    /// The quantity an order would be sent with, unset when it can not be
+    /// corrected.
+    pub corrected_quantity: Option<Quantity>,
+    pub passed: bool,
+    pub checks: Vec<FilterCheck>,
+    /// Smallest quantity that passes every filter at `price`, unset when
+    /// none does.
+    pub min_quantity: Option<Quantity>,
+    /// Filters of the symbol that depend on the account's open orders and
+    /// balance rather than the quantity, checked only when an order is placed.
+    pub deferred: Vec<String>,
+}
+
+/// Runs `quantity` through every filter of `norms` that applies to it.
+pub fn check(
+    norms: &SymbolInfo,
+    price: &Price,
+    quantity: &Quantity,
+    kind: QuantityKind,
+) -> FilterReport {
+    let corrected = match kind {
+        QuantityKind::Base => norms.correct_base_quantity(price, quantity),
+        QuantityKind::Quote => norms.correct_quote_quantity(price, quantity),
+    };
+
+    let (corrected_quantity, checks) = match corrected {
+        Ok(v) => (Some(v), checks(norms, price, &v, kind)),
+        Err(e) => (None, vec![failed("CORRECTION", e.to_string())]),
+    };
+
+    FilterReport {
+        kind,
+        price: *price,
+        quantity: *quantity,
+        corrected_quantity,
+        passed: checks.iter().all(|v| v.passed),
+        checks,
+        min_quantity: min_quantity(norms, price, kind).ok().flatten(),
+        deferred: deferred(norms),
+    }
+}
+
+/// Names of the account filters of `norms`, see `spot::account::filter`.
+fn deferred(norms: &SymbolInfo) -> Vec<String> {
+    let mut result = Vec::new();
+
+    for filter in norms.filters.iter() {
+        let name = match filter {
+            SymbolFilter::MaxNumOrders(_) => "MAX_NUM_ORDERS",
+            SymbolFilter::MaxNumAlgoOrders(_) => "MAX_NUM_ALGO_ORDERS",
+            SymbolFilter::MaxNumIcebergOrders(_) => "MAX_NUM_ICEBERG_ORDERS",
+            SymbolFilter::MaxPosition(_) => "MAX_POSITION",
+            SymbolFilter::IcebergParts(_) => "ICEBERG_PARTS",
+            SymbolFilter::TrailingDelta(_) => "TRAILING_DELTA",
+            _ => continue,
+        };
+        result.push(name.to_string());
+    }
+
+    result
+}
+
+fn checks(
+    norms: &SymbolInfo,
+    price: &Price,
+    quantity: &Quantity,
+    kind: QuantityKind,
+) -> Vec<FilterCheck> {
+    let mut result = Vec::new();
+
+    for filter in norms.filters.iter() {
+        let (name, outcome) = match (kind, filter) {
+            (QuantityKind::Base, SymbolFilter::LotSize(v)) => (
+                "LOT_SIZE",
+                base_quantity::filter_lot_size(quantity, v).map(|_| ()),
+            ),
+            (QuantityKind::Base, SymbolFilter::MarketLotSize(v)) => (
+                "MARKET_LOT_SIZE",
+                base_quantity::filter_market_lot_size(quantity, v).map(|_| ()),
+            ),
+            (QuantityKind::Base, SymbolFilter::Notional(v)) => (
+                "NOTIONAL",
+                base_quantity::filter_notional(price, quantity, v).map(|_| ()),
+            ),
+            (QuantityKind::Quote, SymbolFilter::Notional(v)) => (
+                "NOTIONAL",
+                quote_quantity::filter_notional(quantity, v).map(|_| ()),
+            ),
+            (QuantityKind::Quote, SymbolFilter::MinNotional(v)) => (
+                "MIN_NOTIONAL",
+                quote_quantity::filter_min_notional(quantity, v).map(|_| ()),
+            ),
+            _ => continue,
+        };
+        result.push(outcome_check(name, outcome));
+    }
+
+    let precision = match kind {
+        QuantityKind::Base => norms.base_asset_precision as u32,
+        QuantityKind::Quote => norms.quote_asset_precision as u32,
+    };
+    let outcome = filter_precision(quantity, precision).map(|_| ());
+    result.push(outcome_check("PRECISION", outcome));
+
+    result
+}
+
+/// Raises the lower bounds of the filters to a multiple of the lot step,
+/// then confirms the result passes every filter.
+fn min_quantity(
+    norms: &SymbolInfo,
+    price: &Price,
+    kind: QuantityKind,
+) -> SymbolFilterResult<Option<Quantity>> {
+    let precision = match kind {
+        QuantityKind::Base => norms.base_asset_precision as u32,
+        QuantityKind::Quote => norms.quote_asset_precision as u32,
+    };
+    let mut minimum = Decimal::new(1, precision);
+    let mut step = Decimal::ZERO;
+
+    for filter in norms.filters.iter() {
+        match (kind, filter) {
+            (QuantityKind::Base, SymbolFilter::LotSize(v)) => {
+                minimum = minimum.max(dec(&v.min_qty)?);
+                step = step.max(dec(&v.step_size)?);
+            }
+            (QuantityKind::Base, SymbolFilter::MarketLotSize(v)) => {
+                minimum = minimum.max(dec(&v.min_qty)?);
+                step = step.max(dec(&v.step_size)?);
+            }
+            (QuantityKind::Base, SymbolFilter::Notional(v)) if v.apply_min_to_market => {
+                if price.is_zero() {
+                    return Ok(None);
+                }
+                minimum = minimum.max(dec(&v.min_notional)? / price);
+            }
+            (QuantityKind::Quote, SymbolFilter::Notional(v)) if v.apply_min_to_market => {
+                minimum = minimum.max(dec(&v.min_notional)?);
+            }
+            (QuantityKind::Quote, SymbolFilter::MinNotional(v)) if v.apply_to_market => {
+                minimum = minimum.max(dec(&v.min_notional)?);
+            }
+            _ => continue,
+        }
+    }
+
+    if !step.is_zero() {
+        minimum = (minimum / step).ceil() * step;
+    }
+    let minimum = ceil_with_scale(minimum, precision).normalize();
+
+    let passed = match kind {
+        QuantityKind::Base => norms.filter_base_quantity(price, &minimum),
+        QuantityKind::Quote => norms.filter_quote_quantity(price, &minimum),
+    };
+
+    Ok(passed.ok().map(|_| minimum))
+}
+
+fn ceil_with_scale(value: Decimal, scale: u32) -> Decimal {
+    let truncated = value.trunc_with_scale(scale);
+    match truncated < value {
+        true => truncated + Decimal::new(1, scale),
+        false => truncated,
+    }
+}
+
+fn outcome_check(name: &str, outcome: SymbolFilterResult<()>) -> FilterCheck {
+    match outcome {
+        Ok(()) => FilterCheck {
+            filter: name.into(),
+            passed: true,
+            message: None,
+        },
+        Err(e) => failed(name, e.to_string()),
+    }
+}
+
+fn failed(name: &str, message: String) -> FilterCheck {
+    FilterCheck {
+        filter: name.into(),
+        passed: false,
+        message: Some(message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::binance::filter::spot::tests::{dec, symbol_norms, symbol_price};
+
+    #[test]
+    fn test_check_base() {
+        let report = check(
+            &symbol_norms(),
+            &symbol_price(),
+            &dec("0.00151"),
+            QuantityKind::Base,
+        );
+        assert_eq!(report.corrected_quantity, Some(dec("0.0015")));
+        assert!(report.passed);
+        let names: Vec<&str> = report.checks.iter().map(|v| v.filter.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["LOT_SIZE", "MARKET_LOT_SIZE", "NOTIONAL", "PRECISION"]
+        );
+        assert_eq!(
+            report.deferred,
+            vec![
+                "ICEBERG_PARTS",
+                "TRAILING_DELTA",
+                "MAX_NUM_ORDERS",
+                "MAX_NUM_ALGO_ORDERS"
+            ]
+        );
+
+        // 5 / 3685.96 rounded up to the 0.0001 step
+        assert_eq!(report.min_quantity, Some(dec("0.0014")));
+
+        let report = check(
+            &symbol_norms(),
+            &symbol_price(),
+            &dec("0.0005"),
+            QuantityKind::Base,
+        );
+        assert!(!report.passed);
+        let failed: Vec<&str> = report
+            .checks
+            .iter()
+            .filter(|v| !v.passed)
+            .map(|v| v.filter.as_str())
+            .collect();
+        assert_eq!(failed, vec!["NOTIONAL"]);
+    }
+
+    #[test]
+    fn test_check_quote() {
+        let report = check(
+            &symbol_norms(),
+            &symbol_price(),
+            &dec("4.123456789"),
+            QuantityKind::Quote,
+        );
+        assert_eq!(report.corrected_quantity, Some(dec("4.12345678")));
+        assert!(!report.passed);
+        assert_eq!(report.min_quantity, Some(dec("5")));
+    }
+}
@@ -6,6 +6,16 @@ pub enum SymbolFilterError {
     Notional(String),
     MinNotional(String),
     MarketLotSize(String),
+    IcebergParts(String),
+    MaxNumOrders(String),
+    MaxNumAlgoOrders(String),
+    MaxNumIcebergOrders(String),
+    MaxPosition(String),
+    TrailingDelta(String),
+    Status(String),
+    OrderType(String),
+    ExchangeMaxNumOrders(String),
+    ExchangeMaxNumAlgoOrders(String),
 }
 
 impl std::error::Error for SymbolFilterError {}
@@ -18,6 +28,16 @@ impl std::fmt::Display for SymbolFilterError {
             Self::Precision(e) => format!("PRECISION {}", e),
             Self::MinNotional(e) => format!("MIN_NOTIONAL {}", e),
             Self::MarketLotSize(e) => format!("MARKET_LOT_SIZE {}", e),
+            Self::IcebergParts(e) => format!("ICEBERG_PARTS {}", e),
+            Self::MaxNumOrders(e) => format!("MAX_NUM_ORDERS {}", e),
+            Self::MaxNumAlgoOrders(e) => format!("MAX_NUM_ALGO_ORDERS {}", e),
+            Self::MaxNumIcebergOrders(e) => format!("MAX_NUM_ICEBERG_ORDERS {}", e),
+            Self::MaxPosition(e) => format!("MAX_POSITION {}", e),
+            Self::TrailingDelta(e) => format!("TRAILING_DELTA {}", e),
+            Self::Status(e) => format!("STATUS {}", e),
+            Self::OrderType(e) => format!("ORDER_TYPE {}", e),
+            Self::ExchangeMaxNumOrders(e) => format!("EXCHANGE_MAX_NUM_ORDERS {}", e),
+            Self::ExchangeMaxNumAlgoOrders(e) => format!("EXCHANGE_MAX_NUM_ALGO_ORDERS {}", e),
         };
 
         write!(f, "FILTER {}", message)
@@ -0,0 +1,240 @@
+use binance::types::{Asset, Symbol, SymbolLotSizeFilter, SymbolMarketLotSizeFilter};
+use plot::types::{Price, Quantity};
+use serde::{Deserialize, Serialize};
+
+use super::current::{correct_precision, filter_precision};
+use super::error::SymbolFilterError;
+use super::spot::base_quantity::{
+    correct_lot_size, correct_market_lot_size, filter_lot_size, filter_market_lot_size,
+};
+use super::{dec, SymbolFilterResult};
+
+/// A USDⓈ-M futures symbol as listed by the futures exchange info, by the
+/// exchange field names.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FuturesNorms {
+    pub symbol: Symbol,
+    pub status: String,
+    /// `PERPETUAL`, or the delivery of a quarterly contract.
+    pub contract_type: String,
+    pub base_asset: Asset,
+    pub quote_asset: Asset,
+    pub margin_asset: Asset,
+    pub quantity_precision: u32,
+    pub order_types: Vec<String>,
+    pub filters: Vec<FuturesFilter>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "filterType", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FuturesFilter {
+    LotSize(SymbolLotSizeFilter),
+    MarketLotSize(SymbolMarketLotSizeFilter),
+    MinNotional(FuturesMinNotionalFilter),
+    MaxNumOrders(FuturesLimitFilter),
+    MaxNumAlgoOrders(FuturesLimitFilter),
+    /// Price filters, which market orders are not subject to.
+    #[serde(other)]
+    Other,
+}
+
+/// Unlike its spot namesake, applies to market orders and holds the
+/// minimum in `notional`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FuturesMinNotionalFilter {
+    pub notional: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FuturesLimitFilter {
+    pub limit: u32,
+}
+
+impl FuturesNorms {
+    /// Rounds `quantity` down to the lot steps and the quantity precision.
+    pub fn correct_quantity(&self, quantity: &Quantity) -> SymbolFilterResult<Quantity> {
+        let mut correct_quantity = *quantity;
+
+        for filter in self.filters.iter() {
+            match filter {
+                FuturesFilter::LotSize(v) => {
+                    correct_quantity = correct_lot_size(&correct_quantity, v)?
+                }
+                FuturesFilter::MarketLotSize(v) => {
+                    correct_quantity = correct_market_lot_size(&correct_quantity, v)?
+                }
+                _ => continue,
+            }
+        }
+
+        Ok(correct_precision(
+            &correct_quantity,
+            self.quantity_precision,
+        ))
+    }
+
+    /// The corrected contract quantity `quantity` quote buys at `price`, as
+    /// futures orders are only sized in the base asset.
+    pub fn quote_to_base(
+        &self,
+        price: &Price,
+        quantity: &Quantity,
+    ) -> SymbolFilterResult<Quantity> {
+        if price.is_zero() {
+            return Err(SymbolFilterError::Decimal(
+                "a quote quantity can not be converted at price 0".into(),
+            ));
+        }
+
+        self.correct_quantity(&(quantity / price))
+    }
+
+    /// Checks a market order of `quantity` contracts. The exchange exempts
+    /// reduce only orders from the minimum notional, so closing a position
+    /// smaller than it stays possible.
+    pub fn filter_quantity(
+        &self,
+        price: &Price,
+        quantity: &Quantity,
+        reduce_only: bool,
+    ) -> SymbolFilterResult<()> {
+        for filter in self.filters.iter() {
+            match filter {
+                FuturesFilter::LotSize(v) => filter_lot_size(quantity, v).map(|_| ())?,
+                FuturesFilter::MarketLotSize(v) => {
+                    filter_market_lot_size(quantity, v).map(|_| ())?
+                }
+                FuturesFilter::MinNotional(v) if !reduce_only => {
+                    filter_min_notional(price, quantity, v)?
+                }
+                _ => continue,
+            }
+        }
+
+        filter_precision(quantity, self.quantity_precision)?;
+
+        Ok(())
+    }
+}
+
+pub fn filter_min_notional(
+    price: &Price,
+    quantity: &Quantity,
+    filter: &FuturesMinNotionalFilter,
+) -> SymbolFilterResult<()> {
+    let notional = price * quantity;
+    let min_notional = dec(&filter.notional)?;
+
+    if notional < min_notional {
+        return Err(SymbolFilterError::MinNotional(format!(
+            "the notional value of {} * {} = {} does not meet the minimum required notional value of {}",
+            price, quantity, notional, min_notional
+        )));
+    }
+
+    Ok(())
+}
+
+pub mod trading {
+    use crate::services::binance::filter::error::SymbolFilterError;
+    use crate::services::binance::filter::SymbolFilterResult;
+
+    use super::FuturesNorms;
+
+    pub use crate::services::binance::filter::spot::trading::MARKET;
+
+    /// The only contract status that accepts new orders.
+    const TRADING: &str = "TRADING";
+
+    /// Refuses orders to a contract that is settling, delivering or closed,
+    /// or that does not take `order_type`.
+    pub fn filter(norms: &FuturesNorms, order_type: &str) -> SymbolFilterResult<()> {
+        if norms.status != TRADING {
+            return Err(SymbolFilterError::Status(format!(
+                "{} is {}, orders are only accepted while {}",
+                norms.symbol, norms.status, TRADING
+            )));
+        }
+
+        if !norms.order_types.iter().any(|v| v == order_type) {
+            return Err(SymbolFilterError::OrderType(format!(
+                "{} does not accept {} orders",
+                norms.symbol, order_type
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::binance::filter::spot::tests::dec;
+
+    const FUTURES_NORMS: &str = r#"{"symbol":"BTCUSDT","pair":"BTCUSDT","contractType":"PERPETUAL","deliveryDate":4133404800000,"onboardDate":1569398400000,"status":"TRADING","maintMarginPercent":"2.5000","requiredMarginPercent":"5.0000","baseAsset":"BTC","quoteAsset":"USDT","marginAsset":"USDT","pricePrecision":2,"quantityPrecision":3,"baseAssetPrecision":8,"quotePrecision":8,"underlyingType":"COIN","underlyingSubType":["PoW"],"triggerProtect":"0.0500","liquidationFee":"0.012500","marketTakeBound":"0.05","maxMoveOrderLimit":10000,"filters":[{"minPrice":"556.80","maxPrice":"4529764","filterType":"PRICE_FILTER","tickSize":"0.10"},{"stepSize":"0.001","filterType":"LOT_SIZE","maxQty":"1000","minQty":"0.001"},{"stepSize":"0.001","filterType":"MARKET_LOT_SIZE","maxQty":"120","minQty":"0.001"},{"limit":200,"filterType":"MAX_NUM_ORDERS"},{"limit":10,"filterType":"MAX_NUM_ALGO_ORDERS"},{"notional":"100","filterType":"MIN_NOTIONAL"},{"multiplierDown":"0.9500","multiplierUp":"1.0500","multiplierDecimal":"4","filterType":"PERCENT_PRICE"}],"orderTypes":["LIMIT","MARKET","STOP","STOP_MARKET","TAKE_PROFIT","TAKE_PROFIT_MARKET","TRAILING_STOP_MARKET"],"timeInForce":["GTC","IOC","FOK","GTX","GTD"]}"#;
+
+    fn futures_norms() -> FuturesNorms {
+        serde_json::from_str(FUTURES_NORMS).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let norms = futures_norms();
+        assert_eq!(norms.contract_type, "PERPETUAL");
+        assert_eq!(norms.quantity_precision, 3);
+        assert_eq!(norms.filters.len(), 7);
+        assert!(matches!(norms.filters[0], FuturesFilter::Other));
+        assert!(matches!(norms.filters[5], FuturesFilter::MinNotional(_)));
+    }
+
+    #[test]
+    fn test_correct_quantity() {
+        let norms = futures_norms();
+        assert_eq!(
+            norms.correct_quantity(&dec("0.12345")).unwrap(),
+            dec("0.123")
+        );
+
+        // 1000 / 65000 = 0.01538..
+        let quantity = norms.quote_to_base(&dec("65000"), &dec("1000")).unwrap();
+        assert_eq!(quantity, dec("0.015"));
+        assert!(norms.quote_to_base(&dec("0"), &dec("1000")).is_err());
+    }
+
+    #[test]
+    fn test_filter_quantity() {
+        let norms = futures_norms();
+        let price = dec("65000");
+        assert!(norms.filter_quantity(&price, &dec("0.002"), false).is_ok());
+
+        // 65000 * 0.001 = 65 is short of the 100 minimum notional
+        let e = norms.filter_quantity(&price, &dec("0.001"), false);
+        assert!(matches!(e, Err(SymbolFilterError::MinNotional(_))));
+        assert!(norms.filter_quantity(&price, &dec("0.001"), true).is_ok());
+
+        // Above the market lot maximum but within the lot maximum
+        let e = norms.filter_quantity(&price, &dec("121"), true);
+        assert!(matches!(e, Err(SymbolFilterError::MarketLotSize(_))));
+
+        let e = norms.filter_quantity(&price, &dec("0.0015"), true);
+        assert!(matches!(e, Err(SymbolFilterError::LotSize(_))));
+    }
+
+    #[test]
+    fn test_trading() {
+        let mut norms = futures_norms();
+        assert!(trading::filter(&norms, trading::MARKET).is_ok());
+        assert!(matches!(
+            trading::filter(&norms, "LIMIT_MAKER"),
+            Err(SymbolFilterError::OrderType(_))
+        ));
+
+        norms.status = "SETTLING".into();
+        assert!(matches!(
+            trading::filter(&norms, trading::MARKET),
+            Err(SymbolFilterError::Status(_))
+        ));
+    }
+}
@@ -1,4 +1,6 @@
+pub mod check;
 pub mod error;
+pub mod futures;
 pub mod spot;
 
 type SymbolFilterResult<T> = Result<T, error::SymbolFilterError>;
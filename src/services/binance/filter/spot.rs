@@ -66,6 +66,7 @@ pub mod base_quantity {
                 SymbolFilter::LotSize(v) => filter_lot_size(&quantity, v)?,
                 SymbolFilter::Notional(v) => filter_notional(&price, &quantity, v)?,
                 SymbolFilter::MarketLotSize(v) => filter_market_lot_size(&quantity, v)?,
+                // The account filters need the open orders, see `account::filter`
                 _ => continue,
             };
         }
@@ -238,6 +239,7 @@ pub mod quote_quantity {
             match filter {
                 SymbolFilter::Notional(v) => filter_notional(quantity, v)?,
                 SymbolFilter::MinNotional(v) => filter_min_notional(quantity, v)?,
+                // The account filters need the open orders, see `account::filter`
                 _ => continue,
             };
         }
@@ -304,24 +306,391 @@ pub mod quote_quantity {
     }
 }
 
+pub mod account {
+    use binance::types::{
+        OrderSide, SymbolFilter, SymbolIcebergPartsFilter, SymbolInfo,
+        SymbolMaxNumAlgoOrdersFilter, SymbolMaxNumIcebergOrdersFilter, SymbolMaxNumOrdersFilter,
+        SymbolMaxPositionFilter, SymbolTrailingDeltaFilter,
+    };
+    use plot::types::{Decimal, Quantity};
+
+    use crate::services::binance::filter::error::SymbolFilterError;
+    use crate::services::binance::filter::{self, SymbolFilterResult};
+
+    /// Trailing delta of a trailing stop in basis points. Above applies to
+    /// STOP_LOSS BUY and TAKE_PROFIT SELL, below to the other stop orders.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Trailing {
+        Above(u32),
+        Below(u32),
+    }
+
+    /// The order being placed and what the account already has on the
+    /// symbol, for the filters that depend on more than the quantity.
+    #[derive(Debug, Clone)]
+    pub struct OrderContext {
+        pub side: OrderSide,
+        /// Open orders of the account on the symbol.
+        pub open_orders: u32,
+        pub open_algo_orders: u32,
+        pub open_iceberg_orders: u32,
+        /// Open orders of the account on every symbol.
+        pub exchange_open_orders: u32,
+        pub exchange_open_algo_orders: u32,
+        /// Base asset held, free and locked.
+        pub base_balance: Quantity,
+        /// Base quantity still to be filled by the open buy orders.
+        pub open_buy_quantity: Quantity,
+        /// Whether the order is a stop loss or take profit order.
+        pub algo: bool,
+        pub iceberg_quantity: Option<Quantity>,
+        pub trailing: Option<Trailing>,
+    }
+
+    impl OrderContext {
+        /// A plain market order on an account with nothing open.
+        pub fn market(side: OrderSide, base_balance: Quantity) -> Self {
+            Self {
+                side,
+                open_orders: 0,
+                open_algo_orders: 0,
+                open_iceberg_orders: 0,
+                exchange_open_orders: 0,
+                exchange_open_algo_orders: 0,
+                base_balance,
+                open_buy_quantity: Decimal::ZERO,
+                algo: false,
+                iceberg_quantity: None,
+                trailing: None,
+            }
+        }
+    }
+
+    /// Whether any filter of `norms` depends on the open orders, which are
+    /// otherwise not worth fetching.
+    pub fn counts_open_orders(norms: &SymbolInfo) -> bool {
+        norms.filters.iter().any(|v| {
+            matches!(
+                v,
+                SymbolFilter::MaxNumOrders(_)
+                    | SymbolFilter::MaxNumAlgoOrders(_)
+                    | SymbolFilter::MaxNumIcebergOrders(_)
+                    | SymbolFilter::MaxPosition(_)
+            )
+        })
+    }
+
+    pub fn filter(
+        norms: &SymbolInfo,
+        quantity: &Quantity,
+        context: &OrderContext,
+    ) -> SymbolFilterResult<()> {
+        for filter in norms.filters.iter() {
+            match filter {
+                SymbolFilter::MaxNumOrders(v) => filter_max_num_orders(context, v)?,
+                SymbolFilter::MaxNumAlgoOrders(v) => filter_max_num_algo_orders(context, v)?,
+                SymbolFilter::MaxNumIcebergOrders(v) => filter_max_num_iceberg_orders(context, v)?,
+                SymbolFilter::MaxPosition(v) => filter_max_position(quantity, context, v)?,
+                SymbolFilter::IcebergParts(v) => filter_iceberg_parts(quantity, context, v)?,
+                SymbolFilter::TrailingDelta(v) => filter_trailing_delta(context, v)?,
+                _ => continue,
+            };
+        }
+
+        Ok(())
+    }
+
+    pub fn filter_max_num_orders(
+        context: &OrderContext,
+        filter: &SymbolMaxNumOrdersFilter,
+    ) -> SymbolFilterResult<()> {
+        let max = Decimal::from(filter.max_num_orders);
+        if Decimal::from(context.open_orders) >= max {
+            return Err(SymbolFilterError::MaxNumOrders(format!(
+                "{} orders are open, at most {} are allowed",
+                context.open_orders, max
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub fn filter_max_num_algo_orders(
+        context: &OrderContext,
+        filter: &SymbolMaxNumAlgoOrdersFilter,
+    ) -> SymbolFilterResult<()> {
+        let max = Decimal::from(filter.max_num_algo_orders);
+        if context.algo && Decimal::from(context.open_algo_orders) >= max {
+            return Err(SymbolFilterError::MaxNumAlgoOrders(format!(
+                "{} stop orders are open, at most {} are allowed",
+                context.open_algo_orders, max
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub fn filter_max_num_iceberg_orders(
+        context: &OrderContext,
+        filter: &SymbolMaxNumIcebergOrdersFilter,
+    ) -> SymbolFilterResult<()> {
+        let max = Decimal::from(filter.max_num_iceberg_orders);
+        if context.iceberg_quantity.is_some() && Decimal::from(context.open_iceberg_orders) >= max {
+            return Err(SymbolFilterError::MaxNumIcebergOrders(format!(
+                "{} iceberg orders are open, at most {} are allowed",
+                context.open_iceberg_orders, max
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// The base balance and the open buy orders, plus `quantity` when
+    /// buying, may not exceed the maximum position.
+    pub fn filter_max_position(
+        quantity: &Quantity,
+        context: &OrderContext,
+        filter: &SymbolMaxPositionFilter,
+    ) -> SymbolFilterResult<()> {
+        let max_position = filter::dec(&filter.max_position)?;
+
+        let mut position = context.base_balance + context.open_buy_quantity;
+        if matches!(context.side, OrderSide::Buy) {
+            position += quantity;
+        }
+
+        if position > max_position {
+            return Err(SymbolFilterError::MaxPosition(format!(
+                "the position {} exceeds the maximum position {}",
+                position, max_position
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub fn filter_iceberg_parts(
+        quantity: &Quantity,
+        context: &OrderContext,
+        filter: &SymbolIcebergPartsFilter,
+    ) -> SymbolFilterResult<()> {
+        let Some(iceberg_quantity) = &context.iceberg_quantity else {
+            return Ok(());
+        };
+
+        if iceberg_quantity.is_zero() {
+            return Err(SymbolFilterError::IcebergParts(
+                "the iceberg quantity is zero".into(),
+            ));
+        }
+
+        let parts = (quantity / iceberg_quantity).ceil();
+        let limit = Decimal::from(filter.limit);
+        if parts > limit {
+            return Err(SymbolFilterError::IcebergParts(format!(
+                "the quantity {} splits into {} parts of {}, at most {} are allowed",
+                quantity, parts, iceberg_quantity, limit
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub fn filter_trailing_delta(
+        context: &OrderContext,
+        filter: &SymbolTrailingDeltaFilter,
+    ) -> SymbolFilterResult<()> {
+        let (delta, min, max) = match context.trailing {
+            Some(Trailing::Above(v)) => (
+                v,
+                filter.min_trailing_above_delta,
+                filter.max_trailing_above_delta,
+            ),
+            Some(Trailing::Below(v)) => (
+                v,
+                filter.min_trailing_below_delta,
+                filter.max_trailing_below_delta,
+            ),
+            None => return Ok(()),
+        };
+
+        let (min, max) = (Decimal::from(min), Decimal::from(max));
+        let delta = Decimal::from(delta);
+        if delta < min || delta > max {
+            return Err(SymbolFilterError::TrailingDelta(format!(
+                "the trailing delta {} is outside {} to {}",
+                delta, min, max
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+pub mod trading {
+    use binance::types::SymbolInfo;
+
+    use crate::services::binance::filter::error::SymbolFilterError;
+    use crate::services::binance::filter::SymbolFilterResult;
+
+    /// Order type of every order placed by this service.
+    pub const MARKET: &str = "MARKET";
+
+    /// The only symbol status that accepts new orders.
+    const TRADING: &str = "TRADING";
+
+    /// Refuses orders to a symbol that is halted, in a break or closed to
+    /// spot trading, or that does not take `order_type`.
+    pub fn filter(norms: &SymbolInfo, order_type: &str) -> SymbolFilterResult<()> {
+        if norms.status != TRADING {
+            return Err(SymbolFilterError::Status(format!(
+                "{} is {}, orders are only accepted while {}",
+                norms.symbol, norms.status, TRADING
+            )));
+        }
+
+        if !norms.is_spot_trading_allowed {
+            return Err(SymbolFilterError::Status(format!(
+                "{} does not allow spot trading",
+                norms.symbol
+            )));
+        }
+
+        if !norms.order_types.iter().any(|v| v == order_type) {
+            return Err(SymbolFilterError::OrderType(format!(
+                "{} does not accept {} orders",
+                norms.symbol, order_type
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+pub mod exchange {
+    use binance::types::{ExchangeFilter, ExchangeInfo};
+
+    use super::account::OrderContext;
+    use crate::services::binance::filter::error::SymbolFilterError;
+    use crate::services::binance::filter::SymbolFilterResult;
+
+    /// Order counts the exchange allows an account over all symbols.
+    #[derive(Debug, Clone, Default, PartialEq)]
+    pub struct ExchangeLimits {
+        pub max_num_orders: Option<u64>,
+        pub max_num_algo_orders: Option<u64>,
+    }
+
+    impl ExchangeLimits {
+        pub fn new(info: &ExchangeInfo) -> Self {
+            Self::from_filters(&info.exchange_filters)
+        }
+
+        fn from_filters(filters: &[ExchangeFilter]) -> Self {
+            let mut limits = Self::default();
+            for filter in filters.iter() {
+                match filter {
+                    ExchangeFilter::ExchangeMaxNumOrders(v) => {
+                        limits.max_num_orders = Some(u64::from(v.max_num_orders))
+                    }
+                    ExchangeFilter::ExchangeMaxNumAlgoOrders(v) => {
+                        limits.max_num_algo_orders = Some(u64::from(v.max_num_algo_orders))
+                    }
+                    _ => continue,
+                }
+            }
+
+            limits
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.max_num_orders.is_none() && self.max_num_algo_orders.is_none()
+        }
+    }
+
+    pub fn filter(limits: &ExchangeLimits, context: &OrderContext) -> SymbolFilterResult<()> {
+        if let Some(max) = limits.max_num_orders {
+            if u64::from(context.exchange_open_orders) >= max {
+                return Err(SymbolFilterError::ExchangeMaxNumOrders(format!(
+                    "{} orders are open on the account, at most {} are allowed",
+                    context.exchange_open_orders, max
+                )));
+            }
+        }
+
+        if let (true, Some(max)) = (context.algo, limits.max_num_algo_orders) {
+            if u64::from(context.exchange_open_algo_orders) >= max {
+                return Err(SymbolFilterError::ExchangeMaxNumAlgoOrders(format!(
+                    "{} stop orders are open on the account, at most {} are allowed",
+                    context.exchange_open_algo_orders, max
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use binance::types::OrderSide;
+        use plot::types::Decimal;
+
+        use super::*;
+
+        #[test]
+        fn test_exchange_limits() {
+            let filters: Vec<ExchangeFilter> = serde_json::from_str(
+                r#"[{"filterType":"EXCHANGE_MAX_NUM_ORDERS","maxNumOrders":1000},{"filterType":"EXCHANGE_MAX_NUM_ALGO_ORDERS","maxNumAlgoOrders":200}]"#,
+            )
+            .unwrap();
+            let limits = ExchangeLimits::from_filters(&filters);
+            assert_eq!(limits.max_num_orders, Some(1000));
+            assert_eq!(limits.max_num_algo_orders, Some(200));
+            assert!(ExchangeLimits::from_filters(&[]).is_empty());
+
+            let mut context = OrderContext::market(OrderSide::Buy, Decimal::ZERO);
+            context.exchange_open_orders = 999;
+            context.exchange_open_algo_orders = 200;
+            assert!(filter(&limits, &context).is_ok());
+
+            context.algo = true;
+            assert!(filter(&limits, &context).is_err());
+
+            context.algo = false;
+            context.exchange_open_orders = 1000;
+            assert!(filter(&limits, &context).is_err());
+        }
+    }
+}
+
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use binance::types::SymbolInfo;
     use plot::types::Decimal;
 
     const SYMBOL_PRICE: &str = "3685.96000000";
     const SYMBOL_NORMS: &str = r#"{"allowTrailingStop":true,"allowedSelfTradePreventionModes":["EXPIRE_TAKER","EXPIRE_MAKER","EXPIRE_BOTH"],"baseAsset":"ETH","baseAssetPrecision":8,"baseCommissionPrecision":8,"cancelReplaceAllowed":true,"defaultSelfTradePreventionMode":"EXPIRE_MAKER","filters":[{"filterType":"PRICE_FILTER","maxPrice":"1000000.00000000","minPrice":"0.01000000","tickSize":"0.01000000"},{"filterType":"LOT_SIZE","maxQty":"9000.00000000","minQty":"0.00010000","stepSize":"0.00010000"},{"filterType":"ICEBERG_PARTS","limit":10},{"filterType":"MARKET_LOT_SIZE","maxQty":"1701.08445000","minQty":"0.00000000","stepSize":"0.00000000"},{"filterType":"TRAILING_DELTA","maxTrailingAboveDelta":2000,"maxTrailingBelowDelta":2000,"minTrailingAboveDelta":10,"minTrailingBelowDelta":10},{"askMultiplierDown":"0.2","askMultiplierUp":"5","avgPriceMins":5,"bidMultiplierDown":"0.2","bidMultiplierUp":"5","filterType":"PERCENT_PRICE_BY_SIDE"},{"applyMaxToMarket":false,"applyMinToMarket":true,"avgPriceMins":5,"filterType":"NOTIONAL","maxNotional":"9000000.00000000","minNotional":"5.00000000"},{"filterType":"MAX_NUM_ORDERS","maxNumOrders":200},{"filterType":"MAX_NUM_ALGO_ORDERS","maxNumAlgoOrders":5}],"icebergAllowed":true,"isMarginTradingAllowed":true,"isSpotTradingAllowed":true,"ocoAllowed":true,"orderTypes":["LIMIT","LIMIT_MAKER","MARKET","STOP_LOSS_LIMIT","TAKE_PROFIT_LIMIT"],"otoAllowed":false,"permissionSets":[["SPOT","MARGIN","TRD_GRP_004","TRD_GRP_005","TRD_GRP_006","TRD_GRP_009","TRD_GRP_010","TRD_GRP_011","TRD_GRP_012","TRD_GRP_013","TRD_GRP_014","TRD_GRP_015","TRD_GRP_016","TRD_GRP_017","TRD_GRP_018","TRD_GRP_019","TRD_GRP_020","TRD_GRP_021","TRD_GRP_022","TRD_GRP_023","TRD_GRP_024","TRD_GRP_025"]],"permissions":[],"quoteAsset":"USDT","quoteAssetPrecision":8,"quoteCommissionPrecision":8,"quoteOrderQtyMarketAllowed":true,"quotePrecision":8,"status":"TRADING","symbol":"ETHUSDT"}"#;
 
-    fn dec(value: &str) -> Decimal {
+    pub(crate) fn dec(value: &str) -> Decimal {
         use std::str::FromStr;
         Decimal::from_str(value).unwrap()
     }
 
-    fn symbol_norms() -> SymbolInfo {
+    pub(crate) fn symbol_norms() -> SymbolInfo {
         serde_json::from_str(SYMBOL_NORMS).unwrap()
     }
 
-    fn symbol_price() -> Decimal {
+    /// The norms of [`symbol_norms`] for another pair.
+    pub(crate) fn pair_norms(symbol: &str, base_asset: &str, quote_asset: &str) -> SymbolInfo {
+        let mut norms = symbol_norms();
+        norms.symbol = symbol.into();
+        norms.base_asset = base_asset.into();
+        norms.quote_asset = quote_asset.into();
+
+        norms
+    }
+
+    pub(crate) fn symbol_price() -> Decimal {
         dec(&SYMBOL_PRICE.to_string())
     }
 
@@ -443,4 +812,116 @@ mod tests {
             }
         }
     }
+
+    #[cfg(test)]
+    mod tests_account {
+        use binance::types::{OrderSide, SymbolFilter, SymbolInfo};
+
+        use crate::services::binance::filter::spot::account::*;
+
+        use super::{dec, symbol_norms};
+
+        fn norms_with(filters: &str) -> SymbolInfo {
+            let mut norms = symbol_norms();
+            norms.filters = serde_json::from_str(filters).unwrap();
+            norms
+        }
+
+        #[test]
+        fn test_filter_max_num_orders() {
+            let norms = symbol_norms();
+            let mut context = OrderContext::market(OrderSide::Buy, dec("0"));
+
+            context.open_orders = 199;
+            assert!(filter(&norms, &dec("1"), &context).is_ok());
+
+            context.open_orders = 200;
+            assert!(filter(&norms, &dec("1"), &context).is_err());
+
+            // Algo orders only count against stop orders
+            context.open_orders = 0;
+            context.open_algo_orders = 5;
+            assert!(filter(&norms, &dec("1"), &context).is_ok());
+            context.algo = true;
+            assert!(filter(&norms, &dec("1"), &context).is_err());
+        }
+
+        #[test]
+        fn test_filter_iceberg_parts() {
+            for i in symbol_norms().filters.iter() {
+                if let SymbolFilter::IcebergParts(filter) = i {
+                    let mut context = OrderContext::market(OrderSide::Sell, dec("0"));
+                    assert!(filter_iceberg_parts(&dec("1"), &context, filter).is_ok());
+
+                    context.iceberg_quantity = Some(dec("0.1"));
+                    assert!(filter_iceberg_parts(&dec("1"), &context, filter).is_ok());
+                    assert!(filter_iceberg_parts(&dec("1.01"), &context, filter).is_err());
+
+                    context.iceberg_quantity = Some(dec("0"));
+                    assert!(filter_iceberg_parts(&dec("1"), &context, filter).is_err());
+
+                    break;
+                }
+            }
+        }
+
+        #[test]
+        fn test_filter_trailing_delta() {
+            for i in symbol_norms().filters.iter() {
+                if let SymbolFilter::TrailingDelta(filter) = i {
+                    let mut context = OrderContext::market(OrderSide::Buy, dec("0"));
+                    assert!(filter_trailing_delta(&context, filter).is_ok());
+
+                    context.trailing = Some(Trailing::Above(10));
+                    assert!(filter_trailing_delta(&context, filter).is_ok());
+
+                    context.trailing = Some(Trailing::Below(2001));
+                    assert!(filter_trailing_delta(&context, filter).is_err());
+
+                    context.trailing = Some(Trailing::Above(9));
+                    assert!(filter_trailing_delta(&context, filter).is_err());
+
+                    break;
+                }
+            }
+        }
+
+        #[test]
+        fn test_filter_trading() {
+            use crate::services::binance::filter::spot::trading;
+
+            let mut norms = symbol_norms();
+            assert!(trading::filter(&norms, trading::MARKET).is_ok());
+            assert!(trading::filter(&norms, "STOP_LOSS").is_err());
+
+            norms.status = "BREAK".into();
+            assert!(trading::filter(&norms, trading::MARKET).is_err());
+
+            norms.status = "TRADING".into();
+            norms.is_spot_trading_allowed = false;
+            assert!(trading::filter(&norms, trading::MARKET).is_err());
+        }
+
+        #[test]
+        fn test_filter_max_position() {
+            let norms = norms_with(
+                r#"[{"filterType":"MAX_POSITION","maxPosition":"10.00000000"},{"filterType":"MAX_NUM_ICEBERG_ORDERS","maxNumIcebergOrders":1}]"#,
+            );
+
+            let mut context = OrderContext::market(OrderSide::Buy, dec("6"));
+            context.open_buy_quantity = dec("2");
+            assert!(filter(&norms, &dec("2"), &context).is_ok());
+            assert!(filter(&norms, &dec("2.1"), &context).is_err());
+
+            // A sell never grows the position
+            context.side = OrderSide::Sell;
+            assert!(filter(&norms, &dec("5"), &context).is_ok());
+
+            context.iceberg_quantity = Some(dec("1"));
+            context.open_iceberg_orders = 1;
+            assert!(filter(&norms, &dec("1"), &context).is_err());
+            assert!(counts_open_orders(&norms));
+            assert!(!counts_open_orders(&norms_with("[]")));
+        }
+    }
 }
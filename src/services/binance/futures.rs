@@ -0,0 +1,489 @@
+use std::error::Error;
+use std::sync::Arc;
+use std::time::Duration;
+
+use binance::prelude::{Client, ClientBuilder};
+use binance::types::{OrderSide, Symbol};
+use plot::trade::{Trade, Trader};
+use plot::types::{BaseQuantity, Decimal, Price, QuoteQuantity};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::audit::{Action, AuditLog, Entry};
+use crate::retry;
+use crate::secret::Secret;
+
+use super::filter::error::SymbolFilterError;
+use super::filter::futures::{trading, FuturesNorms};
+use super::risk::RiskManager;
+use super::{ConvertTrades, Endpoint};
+
+/// Highest leverage any USDⓈ-M contract allows, the brackets of a symbol
+/// may allow less.
+pub const MAX_LEVERAGE: u8 = 125;
+
+/// Most funding rates the exchange returns for one request.
+pub const MAX_FUNDING_RATES: u16 = 1000;
+
+/// Binance error code of a margin type change to the current margin type.
+const MARGIN_TYPE_UNCHANGED: i64 = -4046;
+
+pub fn client() -> Result<Client, Box<dyn Error>> {
+    let result = ClientBuilder::new()
+        .set_base_url(Endpoint::global().futures_base_url().to_string())
+        .build()?;
+
+    Ok(result)
+}
+
+/// Signed futures client for an account, on `endpoint` or the global
+/// endpoint.
+pub fn client_with_sign(
+    api_key: Secret<String>,
+    secret_key: Secret<String>,
+    endpoint: Option<&Endpoint>,
+) -> Result<Client, Box<dyn Error>> {
+    let endpoint = endpoint.unwrap_or(Endpoint::global());
+
+    let result = ClientBuilder::new()
+        .set_base_url(endpoint.futures_base_url().to_string())
+        .set_api_key(api_key.into_inner())
+        .set_secret_key(secret_key.into_inner())
+        .set_timeout(Duration::from_secs(5))
+        .build()?;
+
+    Ok(result)
+}
+
+/// Reads a futures response by the exchange field names, the same whatever
+/// types the client parses it into.
+fn convert<T: Serialize, R: DeserializeOwned>(value: T) -> Result<R, serde_json::Error> {
+    serde_json::from_value(serde_json::to_value(value)?)
+}
+
+pub async fn fetch_norms(client: &Client, symbol: &Symbol) -> Result<FuturesNorms, Box<dyn Error>> {
+    let info = retry::read(|| client.futures_exchange_info()).await?;
+    let info = serde_json::to_value(info)?;
+
+    let normal = info["symbols"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|v| v["symbol"] == symbol.as_str())
+        .ok_or(format!("futures symbol not found {}", symbol))?;
+
+    Ok(serde_json::from_value(normal.clone())?)
+}
+
+pub async fn fetch_price(client: &Client, symbol: &Symbol) -> Result<Price, Box<dyn Error>> {
+    #[derive(Deserialize)]
+    struct Ticker {
+        price: Decimal,
+    }
+
+    let ticker: Ticker = convert(retry::read(|| client.futures_price(symbol)).await?)?;
+
+    Ok(ticker.price)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum MarginType {
+    Isolated,
+    Crossed,
+}
+
+impl MarginType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Isolated => "ISOLATED",
+            Self::Crossed => "CROSSED",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Leverage {
+    pub symbol: Symbol,
+    pub leverage: u8,
+    /// Largest position notional at this leverage.
+    pub max_notional_value: String,
+}
+
+/// Sets the initial leverage of `symbol`. Never retried, like any change.
+pub async fn set_leverage(
+    client: &Client,
+    symbol: &Symbol,
+    leverage: u8,
+) -> Result<Leverage, Box<dyn Error>> {
+    if !(1..=MAX_LEVERAGE).contains(&leverage) {
+        return Err(format!("leverage {} is not within 1 and {}", leverage, MAX_LEVERAGE).into());
+    }
+
+    let result = client.futures_change_leverage(symbol, leverage).await?;
+
+    Ok(convert(result)?)
+}
+
+/// Sets the margin type of `symbol`, returning whether it changed.
+pub async fn set_margin_type(
+    client: &Client,
+    symbol: &Symbol,
+    margin_type: MarginType,
+) -> Result<bool, Box<dyn Error>> {
+    match client
+        .futures_change_margin_type(symbol, margin_type.as_str())
+        .await
+    {
+        Ok(_) => Ok(true),
+        Err(e) if retry::error_code(&e.to_string()) == Some(MARGIN_TYPE_UNCHANGED) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// A position of the one way mode, or one side of the hedge mode.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Position {
+    pub symbol: Symbol,
+    /// `BOTH` in one way mode, `LONG` or `SHORT` in hedge mode.
+    pub position_side: String,
+    /// Contracts held, negative when short.
+    pub position_amt: Decimal,
+    pub entry_price: Decimal,
+    pub mark_price: Decimal,
+    #[serde(rename = "unRealizedProfit")]
+    pub unrealized_profit: Decimal,
+    pub liquidation_price: Decimal,
+    pub leverage: Decimal,
+    /// `isolated` or `cross`.
+    pub margin_type: String,
+    pub notional: Decimal,
+}
+
+/// Positions of `symbol`, or every open position when it is unset.
+pub async fn fetch_positions(
+    client: &Client,
+    symbol: Option<&Symbol>,
+) -> Result<Vec<Position>, Box<dyn Error>> {
+    let positions = retry::read(|| client.futures_position_risk(symbol)).await?;
+    let mut positions: Vec<Position> = convert(positions)?;
+
+    // Every listed contract is reported, most of them empty
+    if symbol.is_none() {
+        positions.retain(|v| !v.position_amt.is_zero());
+    }
+
+    Ok(positions)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FundingRate {
+    pub symbol: Symbol,
+    /// Paid by longs to shorts when positive, per funding period.
+    pub funding_rate: Decimal,
+    pub funding_time: u64,
+    /// Empty for the periods before the exchange recorded it.
+    #[serde(default)]
+    pub mark_price: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundingHistory {
+    pub symbol: Symbol,
+    /// Oldest first.
+    pub rates: Vec<FundingRate>,
+    /// What a long position of 1 quote notional paid over the history.
+    pub total_rate: Decimal,
+    pub average_rate: Option<Decimal>,
+}
+
+impl FundingHistory {
+    pub fn new(symbol: Symbol, mut rates: Vec<FundingRate>) -> Self {
+        rates.sort_by_key(|v| v.funding_time);
+
+        let total_rate: Decimal = rates.iter().map(|v| v.funding_rate).sum();
+        let average_rate = match rates.len() {
+            0 => None,
+            n => Some(total_rate / Decimal::from(n)),
+        };
+
+        Self {
+            symbol,
+            rates,
+            total_rate,
+            average_rate,
+        }
+    }
+}
+
+/// Funding rates of `symbol` between the times in milliseconds, the latest
+/// 100 when no time is given.
+pub async fn fetch_funding_rates(
+    client: &Client,
+    symbol: &Symbol,
+    start_time: Option<u64>,
+    end_time: Option<u64>,
+    limit: Option<u16>,
+) -> Result<FundingHistory, Box<dyn Error>> {
+    if limit.is_some_and(|v| v == 0 || v > MAX_FUNDING_RATES) {
+        return Err(format!("limit is not within 1 and {}", MAX_FUNDING_RATES).into());
+    }
+
+    let rates =
+        retry::read(|| client.futures_funding_rate(symbol, start_time, end_time, limit)).await?;
+
+    Ok(FundingHistory::new(symbol.clone(), convert(rates)?))
+}
+
+/// A market order as answered with the `RESULT` response type.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FuturesOrder {
+    pub symbol: Symbol,
+    pub order_id: i64,
+    pub client_order_id: String,
+    pub side: OrderSide,
+    pub status: String,
+    pub orig_qty: Decimal,
+    pub executed_qty: Decimal,
+    pub avg_price: Decimal,
+    pub cum_quote: Decimal,
+    pub reduce_only: bool,
+    pub update_time: u64,
+}
+
+impl ConvertTrades for FuturesOrder {
+    /// A single trade at the average price. The commission is charged to the
+    /// margin balance, not to the contracts or the quote received.
+    fn to_trades(&self) -> Vec<Trade> {
+        if self.executed_qty.is_zero() {
+            return Vec::new();
+        }
+
+        let trade = match self.side {
+            OrderSide::Buy => Trade::with_buy(self.avg_price, self.executed_qty, self.cum_quote),
+            OrderSide::Sell => Trade::with_sell(self.avg_price, self.executed_qty, self.cum_quote),
+        };
+
+        vec![trade]
+    }
+}
+
+/// Trades a USDⓈ-M contract at market. A buy opens or adds to a long, or
+/// reduces a short, and a sell the other way around.
+///
+/// Orders are held to the halts and the order notional and rate limits of
+/// the risk manager. Its position and daily loss limits track spot holdings
+/// and do not apply.
+pub struct BinanceFutures {
+    client: Client,
+    normal: FuturesNorms,
+    audit: Arc<AuditLog>,
+    risk: Arc<RiskManager>,
+    caller: String,
+    reduce_only: bool,
+}
+
+impl BinanceFutures {
+    pub fn new(
+        normal: FuturesNorms,
+        client: Client,
+        audit: Arc<AuditLog>,
+        risk: Arc<RiskManager>,
+        caller: String,
+        reduce_only: bool,
+    ) -> Self {
+        Self {
+            client,
+            normal,
+            audit,
+            risk,
+            caller,
+            reduce_only,
+        }
+    }
+
+    pub fn normal(&self) -> &FuturesNorms {
+        &self.normal
+    }
+
+    /// The contract quantity an order of `quantity` is placed with, once it
+    /// passes the filters of the contract.
+    pub fn prepare(
+        &self,
+        price: &Price,
+        quantity: &BaseQuantity,
+    ) -> Result<BaseQuantity, SymbolFilterError> {
+        trading::filter(&self.normal, trading::MARKET)?;
+        let quantity = self.normal.correct_quantity(quantity)?;
+        self.normal
+            .filter_quantity(price, &quantity, self.reduce_only)?;
+
+        Ok(quantity)
+    }
+
+    /// Buys the contracts `quantity` quote is worth at `price`.
+    pub async fn market_buy(
+        &self,
+        price: &Price,
+        quantity: &QuoteQuantity,
+    ) -> Result<FuturesOrder, Box<dyn Error>> {
+        let payload = serde_json::json!({
+            "symbol": self.normal.symbol,
+            "price": price,
+            "quote_quantity": quantity,
+            "reduce_only": self.reduce_only,
+        });
+        let quantity = self.normal.quote_to_base(price, quantity);
+
+        self.place(Action::FuturesBuy, OrderSide::Buy, price, quantity, payload)
+            .await
+    }
+
+    /// Sells `quantity` contracts.
+    pub async fn market_sell(
+        &self,
+        price: &Price,
+        quantity: &BaseQuantity,
+    ) -> Result<FuturesOrder, Box<dyn Error>> {
+        let payload = serde_json::json!({
+            "symbol": self.normal.symbol,
+            "price": price,
+            "base_quantity": quantity,
+            "reduce_only": self.reduce_only,
+        });
+
+        self.place(
+            Action::FuturesSell,
+            OrderSide::Sell,
+            price,
+            Ok(*quantity),
+            payload,
+        )
+        .await
+    }
+
+    async fn place(
+        &self,
+        action: Action,
+        side: OrderSide,
+        price: &Price,
+        quantity: Result<BaseQuantity, SymbolFilterError>,
+        payload: serde_json::Value,
+    ) -> Result<FuturesOrder, Box<dyn Error>> {
+        let mut corrected_quantity = None;
+
+        let result = async {
+            let quantity = self.prepare(price, &quantity?)?;
+            corrected_quantity = Some(quantity);
+            self.risk.check_futures(
+                &self.caller,
+                &self.normal.symbol,
+                &self.normal.quote_asset,
+                &(price * quantity),
+            )?;
+            let client_order_id = crate::trace::client_order_id();
+
+            let order = self
+                .client
+                .futures_market_order(
+                    &self.normal.symbol,
+                    side,
+                    &quantity.to_string(),
+                    self.reduce_only,
+                    client_order_id.as_deref(),
+                )
+                .await?;
+
+            Ok::<_, Box<dyn Error>>(convert::<_, FuturesOrder>(order)?)
+        }
+        .await;
+
+        let corrected_quantity = corrected_quantity.map(|v| v.to_string());
+        let entry = Entry::new(
+            action,
+            self.caller.clone(),
+            payload,
+            corrected_quantity,
+            &result,
+        );
+        self.audit.record(entry);
+
+        result
+    }
+}
+
+impl Trader for BinanceFutures {
+    async fn buy(
+        &self,
+        price: &Price,
+        quantity: &QuoteQuantity,
+    ) -> Result<Vec<Trade>, Box<dyn Error>> {
+        Ok(self.market_buy(price, quantity).await?.to_trades())
+    }
+
+    async fn sell(
+        &self,
+        price: &Price,
+        quantity: &BaseQuantity,
+    ) -> Result<Vec<Trade>, Box<dyn Error>> {
+        Ok(self.market_sell(price, quantity).await?.to_trades())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate(time: u64, rate: &str) -> FundingRate {
+        serde_json::from_value(serde_json::json!({
+            "symbol": "BTCUSDT",
+            "fundingRate": rate,
+            "fundingTime": time,
+            "markPrice": "",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_funding_history() {
+        let rates = vec![
+            rate(1700028800000, "-0.0002"),
+            rate(1700000000000, "0.0001"),
+            rate(1700014400000, "0.0004"),
+        ];
+
+        let history = FundingHistory::new("BTCUSDT".into(), rates);
+        let times: Vec<u64> = history.rates.iter().map(|v| v.funding_time).collect();
+        assert_eq!(times, vec![1700000000000, 1700014400000, 1700028800000]);
+        assert_eq!(history.total_rate, Decimal::new(3, 4));
+        assert_eq!(history.average_rate, Some(Decimal::new(1, 4)));
+
+        let history = FundingHistory::new("BTCUSDT".into(), Vec::new());
+        assert_eq!(history.average_rate, None);
+    }
+
+    #[test]
+    fn test_order_to_trades() {
+        let order: FuturesOrder = serde_json::from_value(serde_json::json!({
+            "symbol": "BTCUSDT",
+            "orderId": 1,
+            "clientOrderId": "a",
+            "side": "SELL",
+            "status": "FILLED",
+            "origQty": "0.002",
+            "executedQty": "0.002",
+            "avgPrice": "65000",
+            "cumQuote": "130",
+            "reduceOnly": false,
+            "updateTime": 1700000000000u64,
+        }))
+        .unwrap();
+
+        assert_eq!(order.to_trades().len(), 1);
+        assert_eq!(order.cum_quote, Decimal::from(130));
+    }
+}
@@ -0,0 +1,173 @@
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use binance::types::Symbol;
+use serde::{Deserialize, Serialize};
+
+use crate::extension::LockResultExt;
+
+use super::risk::RiskError;
+
+/// A stop on trading. An unset account or symbol matches every account or
+/// symbol, so a halt with neither stops all trading.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Halt {
+    pub account: Option<String>,
+    pub symbol: Option<Symbol>,
+    pub reason: Option<String>,
+    pub since: u128,
+}
+
+impl Halt {
+    fn covers(&self, account: &str, symbol: &Symbol) -> bool {
+        self.account.as_deref().is_none_or(|v| v == account)
+            && self.symbol.as_ref().is_none_or(|v| v == symbol)
+    }
+
+    fn same_scope(&self, account: Option<&str>, symbol: Option<&Symbol>) -> bool {
+        self.account.as_deref() == account && self.symbol.as_ref() == symbol
+    }
+
+    fn scope(&self) -> String {
+        let account = self.account.as_deref().unwrap_or("all accounts");
+        let symbol = self.symbol.as_deref().unwrap_or("all symbols");
+
+        format!("{}, {}", account, symbol)
+    }
+}
+
+/// Active halts, persisted to a JSON file so they survive a restart.
+#[derive(Debug)]
+pub struct Halts {
+    path: PathBuf,
+    halts: Mutex<Vec<Halt>>,
+}
+
+impl Halts {
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+
+        let halts = match std::fs::read(&path) {
+            Ok(v) => serde_json::from_slice(&v)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+
+        Ok(Self {
+            path,
+            halts: Mutex::new(halts),
+        })
+    }
+
+    /// Opens the file at `HALT_STATE_PATH`, by default `halt.json`.
+    pub fn from_env() -> io::Result<Self> {
+        Self::open(std::env::var("HALT_STATE_PATH").unwrap_or("halt.json".into()))
+    }
+
+    pub fn list(&self) -> Vec<Halt> {
+        self.halts.lock().ignore_poison().clone()
+    }
+
+    /// Adds `halt`, replacing any halt on the same account and symbol.
+    pub fn halt(&self, halt: Halt) -> io::Result<()> {
+        let mut halts = self.halts.lock().ignore_poison();
+        let mut next = halts.clone();
+        next.retain(|v| !v.same_scope(halt.account.as_deref(), halt.symbol.as_ref()));
+        next.push(halt);
+
+        self.persist(&next)?;
+        *halts = next;
+
+        Ok(())
+    }
+
+    /// Lifts the halt on exactly this account and symbol, returning it.
+    ///
+    /// Narrower or wider halts are left in place.
+    pub fn resume(&self, account: Option<&str>, symbol: Option<&Symbol>) -> io::Result<Vec<Halt>> {
+        let mut halts = self.halts.lock().ignore_poison();
+        let (removed, next): (Vec<Halt>, Vec<Halt>) = halts
+            .iter()
+            .cloned()
+            .partition(|v| v.same_scope(account, symbol));
+
+        self.persist(&next)?;
+        *halts = next;
+
+        Ok(removed)
+    }
+
+    pub fn check(&self, account: &str, symbol: &Symbol) -> Result<(), RiskError> {
+        let halts = self.halts.lock().ignore_poison();
+
+        match halts.iter().find(|v| v.covers(account, symbol)) {
+            Some(v) => Err(RiskError::Halted(format!(
+                "{} since {}: {}",
+                v.scope(),
+                v.since,
+                v.reason.as_deref().unwrap_or("no reason given")
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    // Written aside and renamed, so a crash never leaves a truncated file
+    fn persist(&self, halts: &[Halt]) -> io::Result<()> {
+        let temporary = self.path.with_extension("tmp");
+        std::fs::write(&temporary, serde_json::to_vec_pretty(halts)?)?;
+
+        std::fs::rename(temporary, &self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn halt(account: Option<&str>, symbol: Option<&str>) -> Halt {
+        Halt {
+            account: account.map(String::from),
+            symbol: symbol.map(String::from),
+            reason: Some("test".into()),
+            since: 0,
+        }
+    }
+
+    #[test]
+    fn test_halt_scope_and_persist() {
+        let path = std::env::temp_dir().join(format!("harmony-halt-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let (eth, btc) = (String::from("ETHUSDT"), String::from("BTCUSDT"));
+
+        let halts = Halts::open(&path).unwrap();
+        halts.halt(halt(Some("key:a"), None)).unwrap();
+        halts.halt(halt(None, Some("BTCUSDT"))).unwrap();
+
+        assert!(matches!(
+            halts.check("key:a", &eth),
+            Err(RiskError::Halted(_))
+        ));
+        assert!(matches!(
+            halts.check("key:b", &btc),
+            Err(RiskError::Halted(_))
+        ));
+        assert!(halts.check("key:b", &eth).is_ok());
+
+        // Halts survive a reopen
+        let halts = Halts::open(&path).unwrap();
+        assert_eq!(halts.list().len(), 2);
+
+        let removed = halts.resume(Some("key:a"), None).unwrap();
+        assert_eq!(removed, vec![halt(Some("key:a"), None)]);
+        assert!(halts.check("key:a", &eth).is_ok());
+        assert!(halts.check("key:a", &btc).is_err());
+
+        halts.halt(halt(None, None)).unwrap();
+        assert!(halts.check("key:c", &eth).is_err());
+
+        let halts = Halts::open(&path).unwrap();
+        assert_eq!(halts.list().len(), 2);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
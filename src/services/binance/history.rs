@@ -0,0 +1,271 @@
+use std::error::Error;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use binance::error::ClientError;
+use binance::prelude::Client;
+use binance::types::{Symbol, Trade};
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+use crate::retry;
+
+/// Largest page of account trades Binance returns.
+const TRADES_PAGE_LIMIT: u16 = 1000;
+
+/// Which cached trades a query returns.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TradeQuery {
+    pub order_id: Option<i64>,
+    pub start_time: Option<u128>,
+    pub end_time: Option<u128>,
+    pub from_id: Option<i64>,
+    pub limit: Option<usize>,
+}
+
+impl TradeQuery {
+    fn matches(&self, trade: &Trade) -> bool {
+        self.order_id.is_none_or(|v| trade.order_id == v)
+            && self.start_time.is_none_or(|v| trade.time >= v)
+            && self.end_time.is_none_or(|v| trade.time <= v)
+            && self.from_id.is_none_or(|v| trade.id >= v)
+    }
+}
+
+/// Outcome of syncing one symbol.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncReport {
+    pub symbol: Symbol,
+    /// Trades fetched and stored by this sync.
+    pub added: usize,
+    /// Trades cached after this sync.
+    pub total: usize,
+    pub last_id: Option<i64>,
+}
+
+/// Account trades cached on disk, one JSON lines file per account and symbol.
+///
+/// Files are only ever appended to in trade id order, so the last line
+/// holds the cursor the next sync continues from.
+#[derive(Debug)]
+pub struct TradeHistory {
+    path: PathBuf,
+    // Held while a file is read or appended, never across a request
+    lock: Mutex<()>,
+}
+
+impl TradeHistory {
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        std::fs::create_dir_all(&path)?;
+
+        Ok(Self {
+            path,
+            lock: Mutex::new(()),
+        })
+    }
+
+    /// Opens the directory at `TRADE_CACHE_PATH`, by default `trades`.
+    pub fn from_env() -> io::Result<Self> {
+        Self::open(std::env::var("TRADE_CACHE_PATH").unwrap_or("trades".into()))
+    }
+
+    /// Pulls the trades of `symbol` newer than the cache and appends them.
+    pub async fn sync(
+        &self,
+        client: &Client,
+        caller: &str,
+        symbol: &Symbol,
+    ) -> Result<SyncReport, Box<dyn Error>> {
+        let cached = self.load(caller, symbol).await?;
+        let from_id = cached.last().map_or(0, |v| v.id + 1);
+        let fetched = fetch_trades(client, symbol, from_id).await?;
+
+        let _guard = self.lock.lock().await;
+        let path = self.file(caller, symbol)?;
+
+        // A concurrent sync may have stored part of the page already
+        let last_id = read(&path).await?.last().map(|v| v.id);
+        let fresh: Vec<&Trade> = fetched
+            .iter()
+            .filter(|v| last_id.is_none_or(|id| v.id > id))
+            .collect();
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await?;
+        let mut lines = Vec::new();
+        for trade in fresh.iter() {
+            serde_json::to_writer(&mut lines, trade)?;
+            lines.push(b'\n');
+        }
+        file.write_all(&lines).await?;
+        file.sync_data().await?;
+
+        let trades = read(&path).await?;
+
+        Ok(SyncReport {
+            symbol: symbol.clone(),
+            added: fresh.len(),
+            total: trades.len(),
+            last_id: trades.last().map(|v| v.id),
+        })
+    }
+
+    /// Syncs `symbol` and returns every trade of `caller` on it, oldest first.
+    pub async fn trades(
+        &self,
+        client: &Client,
+        caller: &str,
+        symbol: &Symbol,
+    ) -> Result<Vec<Trade>, Box<dyn Error>> {
+        self.sync(client, caller, symbol).await?;
+
+        self.load(caller, symbol).await
+    }
+
+    /// Every cached trade of `caller` on `symbol`, oldest first.
+    pub async fn load(&self, caller: &str, symbol: &Symbol) -> Result<Vec<Trade>, Box<dyn Error>> {
+        let _guard = self.lock.lock().await;
+
+        read(&self.file(caller, symbol)?).await
+    }
+
+    pub async fn query(
+        &self,
+        caller: &str,
+        symbol: &Symbol,
+        query: &TradeQuery,
+    ) -> Result<Vec<Trade>, Box<dyn Error>> {
+        let trades = self.load(caller, symbol).await?;
+
+        Ok(trades
+            .into_iter()
+            .filter(|v| query.matches(v))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .collect())
+    }
+
+    fn file(&self, caller: &str, symbol: &Symbol) -> Result<PathBuf, Box<dyn Error>> {
+        // Both end up in a file name
+        let safe = |v: &str| !v.is_empty() && v.chars().all(|c| c.is_ascii_alphanumeric());
+        let account = caller.trim_start_matches("key:");
+        if !safe(account) {
+            return Err(format!("invalid account {}", caller).into());
+        }
+        if !safe(symbol) {
+            return Err(format!("invalid symbol {}", symbol).into());
+        }
+
+        Ok(self.path.join(format!("{}_{}.jsonl", account, symbol)))
+    }
+}
+
+async fn read(path: &Path) -> Result<Vec<Trade>, Box<dyn Error>> {
+    let text = match tokio::fs::read_to_string(path).await {
+        Ok(v) => v,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut trades = Vec::new();
+    for line in text.lines() {
+        if line.is_empty() {
+            continue;
+        }
+
+        trades.push(serde_json::from_str(line)?);
+    }
+
+    Ok(trades)
+}
+
+/// Every trade of the account on `symbol` from `from_id` on, oldest first.
+pub async fn fetch_trades(
+    client: &Client,
+    symbol: &Symbol,
+    from_id: i64,
+) -> Result<Vec<Trade>, ClientError> {
+    let mut trades: Vec<Trade> = Vec::new();
+
+    loop {
+        let from_id = Some(trades.last().map_or(from_id, |v| v.id + 1));
+        let page = retry::read(|| {
+            client.spot_trades(
+                symbol,
+                None,
+                None,
+                None,
+                from_id,
+                Some(TRADES_PAGE_LIMIT),
+                None,
+            )
+        })
+        .await?;
+
+        let done = page.len() < TRADES_PAGE_LIMIT as usize;
+        trades.extend(page);
+        if done {
+            return Ok(trades);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(id: i64) -> Trade {
+        Trade {
+            symbol: "ETHUSDT".into(),
+            id,
+            order_id: id / 10,
+            price: "100".into(),
+            qty: "1".into(),
+            quote_qty: "100".into(),
+            commission: "0".into(),
+            commission_asset: "BNB".into(),
+            time: 1_700_000_000_000 + id as u128,
+            is_buyer: true,
+            is_maker: false,
+            is_best_match: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cache_query() {
+        let path = std::env::temp_dir().join(format!("harmony-trades-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        let history = TradeHistory::open(&path).unwrap();
+        let symbol = String::from("ETHUSDT");
+
+        let file = history.file("key:abc", &symbol).unwrap();
+        let lines: Vec<String> = [10, 11, 20]
+            .into_iter()
+            .map(|v| serde_json::to_string(&trade(v)).unwrap())
+            .collect();
+        std::fs::write(&file, lines.join("\n") + "\n").unwrap();
+
+        let query = TradeQuery {
+            order_id: Some(1),
+            ..Default::default()
+        };
+        let found = history.query("key:abc", &symbol, &query).await.unwrap();
+        assert_eq!(found.iter().map(|v| v.id).collect::<Vec<_>>(), [10, 11]);
+
+        let query = TradeQuery {
+            from_id: Some(11),
+            limit: Some(1),
+            ..Default::default()
+        };
+        let found = history.query("key:abc", &symbol, &query).await.unwrap();
+        assert_eq!(found[0].id, 11);
+        assert_eq!(found.len(), 1);
+
+        assert!(history.load("key:other", &symbol).await.unwrap().is_empty());
+        assert!(history.file("key:abc", &"../ETH".into()).is_err());
+    }
+}
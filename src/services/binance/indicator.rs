@@ -0,0 +1,327 @@
+use std::str::FromStr;
+
+use plot::types::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::klines::Candle;
+
+/// Decimal places of every computed value.
+const INDICATOR_SCALE: u32 = 8;
+
+/// Band width of Bollinger bands when none is given.
+const DEFAULT_BOLLINGER_WIDTH: i64 = 2;
+
+type Values = Vec<Option<Decimal>>;
+
+/// A technical indicator over closing prices, written as `name:period`,
+/// e.g. `sma:20`, or `bollinger:20:2` with the band width in deviations.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Indicator {
+    Sma(usize),
+    Ema(usize),
+    Rsi(usize),
+    Atr(usize),
+    Bollinger(usize, Decimal),
+}
+
+/// Values of one indicator, aligned with the candles. A value is unset until
+/// enough candles are seen.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Series {
+    pub name: String,
+    pub values: Vec<Option<Decimal>>,
+}
+
+impl Indicator {
+    /// Parses a comma separated list such as `sma:20,rsi:14`.
+    pub fn parse_list(value: &str) -> Result<Vec<Self>, String> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(Self::from_str)
+            .collect()
+    }
+
+    pub fn compute(&self, candles: &[Candle]) -> Vec<Series> {
+        let closes: Vec<Decimal> = candles.iter().map(|v| v.close).collect();
+        let series = |name: String, values: Values| Series {
+            name,
+            values: values
+                .into_iter()
+                .map(|v| v.map(|v| v.round_dp(INDICATOR_SCALE)))
+                .collect(),
+        };
+
+        match self {
+            Self::Sma(n) => vec![series(format!("sma:{}", n), sma(&closes, *n))],
+            Self::Ema(n) => vec![series(format!("ema:{}", n), ema(&closes, *n))],
+            Self::Rsi(n) => vec![series(format!("rsi:{}", n), rsi(&closes, *n))],
+            Self::Atr(n) => vec![series(format!("atr:{}", n), atr(candles, *n))],
+            Self::Bollinger(n, width) => {
+                let (lower, middle, upper) = bollinger(&closes, *n, *width);
+                let name = format!("bollinger:{}:{}", n, width);
+
+                vec![
+                    series(format!("{}:lower", name), lower),
+                    series(format!("{}:middle", name), middle),
+                    series(format!("{}:upper", name), upper),
+                ]
+            }
+        }
+    }
+}
+
+impl FromStr for Indicator {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid indicator {}", value);
+        let mut parts = value.split(':');
+        let name = parts.next().unwrap_or_default().to_lowercase();
+
+        let period: usize = match parts.next() {
+            Some(v) => v.parse().map_err(|_| invalid())?,
+            None => return Err(invalid()),
+        };
+        if period == 0 {
+            return Err(invalid());
+        }
+
+        let indicator = match name.as_str() {
+            "sma" => Self::Sma(period),
+            "ema" => Self::Ema(period),
+            "rsi" => Self::Rsi(period),
+            "atr" => Self::Atr(period),
+            "bollinger" => {
+                let width = match parts.next() {
+                    Some(v) => Decimal::from_str(v).map_err(|_| invalid())?,
+                    None => Decimal::from(DEFAULT_BOLLINGER_WIDTH),
+                };
+                Self::Bollinger(period, width)
+            }
+            _ => return Err(invalid()),
+        };
+
+        match parts.next() {
+            Some(_) => Err(invalid()),
+            None => Ok(indicator),
+        }
+    }
+}
+
+fn sma(values: &[Decimal], n: usize) -> Values {
+    let mut result = vec![None; values.len()];
+    let mut sum = Decimal::ZERO;
+
+    for (i, v) in values.iter().enumerate() {
+        sum += v;
+        if i >= n {
+            sum -= values[i - n];
+        }
+        if i + 1 >= n {
+            result[i] = Some(sum / Decimal::from(n));
+        }
+    }
+
+    result
+}
+
+/// Seeded with the simple average of the first `n` values.
+fn ema(values: &[Decimal], n: usize) -> Values {
+    let mut result = vec![None; values.len()];
+    if values.len() < n {
+        return result;
+    }
+
+    let alpha = Decimal::TWO / Decimal::from(n + 1);
+    let mut average = values[..n].iter().sum::<Decimal>() / Decimal::from(n);
+    result[n - 1] = Some(average);
+
+    for (i, v) in values.iter().enumerate().skip(n) {
+        average += (v - average) * alpha;
+        result[i] = Some(average);
+    }
+
+    result
+}
+
+/// Wilder's moving average, seeded with the simple average of the first `n`.
+fn wilder(values: &[Decimal], n: usize) -> Values {
+    let mut result = vec![None; values.len()];
+    if values.len() < n {
+        return result;
+    }
+
+    let period = Decimal::from(n);
+    let mut average = values[..n].iter().sum::<Decimal>() / period;
+    result[n - 1] = Some(average);
+
+    for (i, v) in values.iter().enumerate().skip(n) {
+        average = (average * (period - Decimal::ONE) + v) / period;
+        result[i] = Some(average);
+    }
+
+    result
+}
+
+fn rsi(closes: &[Decimal], n: usize) -> Values {
+    let mut result = vec![None; closes.len()];
+    if closes.len() < 2 {
+        return result;
+    }
+
+    let changes: Vec<Decimal> = closes.windows(2).map(|v| v[1] - v[0]).collect();
+    let gains: Vec<Decimal> = changes.iter().map(|&v| v.max(Decimal::ZERO)).collect();
+    let losses: Vec<Decimal> = changes.iter().map(|&v| (-v).max(Decimal::ZERO)).collect();
+
+    let gains = wilder(&gains, n);
+    let losses = wilder(&losses, n);
+    for (i, (gain, loss)) in gains.iter().zip(losses.iter()).enumerate() {
+        // Change `i` ends at candle `i + 1`
+        result[i + 1] = match (gain, loss) {
+            (Some(_), Some(loss)) if loss.is_zero() => Some(Decimal::ONE_HUNDRED),
+            (Some(gain), Some(loss)) => {
+                let strength = gain / loss;
+                Some(Decimal::ONE_HUNDRED - Decimal::ONE_HUNDRED / (Decimal::ONE + strength))
+            }
+            _ => None,
+        };
+    }
+
+    result
+}
+
+fn atr(candles: &[Candle], n: usize) -> Values {
+    let ranges: Vec<Decimal> = candles
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            let range = v.high - v.low;
+            match i.checked_sub(1).map(|i| candles[i].close) {
+                Some(close) => range.max((v.high - close).abs()).max((v.low - close).abs()),
+                None => range,
+            }
+        })
+        .collect();
+
+    wilder(&ranges, n)
+}
+
+/// Lower, middle and upper band, `width` population deviations apart.
+fn bollinger(closes: &[Decimal], n: usize, width: Decimal) -> (Values, Values, Values) {
+    let middle = sma(closes, n);
+    let mut lower = vec![None; closes.len()];
+    let mut upper = vec![None; closes.len()];
+
+    for (i, mean) in middle.iter().enumerate() {
+        let Some(mean) = mean else { continue };
+
+        let window = &closes[i + 1 - n..=i];
+        let variance = window
+            .iter()
+            .map(|v| (v - mean) * (v - mean))
+            .sum::<Decimal>()
+            / Decimal::from(n);
+        let deviation = sqrt(variance) * width;
+
+        lower[i] = Some(mean - deviation);
+        upper[i] = Some(mean + deviation);
+    }
+
+    (lower, middle, upper)
+}
+
+/// Square root by Newton's method, exact to the scale of the indicators.
+pub(super) fn sqrt(value: Decimal) -> Decimal {
+    if value <= Decimal::ZERO {
+        return Decimal::ZERO;
+    }
+
+    let epsilon = Decimal::new(1, INDICATOR_SCALE + 2);
+    let mut root = match value > Decimal::ONE {
+        true => value / Decimal::TWO,
+        false => Decimal::ONE,
+    };
+    loop {
+        let next = (root + value / root) / Decimal::TWO;
+        if (next - root).abs() < epsilon {
+            return next;
+        }
+        root = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candles(closes: &[i64]) -> Vec<Candle> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, v)| Candle {
+                open_time: i as u128,
+                open: Decimal::from(*v),
+                high: Decimal::from(*v + 1),
+                low: Decimal::from(*v - 1),
+                close: Decimal::from(*v),
+                volume: Decimal::ONE,
+                close_time: i as u128,
+            })
+            .collect()
+    }
+
+    fn values(indicator: &str, closes: &[i64]) -> Vec<Series> {
+        Indicator::from_str(indicator)
+            .unwrap()
+            .compute(&candles(closes))
+    }
+
+    #[test]
+    fn test_parse() {
+        let list = Indicator::parse_list("sma:20, bollinger:20").unwrap();
+        assert_eq!(list[0], Indicator::Sma(20));
+        assert_eq!(list[1], Indicator::Bollinger(20, Decimal::TWO));
+
+        assert!(Indicator::from_str("sma").is_err());
+        assert!(Indicator::from_str("sma:0").is_err());
+        assert!(Indicator::from_str("macd:12").is_err());
+    }
+
+    #[test]
+    fn test_moving_averages() {
+        let sma = &values("sma:3", &[1, 2, 3, 4])[0];
+        assert_eq!(sma.values[..2], [None, None]);
+        assert_eq!(sma.values[2], Some(Decimal::from(2)));
+        assert_eq!(sma.values[3], Some(Decimal::from(3)));
+
+        // alpha = 0.5, seeded with 2
+        let ema = &values("ema:3", &[1, 2, 3, 5])[0];
+        assert_eq!(ema.values[2], Some(Decimal::from(2)));
+        assert_eq!(ema.values[3], Some(Decimal::new(35, 1)));
+    }
+
+    #[test]
+    fn test_rsi_and_atr() {
+        let rsi = &values("rsi:2", &[1, 2, 3, 2])[0];
+        assert_eq!(rsi.values[..2], [None, None]);
+        assert_eq!(rsi.values[2], Some(Decimal::ONE_HUNDRED));
+        // Gains 1 then 0.5, losses 0 then 0.5
+        assert_eq!(rsi.values[3], Some(Decimal::from(50)));
+
+        let atr = &values("atr:2", &[10, 10, 14])[0];
+        assert_eq!(atr.values[1], Some(Decimal::from(2)));
+        // True range of the gap up is 15 - 10
+        assert_eq!(atr.values[2], Some(Decimal::new(35, 1)));
+    }
+
+    #[test]
+    fn test_bollinger() {
+        let bands = values("bollinger:2:1", &[1, 3]);
+        assert_eq!(bands[0].name, "bollinger:2:1:lower");
+        assert_eq!(bands[0].values[1], Some(Decimal::from(1)));
+        assert_eq!(bands[1].values[1], Some(Decimal::from(2)));
+        assert_eq!(bands[2].values[1], Some(Decimal::from(3)));
+    }
+}
@@ -0,0 +1,59 @@
+use std::error::Error;
+use std::str::FromStr;
+
+use binance::prelude::Client;
+use binance::types::Symbol;
+use plot::types::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::retry;
+
+/// Intervals Binance serves candles for.
+pub const INTERVALS: [&str; 16] = [
+    "1s", "1m", "3m", "5m", "15m", "30m", "1h", "2h", "4h", "6h", "8h", "12h", "1d", "3d", "1w",
+    "1M",
+];
+
+/// One candlestick, times are Unix milliseconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Candle {
+    pub open_time: u128,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    pub volume: Decimal,
+    pub close_time: u128,
+}
+
+/// Candles of `symbol` oldest first, at most `limit` of them.
+pub async fn fetch_candles(
+    client: &Client,
+    symbol: &Symbol,
+    interval: &str,
+    start_time: Option<u128>,
+    end_time: Option<u128>,
+    limit: Option<u16>,
+) -> Result<Vec<Candle>, Box<dyn Error>> {
+    if !INTERVALS.contains(&interval) {
+        return Err(format!("invalid interval {}", interval).into());
+    }
+
+    let klines =
+        retry::read(|| client.klines(symbol, interval, start_time, end_time, limit)).await?;
+
+    let mut candles = Vec::with_capacity(klines.len());
+    for v in klines.iter() {
+        candles.push(Candle {
+            open_time: v.open_time,
+            open: Decimal::from_str(&v.open)?,
+            high: Decimal::from_str(&v.high)?,
+            low: Decimal::from_str(&v.low)?,
+            close: Decimal::from_str(&v.close)?,
+            volume: Decimal::from_str(&v.volume)?,
+            close_time: v.close_time,
+        });
+    }
+
+    Ok(candles)
+}
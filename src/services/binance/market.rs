@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::str::FromStr;
+
+use binance::prelude::Client;
+use binance::types::{Asset, ExchangeInfo, Symbol, SymbolInfo, SymbolPrice};
+use plot::types::{Decimal, Price};
+
+use crate::retry;
+
+use super::filter::spot::exchange::ExchangeLimits;
+
+/// Symbols a batch query covers: those listed, and every pair quoted in
+/// `quote` when it is set.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Selection {
+    pub symbols: Vec<Symbol>,
+    pub quote: Option<Asset>,
+}
+
+impl Selection {
+    /// Merges a single `symbol` with a comma separated `symbols` list.
+    pub fn new(symbol: Option<Symbol>, symbols: Option<&str>, quote: Option<Asset>) -> Self {
+        let mut result: Vec<Symbol> = symbol.into_iter().collect();
+        for v in symbols.unwrap_or_default().split(',').map(str::trim) {
+            if !v.is_empty() && !result.iter().any(|s| s == v) {
+                result.push(v.into());
+            }
+        }
+
+        Self {
+            symbols: result,
+            quote: quote.filter(|v| !v.is_empty()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty() && self.quote.is_none()
+    }
+
+    /// Whether `symbol`, quoted in `quote_asset` by its exchange info, is
+    /// selected. A symbol ending with the quote asset may be quoted in a
+    /// longer one, `TUSD` for `USD`, so the suffix is never enough.
+    fn matches(&self, symbol: &str, quote_asset: Option<&str>) -> bool {
+        self.symbols.iter().any(|v| v == symbol)
+            || self
+                .quote
+                .as_deref()
+                .is_some_and(|v| quote_asset == Some(v))
+    }
+
+    /// The selected prices out of `prices`, listed symbols first in the
+    /// order asked for. `normals` tell which asset each symbol is quoted
+    /// in, only needed to select by quote.
+    pub fn prices(
+        &self,
+        prices: Vec<SymbolPrice>,
+        normals: &[SymbolInfo],
+    ) -> Result<Vec<SymbolPrice>, String> {
+        let quotes: HashMap<&str, &str> = normals
+            .iter()
+            .map(|v| (v.symbol.as_str(), v.quote_asset.as_str()))
+            .collect();
+        let selected = prices
+            .into_iter()
+            .filter(|v| self.matches(&v.symbol, quotes.get(v.symbol.as_str()).copied()))
+            .collect();
+
+        self.order(selected, |v| &v.symbol)
+    }
+
+    /// The selected symbols out of the exchange info of every symbol,
+    /// listed ones first in the order asked for.
+    pub fn normals(&self, normals: Vec<SymbolInfo>) -> Result<Vec<SymbolInfo>, String> {
+        let selected = normals
+            .into_iter()
+            .filter(|v| self.matches(&v.symbol, Some(&v.quote_asset)))
+            .collect();
+
+        self.order(selected, |v| &v.symbol)
+    }
+
+    /// Moves the listed symbols of `selected` to the front, in the order
+    /// asked for, failing on the first one missing.
+    fn order<T>(
+        &self,
+        mut selected: Vec<T>,
+        symbol: impl Fn(&T) -> &str,
+    ) -> Result<Vec<T>, String> {
+        let mut result = Vec::with_capacity(selected.len());
+        for v in self.symbols.iter() {
+            match selected.iter().position(|s| symbol(s) == v.as_str()) {
+                Some(i) => result.push(selected.remove(i)),
+                None => return Err(format!("symbol not found {}", v)),
+            }
+        }
+        result.extend(selected);
+
+        Ok(result)
+    }
+}
+
+/// Last price of `symbol`.
+pub async fn fetch_price(client: &Client, symbol: &Symbol) -> Result<Price, Box<dyn Error>> {
+    let price = retry::read(|| client.price(symbol)).await?.price;
+
+    Ok(Decimal::from_str(&price)?)
+}
+
+/// Exchange info of `symbol`, the norms its orders are filtered by.
+pub async fn fetch_normal(client: &Client, symbol: &Symbol) -> Result<SymbolInfo, Box<dyn Error>> {
+    Ok(fetch_normal_with_limits(client, symbol).await?.0)
+}
+
+/// Exchange info of `symbol` and the order limits of the whole exchange,
+/// which come with it.
+pub async fn fetch_normal_with_limits(
+    client: &Client,
+    symbol: &Symbol,
+) -> Result<(SymbolInfo, ExchangeLimits), Box<dyn Error>> {
+    let mut info = retry::read(|| client.exchange_info(symbol)).await?;
+    let limits = ExchangeLimits::new(&info);
+
+    let normal = info
+        .symbols
+        .pop()
+        .ok_or(format!("symbol {} exchange info not found", symbol))?;
+
+    Ok((normal, limits))
+}
+
+/// Prices of the selected symbols, or of every symbol when none is selected,
+/// from a single request, and one for the exchange info when selecting by
+/// quote.
+pub async fn fetch_prices(
+    client: &Client,
+    selection: &Selection,
+) -> Result<Vec<SymbolPrice>, Box<dyn Error>> {
+    if let ([symbol], None) = (selection.symbols.as_slice(), &selection.quote) {
+        return Ok(vec![retry::read(|| client.price(symbol)).await?]);
+    }
+
+    let prices = retry::read(|| client.prices(None)).await?;
+    if selection.is_empty() {
+        return Ok(prices);
+    }
+
+    let normals = match selection.quote {
+        Some(_) => fetch_all_exchange_info(client).await?.symbols,
+        None => Vec::new(),
+    };
+
+    Ok(selection.prices(prices, &normals)?)
+}
+
+/// Exchange info of every symbol, from a single request.
+pub async fn fetch_all_exchange_info(client: &Client) -> Result<ExchangeInfo, Box<dyn Error>> {
+    Ok(retry::read(|| client.exchange_infos(None)).await?)
+}
+
+/// Exchange info of the selected symbols, filtered out of that of every
+/// symbol, which weighs less than asking for each selected one.
+pub async fn fetch_exchange_info(
+    client: &Client,
+    selection: &Selection,
+) -> Result<ExchangeInfo, Box<dyn Error>> {
+    if selection.is_empty() {
+        return Err("no symbol selected".into());
+    }
+
+    let mut info = fetch_all_exchange_info(client).await?;
+    info.symbols = selection.normals(info.symbols)?;
+
+    Ok(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::binance::filter::spot::tests::pair_norms;
+
+    fn price(symbol: &str) -> SymbolPrice {
+        SymbolPrice {
+            symbol: symbol.into(),
+            price: "1".into(),
+        }
+    }
+
+    #[test]
+    fn test_selection() {
+        let selection = Selection::new(
+            Some("ETHBTC".into()),
+            Some("BNBUSDT, ETHBTC,"),
+            Some("USDT".into()),
+        );
+        assert_eq!(selection.symbols, vec!["ETHBTC", "BNBUSDT"]);
+
+        let prices = || {
+            vec![
+                price("BTCUSDT"),
+                price("BNBUSDT"),
+                price("ETHBTC"),
+                price("BNBBTC"),
+                price("BTCTUSD"),
+            ]
+        };
+        let normals = [
+            pair_norms("BTCUSDT", "BTC", "USDT"),
+            pair_norms("BNBUSDT", "BNB", "USDT"),
+            pair_norms("ETHBTC", "ETH", "BTC"),
+            pair_norms("BNBBTC", "BNB", "BTC"),
+            pair_norms("BTCTUSD", "BTC", "TUSD"),
+        ];
+        let symbols: Vec<Symbol> = selection
+            .prices(prices(), &normals)
+            .unwrap()
+            .into_iter()
+            .map(|v| v.symbol)
+            .collect();
+        assert_eq!(symbols, vec!["ETHBTC", "BNBUSDT", "BTCUSDT"]);
+
+        let selection = Selection::new(None, Some("XRPBTC"), None);
+        assert!(selection.prices(prices(), &normals).is_err());
+
+        // Quoted in TUSD, not USD, whatever the symbol ends with
+        let selection = Selection::new(None, None, Some("USD".into()));
+        assert!(selection.prices(prices(), &normals).unwrap().is_empty());
+        let selection = Selection::new(None, None, Some("USDT".into()));
+        let symbols: Vec<Symbol> = selection
+            .normals(normals.to_vec())
+            .unwrap()
+            .into_iter()
+            .map(|v| v.symbol)
+            .collect();
+        assert_eq!(symbols, vec!["BTCUSDT", "BNBUSDT"]);
+        assert!(Selection::new(None, Some(" , "), Some("".into())).is_empty());
+    }
+}
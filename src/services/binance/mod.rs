@@ -1,32 +1,95 @@
+pub mod depth;
+pub mod dry_run;
+pub mod dust;
+pub mod execution;
 pub mod filter;
+pub mod futures;
+pub mod halt;
+pub mod history;
+pub mod indicator;
+pub mod klines;
+pub mod market;
+pub mod pnl;
+pub mod portfolio;
+pub mod risk;
+pub mod suggest;
+pub mod tax;
+
+mod endpoint;
+pub use endpoint::Endpoint;
 
 use binance::{
+    error::ClientError,
     prelude::{Client, ClientBuilder},
-    types::{OrderSide, SymbolInfo},
+    types::{OrderInfo, OrderResponseFull, OrderSide, Symbol, SymbolInfo},
 };
 use filter::error::SymbolFilterError;
+use filter::spot::exchange::ExchangeLimits;
 use plot::{
     trade::{Trade, Trader},
-    types::{BaseQuantity, Decimal, Price, QuoteQuantity},
+    types::{BaseQuantity, Decimal, Price, Quantity, QuoteQuantity},
 };
-use std::{error::Error, time::Duration};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::{error::Error, sync::Arc, time::Duration};
+
+use crate::audit::{Action, AuditLog, Entry};
+use crate::idempotency;
+use crate::retry;
+use crate::secret::Secret;
+use risk::RiskManager;
+
+/// Binance error code of a query for an order that does not exist.
+const ORDER_NOT_FOUND: i64 = -2013;
 
 pub fn client() -> Result<Client, Box<dyn Error>> {
-    let result = ClientBuilder::new().build()?;
+    let result = ClientBuilder::new()
+        .set_base_url(Endpoint::global().base_url().to_string())
+        .build()?;
 
     Ok(result)
 }
 
-pub fn client_with_sign(api_key: String, secret_key: String) -> Result<Client, Box<dyn Error>> {
+/// Signed client for an account, on `endpoint` or the global endpoint.
+pub fn client_with_sign(
+    api_key: Secret<String>,
+    secret_key: Secret<String>,
+    endpoint: Option<&Endpoint>,
+) -> Result<Client, Box<dyn Error>> {
+    let endpoint = endpoint.unwrap_or(Endpoint::global());
+
     let result = ClientBuilder::new()
-        .set_api_key(api_key)
-        .set_secret_key(secret_key)
+        .set_base_url(endpoint.base_url().to_string())
+        .set_api_key(api_key.into_inner())
+        .set_secret_key(secret_key.into_inner())
         .set_timeout(Duration::from_secs(5))
         .build()?;
 
     Ok(result)
 }
 
+/// Proves the caller holds the secret key of the client's API key, with a
+/// signed request the exchange only answers when the signature matches.
+pub async fn authenticate(client: &Client) -> Result<(), ClientError> {
+    retry::read(|| client.user_asset(None, Some(false), None)).await?;
+
+    Ok(())
+}
+
+/// The order placed on `symbol` with `client_order_id`, if there is one.
+pub async fn find_order(
+    client: &Client,
+    symbol: &Symbol,
+    client_order_id: &str,
+) -> Result<Option<OrderInfo>, ClientError> {
+    let query = || client.spot_order_info_with_client_order_id(symbol, client_order_id, None);
+
+    match retry::read(query).await {
+        Ok(v) => Ok(Some(v)),
+        Err(e) if retry::error_code(&e.to_string()) == Some(ORDER_NOT_FOUND) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
 pub struct BinanceSpotTest {
     normal: SymbolInfo,
     commission: Decimal,
@@ -36,33 +99,59 @@ impl BinanceSpotTest {
     pub fn new(normal: SymbolInfo, commission: Decimal) -> Self {
         Self { normal, commission }
     }
-}
 
-impl Trader for BinanceSpotTest {
-    async fn buy(
+    pub fn normal(&self) -> &SymbolInfo {
+        &self.normal
+    }
+
+    /// Corrected quote quantity spent and base quantity received by a buy.
+    pub fn fill_buy(
         &self,
         price: &Price,
         quantity: &QuoteQuantity,
-    ) -> Result<Vec<Trade>, Box<dyn Error>> {
+    ) -> Result<(QuoteQuantity, BaseQuantity), SymbolFilterError> {
         let quote_quantity = self.normal.correct_quote_quantity(price, quantity)?;
         self.normal.filter_quote_quantity(price, &quote_quantity)?;
         let base_quantity = (quote_quantity / price) * (Decimal::ONE - self.commission);
         let base_quantity = base_quantity.trunc_with_scale(self.normal.base_asset_precision.into());
-        let trade = Trade::with_buy(price.clone(), base_quantity, quote_quantity);
 
-        Ok(vec![trade])
+        Ok((quote_quantity, base_quantity))
     }
 
-    async fn sell(
+    /// Corrected base quantity spent and quote quantity received by a sell.
+    pub fn fill_sell(
         &self,
         price: &Price,
         quantity: &BaseQuantity,
-    ) -> Result<Vec<Trade>, Box<dyn Error>> {
+    ) -> Result<(BaseQuantity, QuoteQuantity), SymbolFilterError> {
         let base_quantity = self.normal.correct_base_quantity(price, quantity)?;
         self.normal.filter_base_quantity(price, &base_quantity)?;
         let quote_quantity = (base_quantity * price) * (Decimal::ONE - self.commission);
         let quote_quantity =
             quote_quantity.trunc_with_scale(self.normal.quote_asset_precision.into());
+
+        Ok((base_quantity, quote_quantity))
+    }
+}
+
+impl Trader for BinanceSpotTest {
+    async fn buy(
+        &self,
+        price: &Price,
+        quantity: &QuoteQuantity,
+    ) -> Result<Vec<Trade>, Box<dyn Error>> {
+        let (quote_quantity, base_quantity) = self.fill_buy(price, quantity)?;
+        let trade = Trade::with_buy(price.clone(), base_quantity, quote_quantity);
+
+        Ok(vec![trade])
+    }
+
+    async fn sell(
+        &self,
+        price: &Price,
+        quantity: &BaseQuantity,
+    ) -> Result<Vec<Trade>, Box<dyn Error>> {
+        let (base_quantity, quote_quantity) = self.fill_sell(price, quantity)?;
         let trade = Trade::with_sell(price.clone(), base_quantity, quote_quantity);
 
         Ok(vec![trade])
@@ -72,11 +161,231 @@ impl Trader for BinanceSpotTest {
 pub struct BinanceSpot {
     client: Client,
     normal: SymbolInfo,
+    limits: ExchangeLimits,
+    audit: Arc<AuditLog>,
+    risk: Arc<RiskManager>,
+    caller: String,
+    max_slippage: Option<Decimal>,
+    idempotency_key: Option<String>,
+    orders: AtomicU32,
+}
+
+impl BinanceSpot {
+    pub fn new(
+        normal: SymbolInfo,
+        limits: ExchangeLimits,
+        client: Client,
+        audit: Arc<AuditLog>,
+        risk: Arc<RiskManager>,
+        caller: String,
+    ) -> Self {
+        Self {
+            client,
+            normal,
+            limits,
+            audit,
+            risk,
+            caller,
+            max_slippage: None,
+            idempotency_key: None,
+            orders: AtomicU32::new(0),
+        }
+    }
+
+    /// Refuses orders whose estimated price impact exceeds `max_slippage`
+    /// percent, unchecked when unset.
+    pub fn with_max_slippage(mut self, max_slippage: Option<Decimal>) -> Self {
+        self.max_slippage = max_slippage;
+        self
+    }
+
+    /// Numbers the orders placed from `key` instead of the request id, so a
+    /// retry with the same key can find them again.
+    pub fn with_idempotency_key(mut self, key: Option<String>) -> Self {
+        self.idempotency_key = key;
+        self
+    }
+
+    fn client_order_id(&self) -> Option<String> {
+        match &self.idempotency_key {
+            Some(key) => {
+                let sequence = self.orders.fetch_add(1, Ordering::Relaxed);
+                Some(idempotency::child_order_id(&self.caller, key, sequence))
+            }
+            None => crate::trace::client_order_id(),
+        }
+    }
+
+    fn audit(
+        &self,
+        action: Action,
+        payload: serde_json::Value,
+        corrected_quantity: Option<Decimal>,
+        result: &Result<OrderResponseFull, Box<dyn Error>>,
+    ) {
+        let corrected_quantity = corrected_quantity.map(|v| v.to_string());
+        let entry = Entry::new(
+            action,
+            self.caller.clone(),
+            payload,
+            corrected_quantity,
+            result,
+        );
+
+        self.audit.record(entry);
+    }
+
+    /// Spends `quantity` quote at market, corrected to the symbol filters,
+    /// once the account passes the risk checks.
+    pub async fn market_buy(
+        &self,
+        price: &Price,
+        quantity: &QuoteQuantity,
+    ) -> Result<OrderResponseFull, Box<dyn Error>> {
+        let client_order_id = self.client_order_id();
+
+        self.market_buy_with_client_order_id(price, quantity, client_order_id)
+            .await
+    }
+
+    /// [`market_buy`](Self::market_buy) placed as `client_order_id`, so a
+    /// retried request can find it again.
+    pub async fn market_buy_with_client_order_id(
+        &self,
+        price: &Price,
+        quantity: &QuoteQuantity,
+        client_order_id: Option<String>,
+    ) -> Result<OrderResponseFull, Box<dyn Error>> {
+        let mut corrected_quantity = None;
+
+        let result = async {
+            let quantity = self.normal.correct_quote_quantity(price, quantity)?;
+            corrected_quantity = Some(quantity);
+            self.normal.filter_quote_quantity(price, &quantity)?;
+            self.check_slippage(OrderSide::Buy, &quantity).await?;
+            let reservation = self
+                .risk
+                .pre_trade(
+                    &self.client,
+                    &self.caller,
+                    &self.normal,
+                    &self.limits,
+                    OrderSide::Buy,
+                    price,
+                    &quantity,
+                )
+                .await?;
+
+            // Buy the base quantity by the quoted quantity
+            let order = self
+                .client
+                .spot_market_order_with_quote(
+                    &self.normal.symbol,
+                    OrderSide::Buy,
+                    &quantity.to_string(),
+                    client_order_id.as_deref(),
+                )
+                .await?;
+
+            self.risk.record(reservation, &order);
+
+            Ok::<_, Box<dyn Error>>(order)
+        }
+        .await;
+
+        let payload = serde_json::json!({
+            "symbol": self.normal.symbol,
+            "price": price,
+            "quote_quantity": quantity,
+            "max_slippage": self.max_slippage,
+            "client_order_id": client_order_id,
+        });
+        self.audit(Action::SpotBuy, payload, corrected_quantity, &result);
+
+        result
+    }
+
+    /// Sells `quantity` base at market, corrected to the symbol filters,
+    /// once the account passes the risk checks.
+    pub async fn market_sell(
+        &self,
+        price: &Price,
+        quantity: &BaseQuantity,
+    ) -> Result<OrderResponseFull, Box<dyn Error>> {
+        let client_order_id = self.client_order_id();
+
+        self.market_sell_with_client_order_id(price, quantity, client_order_id)
+            .await
+    }
+
+    /// [`market_sell`](Self::market_sell) placed as `client_order_id`, so a
+    /// retried request can find it again.
+    pub async fn market_sell_with_client_order_id(
+        &self,
+        price: &Price,
+        quantity: &BaseQuantity,
+        client_order_id: Option<String>,
+    ) -> Result<OrderResponseFull, Box<dyn Error>> {
+        let mut corrected_quantity = None;
+
+        let result = async {
+            let quantity = self.normal.correct_base_quantity(price, quantity)?;
+            corrected_quantity = Some(quantity);
+            self.normal.filter_base_quantity(price, &quantity)?;
+            self.check_slippage(OrderSide::Sell, &quantity).await?;
+            let reservation = self
+                .risk
+                .pre_trade(
+                    &self.client,
+                    &self.caller,
+                    &self.normal,
+                    &self.limits,
+                    OrderSide::Sell,
+                    price,
+                    &quantity,
+                )
+                .await?;
+
+            let order = self
+                .client
+                .spot_market_order_with_base(
+                    &self.normal.symbol,
+                    OrderSide::Sell,
+                    &quantity.to_string(),
+                    client_order_id.as_deref(),
+                )
+                .await?;
+
+            self.risk.record(reservation, &order);
+
+            Ok::<_, Box<dyn Error>>(order)
+        }
+        .await;
+
+        let payload = serde_json::json!({
+            "symbol": self.normal.symbol,
+            "price": price,
+            "base_quantity": quantity,
+            "max_slippage": self.max_slippage,
+            "client_order_id": client_order_id,
+        });
+        self.audit(Action::SpotSell, payload, corrected_quantity, &result);
+
+        result
+    }
 }
 
 impl BinanceSpot {
-    pub fn new(normal: SymbolInfo, client: Client) -> Self {
-        Self { client, normal }
+    async fn check_slippage(
+        &self,
+        side: OrderSide,
+        quantity: &Quantity,
+    ) -> Result<(), Box<dyn Error>> {
+        if let Some(max) = &self.max_slippage {
+            depth::check_slippage(&self.client, &self.normal.symbol, side, quantity, max).await?;
+        }
+
+        Ok(())
     }
 }
 
@@ -86,21 +395,7 @@ impl Trader for BinanceSpot {
         price: &Price,
         quantity: &QuoteQuantity,
     ) -> Result<Vec<Trade>, Box<dyn Error>> {
-        let quantity = self.normal.correct_quote_quantity(price, quantity)?;
-        self.normal.filter_quote_quantity(price, &quantity)?;
-
-        // Buy the base quantity by the quoted quantity
-        let order = self
-            .client
-            .spot_market_order_with_quote(
-                &self.normal.symbol,
-                OrderSide::Buy,
-                &quantity.to_string(),
-                None,
-            )
-            .await?;
-
-        Ok(order.to_trades())
+        Ok(self.market_buy(price, quantity).await?.to_trades())
     }
 
     async fn sell(
@@ -108,20 +403,7 @@ impl Trader for BinanceSpot {
         price: &Price,
         quantity: &BaseQuantity,
     ) -> Result<Vec<Trade>, Box<dyn Error>> {
-        let quantity = self.normal.correct_base_quantity(price, quantity)?;
-        self.normal.filter_base_quantity(price, &quantity)?;
-
-        let order = self
-            .client
-            .spot_market_order_with_base(
-                &self.normal.symbol,
-                OrderSide::Sell,
-                &quantity.to_string(),
-                None,
-            )
-            .await?;
-
-        Ok(order.to_trades())
+        Ok(self.market_sell(price, quantity).await?.to_trades())
     }
 }
 
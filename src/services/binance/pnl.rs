@@ -0,0 +1,469 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::error::Error;
+use std::str::FromStr;
+
+use binance::prelude::Client;
+use binance::types::{Asset, Symbol, SymbolInfo, Trade};
+use plot::types::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::retry;
+use crate::time::date;
+
+use super::history::TradeHistory;
+
+/// How disposals are matched against earlier acquisitions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CostMethod {
+    /// Oldest acquisition first.
+    #[default]
+    Fifo,
+    /// Newest acquisition first.
+    Lifo,
+    /// Highest unit cost first.
+    Hifo,
+    /// Every acquisition merged into one lot at the average price.
+    Average,
+}
+
+/// Base bought in one trade, or all of it under [`CostMethod::Average`],
+/// which keeps the first trade.
+#[derive(Debug, Clone, PartialEq)]
+struct Lot {
+    trade_id: i64,
+    time: u128,
+    quantity: Decimal,
+    /// Quote paid for `quantity`, fees included.
+    cost: Decimal,
+    /// Commission paid for `quantity`, valued in quote.
+    fee: Decimal,
+}
+
+impl Lot {
+    fn unit_cost(&self) -> Decimal {
+        self.cost / self.quantity
+    }
+}
+
+/// Part of one sell matched against part of one buy, valued in the quote asset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Disposal {
+    pub symbol: Symbol,
+    pub quote_asset: Asset,
+    pub quantity: Decimal,
+    /// UTC date of the buy, unset when the base was bought before the
+    /// ingested history.
+    pub acquired_date: Option<String>,
+    pub acquired_trade_id: Option<i64>,
+    pub disposed_date: String,
+    pub disposed_trade_id: i64,
+    /// Unset along with the acquisition.
+    pub cost: Option<Decimal>,
+    /// Net of the sell commission.
+    pub proceeds: Decimal,
+    /// Commissions of both trades already in `cost` and `proceeds`.
+    pub fees: Decimal,
+    pub gain: Option<Decimal>,
+    /// Unix time of the sell in milliseconds.
+    pub disposed_time: u128,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fee {
+    pub asset: Asset,
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyPnl {
+    /// UTC date, `YYYY-MM-DD`.
+    pub date: String,
+    pub realized: Decimal,
+}
+
+/// Profit and loss of one symbol, in its quote asset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PnlReport {
+    pub symbol: Symbol,
+    pub base_asset: Asset,
+    pub quote_asset: Asset,
+    pub method: CostMethod,
+    pub trades: usize,
+    /// Base still held from the ingested trades.
+    pub position: Decimal,
+    pub cost_basis: Decimal,
+    pub average_price: Option<Decimal>,
+    pub market_price: Decimal,
+    pub realized: Decimal,
+    pub unrealized: Decimal,
+    /// Base sold beyond what the ingested trades bought, booked at no profit.
+    pub unmatched: Decimal,
+    /// Commissions by asset. Base and quote fees are already in the cost
+    /// basis and proceeds, fees in other assets are not.
+    pub fees: Vec<Fee>,
+    /// Realized profit and loss by the day of the closing trade.
+    pub daily: Vec<DailyPnl>,
+}
+
+/// Books the trades of one symbol into positions and realized profit, and
+/// every sell into the lots it disposed of.
+///
+/// Trades must be ingested in execution order. Commissions paid in the base
+/// or quote asset adjust the lot cost and proceeds, at the trade price for
+/// base commissions. Commissions in any other asset are only reported.
+#[derive(Debug, Clone)]
+pub struct PnlLedger {
+    symbol: Symbol,
+    base_asset: Asset,
+    quote_asset: Asset,
+    method: CostMethod,
+    trades: usize,
+    lots: VecDeque<Lot>,
+    realized: Decimal,
+    unmatched: Decimal,
+    fees: BTreeMap<Asset, Decimal>,
+    daily: BTreeMap<String, Decimal>,
+    disposals: Vec<Disposal>,
+}
+
+impl PnlLedger {
+    pub fn new(symbol: Symbol, base_asset: Asset, quote_asset: Asset, method: CostMethod) -> Self {
+        Self {
+            symbol,
+            base_asset,
+            quote_asset,
+            method,
+            trades: 0,
+            lots: VecDeque::new(),
+            realized: Decimal::ZERO,
+            unmatched: Decimal::ZERO,
+            fees: BTreeMap::new(),
+            daily: BTreeMap::new(),
+            disposals: Vec::new(),
+        }
+    }
+
+    /// Books every trade of `caller` on `normal`, synced into `history`
+    /// first.
+    pub async fn load(
+        client: &Client,
+        history: &TradeHistory,
+        caller: &str,
+        normal: &SymbolInfo,
+        method: CostMethod,
+    ) -> Result<Self, Box<dyn Error>> {
+        let trades = history.trades(client, caller, &normal.symbol).await?;
+
+        let mut ledger = Self::new(
+            normal.symbol.clone(),
+            normal.base_asset.clone(),
+            normal.quote_asset.clone(),
+            method,
+        );
+        for trade in trades.iter() {
+            ledger.ingest(trade)?;
+        }
+
+        Ok(ledger)
+    }
+
+    /// Books every trade of `caller` on `normal` and reports it at the
+    /// current price.
+    pub async fn fetch(
+        client: &Client,
+        history: &TradeHistory,
+        caller: &str,
+        normal: &SymbolInfo,
+        method: CostMethod,
+    ) -> Result<PnlReport, Box<dyn Error>> {
+        let ledger = Self::load(client, history, caller, normal, method).await?;
+        let price = retry::read(|| client.price(&normal.symbol)).await?.price;
+
+        Ok(ledger.report(Decimal::from_str(&price)?))
+    }
+
+    pub fn ingest(&mut self, trade: &Trade) -> Result<(), Box<dyn Error>> {
+        let price = Decimal::from_str(&trade.price)?;
+        let quantity = Decimal::from_str(&trade.qty)?;
+        let quote = Decimal::from_str(&trade.quote_qty)?;
+        let commission = Decimal::from_str(&trade.commission)?;
+
+        *self.fees.entry(trade.commission_asset.clone()).or_default() += commission;
+        let base_fee = fee_in(&self.base_asset, &trade.commission_asset, commission);
+        let quote_fee = fee_in(&self.quote_asset, &trade.commission_asset, commission);
+
+        let fee = base_fee * price + quote_fee;
+
+        self.trades += 1;
+        match trade.is_buyer {
+            // Nothing is left to dispose of when the commission took it all
+            true if quantity <= base_fee => {}
+            true => self.acquire(Lot {
+                trade_id: trade.id,
+                time: trade.time,
+                quantity: quantity - base_fee,
+                cost: quote + quote_fee,
+                fee,
+            }),
+            false => {
+                let cost = self.dispose(trade, quantity + base_fee, quote - quote_fee, fee);
+                let realized = quote - quote_fee - cost;
+
+                self.realized += realized;
+                *self.daily.entry(date(trade.time)).or_default() += realized;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Every sell so far, split by the lots it disposed of.
+    pub fn disposals(&self) -> &[Disposal] {
+        &self.disposals
+    }
+
+    pub fn into_disposals(self) -> Vec<Disposal> {
+        self.disposals
+    }
+
+    pub fn report(&self, market_price: Decimal) -> PnlReport {
+        let position: Decimal = self.lots.iter().map(|v| v.quantity).sum();
+        let cost_basis: Decimal = self.lots.iter().map(|v| v.cost).sum();
+
+        PnlReport {
+            symbol: self.symbol.clone(),
+            base_asset: self.base_asset.clone(),
+            quote_asset: self.quote_asset.clone(),
+            method: self.method,
+            trades: self.trades,
+            position,
+            cost_basis,
+            average_price: match position.is_zero() {
+                true => None,
+                false => Some(cost_basis / position),
+            },
+            market_price,
+            realized: self.realized,
+            unrealized: position * market_price - cost_basis,
+            unmatched: self.unmatched,
+            fees: self
+                .fees
+                .iter()
+                .map(|(asset, amount)| Fee {
+                    asset: asset.clone(),
+                    amount: *amount,
+                })
+                .collect(),
+            daily: self
+                .daily
+                .iter()
+                .map(|(date, realized)| DailyPnl {
+                    date: date.clone(),
+                    realized: *realized,
+                })
+                .collect(),
+        }
+    }
+
+    fn acquire(&mut self, lot: Lot) {
+        match (self.method, self.lots.front_mut()) {
+            (CostMethod::Average, Some(v)) => {
+                v.quantity += lot.quantity;
+                v.cost += lot.cost;
+                v.fee += lot.fee;
+            }
+            _ => self.lots.push_back(lot),
+        }
+    }
+
+    /// Splits a sell of `quantity` over the lots in the order of the method,
+    /// returning the cost it carried.
+    fn dispose(
+        &mut self,
+        trade: &Trade,
+        quantity: Decimal,
+        proceeds: Decimal,
+        fee: Decimal,
+    ) -> Decimal {
+        let mut remaining = quantity;
+        let mut cost = Decimal::ZERO;
+
+        while remaining > Decimal::ZERO {
+            let Some(index) = self.next_lot() else { break };
+            let lot = &mut self.lots[index];
+
+            let part = remaining.min(lot.quantity);
+            let part_cost = share(lot.cost, part, lot.quantity);
+            let lot_fee = share(lot.fee, part, lot.quantity);
+            let part_proceeds = share(proceeds, part, quantity);
+
+            self.disposals.push(Disposal {
+                symbol: self.symbol.clone(),
+                quote_asset: self.quote_asset.clone(),
+                quantity: part,
+                acquired_date: Some(date(lot.time)),
+                acquired_trade_id: Some(lot.trade_id),
+                disposed_date: date(trade.time),
+                disposed_trade_id: trade.id,
+                cost: Some(part_cost),
+                proceeds: part_proceeds,
+                fees: lot_fee + share(fee, part, quantity),
+                gain: Some(part_proceeds - part_cost),
+                disposed_time: trade.time,
+            });
+
+            lot.quantity -= part;
+            lot.cost -= part_cost;
+            lot.fee -= lot_fee;
+            if lot.quantity.is_zero() {
+                self.lots.remove(index);
+            }
+            cost += part_cost;
+            remaining -= part;
+        }
+
+        // Sold base bought before the ingested history, assumed at cost
+        if remaining > Decimal::ZERO {
+            let part_proceeds = share(proceeds, remaining, quantity);
+            self.unmatched += remaining;
+            cost += part_proceeds;
+
+            self.disposals.push(Disposal {
+                symbol: self.symbol.clone(),
+                quote_asset: self.quote_asset.clone(),
+                quantity: remaining,
+                acquired_date: None,
+                acquired_trade_id: None,
+                disposed_date: date(trade.time),
+                disposed_trade_id: trade.id,
+                cost: None,
+                proceeds: part_proceeds,
+                fees: share(fee, remaining, quantity),
+                gain: None,
+                disposed_time: trade.time,
+            });
+        }
+
+        cost
+    }
+
+    fn next_lot(&self) -> Option<usize> {
+        if self.lots.is_empty() {
+            return None;
+        }
+
+        match self.method {
+            CostMethod::Fifo | CostMethod::Average => Some(0),
+            CostMethod::Lifo => Some(self.lots.len() - 1),
+            // The earliest of equally priced lots goes first
+            CostMethod::Hifo => (0..self.lots.len())
+                .rev()
+                .max_by_key(|&i| self.lots[i].unit_cost()),
+        }
+    }
+}
+
+/// The part of `value` that `part` of `whole` carries, all of it when
+/// `part` is the whole.
+fn share(value: Decimal, part: Decimal, whole: Decimal) -> Decimal {
+    match part == whole {
+        true => value,
+        false => value * part / whole,
+    }
+}
+
+/// The part of a commission charged in `asset`.
+fn fee_in(asset: &Asset, commission_asset: &Asset, commission: Decimal) -> Decimal {
+    match asset == commission_asset {
+        true => commission,
+        false => Decimal::ZERO,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(is_buyer: bool, price: &str, qty: &str, fee: &str, fee_asset: &str) -> Trade {
+        let (p, q) = (
+            Decimal::from_str(price).unwrap(),
+            Decimal::from_str(qty).unwrap(),
+        );
+
+        Trade {
+            symbol: "ETHUSDT".into(),
+            id: 0,
+            order_id: 0,
+            price: price.into(),
+            qty: qty.into(),
+            quote_qty: (p * q).to_string(),
+            commission: fee.into(),
+            commission_asset: fee_asset.into(),
+            time: 1_735_689_600_000,
+            is_buyer,
+            is_maker: false,
+            is_best_match: true,
+        }
+    }
+
+    fn ledger(method: CostMethod) -> PnlLedger {
+        let mut ledger = PnlLedger::new("ETHUSDT".into(), "ETH".into(), "USDT".into(), method);
+        ledger
+            .ingest(&trade(true, "100", "1", "0", "USDT"))
+            .unwrap();
+        ledger
+            .ingest(&trade(true, "200", "1", "0", "USDT"))
+            .unwrap();
+        ledger
+            .ingest(&trade(false, "300", "1", "1", "USDT"))
+            .unwrap();
+
+        ledger
+    }
+
+    #[test]
+    fn test_fifo() {
+        let d = |v: &str| Decimal::from_str(v).unwrap();
+        let report = ledger(CostMethod::Fifo).report(d("250"));
+
+        assert_eq!(report.realized, d("199"));
+        assert_eq!(report.position, d("1"));
+        assert_eq!(report.cost_basis, d("200"));
+        assert_eq!(report.unrealized, d("50"));
+        assert_eq!(report.daily[0].date, "2025-01-01");
+        assert_eq!(report.fees[0].amount, d("1"));
+    }
+
+    #[test]
+    fn test_average() {
+        let d = |v: &str| Decimal::from_str(v).unwrap();
+        let report = ledger(CostMethod::Average).report(d("250"));
+
+        assert_eq!(report.realized, d("149"));
+        assert_eq!(report.average_price, Some(d("150")));
+        assert_eq!(report.unrealized, d("100"));
+    }
+
+    #[test]
+    fn test_base_fee_and_unmatched() {
+        let d = |v: &str| Decimal::from_str(v).unwrap();
+        let mut ledger = PnlLedger::new(
+            "ETHUSDT".into(),
+            "ETH".into(),
+            "USDT".into(),
+            CostMethod::Fifo,
+        );
+        ledger
+            .ingest(&trade(true, "100", "1", "0.1", "ETH"))
+            .unwrap();
+        ledger
+            .ingest(&trade(false, "100", "2", "0", "BNB"))
+            .unwrap();
+
+        let report = ledger.report(d("100"));
+        assert_eq!(report.position, d("0"));
+        assert_eq!(report.unmatched, d("1.1"));
+        assert_eq!(report.realized, d("-10"));
+    }
+}
@@ -0,0 +1,311 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::error::Error;
+use std::str::FromStr;
+
+use binance::prelude::Client;
+use binance::types::{Asset, Symbol, SymbolInfo, SymbolPrice, UserAsset};
+use plot::types::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::retry;
+
+use super::market::fetch_all_exchange_info;
+
+/// Assets tried as intermediate hops when an asset has no pair with the
+/// chosen quote.
+const BRIDGE_ASSETS: [&str; 6] = ["USDT", "FDUSD", "USDC", "BTC", "ETH", "BNB"];
+
+/// Decimal places of values in the quote asset.
+const VALUE_SCALE: u32 = 8;
+
+/// Decimal places of allocation percentages.
+const ALLOCATION_SCALE: u32 = 2;
+
+/// One asset of the account, valued in the quote asset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Holding {
+    pub asset: Asset,
+    pub free: Decimal,
+    pub locked: Decimal,
+    pub total: Decimal,
+    /// Price of one unit in the quote asset, unset when no route was found.
+    pub price: Option<Decimal>,
+    pub value: Option<Decimal>,
+    /// Share of the total equity in percent.
+    pub allocation: Option<Decimal>,
+    /// Symbols the price was derived through, empty for the quote itself.
+    pub route: Vec<Symbol>,
+}
+
+/// Every non-zero balance of an account valued in a single quote asset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Portfolio {
+    pub quote: Asset,
+    /// Sum of every priced holding.
+    pub equity: Decimal,
+    /// Holdings by value, largest first, unpriced ones last.
+    pub holdings: Vec<Holding>,
+    /// Assets without any route to the quote, left out of the equity.
+    pub unpriced: Vec<Asset>,
+}
+
+impl Portfolio {
+    /// Values the account behind `client` at current prices.
+    pub async fn fetch(client: &Client, quote: &Asset) -> Result<Self, Box<dyn Error>> {
+        let assets = retry::read(|| client.user_asset(None, Some(false), None)).await?;
+        let prices = retry::read(|| client.prices(None)).await?;
+        let normals = fetch_normals(client, &assets, &prices, &[quote.clone()]).await?;
+
+        Self::value(&assets, &prices, &normals, quote)
+    }
+
+    /// Values `assets` through the symbols of `normals`, the only ones whose
+    /// base and quote assets are known.
+    pub fn value(
+        assets: &[UserAsset],
+        prices: &[SymbolPrice],
+        normals: &[SymbolInfo],
+        quote: &Asset,
+    ) -> Result<Self, Box<dyn Error>> {
+        let market = Market::new(prices, normals);
+
+        let mut holdings = Vec::new();
+        for v in assets.iter() {
+            let free = Decimal::from_str(&v.free)?;
+            let locked = Decimal::from_str(&v.locked)?;
+            let total = free + locked;
+            if total.is_zero() {
+                continue;
+            }
+
+            let (price, route) = match market.route(&v.asset, quote) {
+                Some((price, route)) => (Some(price), route),
+                None => (None, Vec::new()),
+            };
+
+            holdings.push(Holding {
+                asset: v.asset.clone(),
+                free,
+                locked,
+                total,
+                price,
+                value: price.map(|v| (v * total).round_dp(VALUE_SCALE)),
+                allocation: None,
+                route,
+            });
+        }
+
+        let equity: Decimal = holdings.iter().filter_map(|v| v.value).sum();
+        for holding in holdings.iter_mut() {
+            holding.allocation = match (holding.value, equity.is_zero()) {
+                (Some(v), false) => {
+                    Some((v * Decimal::ONE_HUNDRED / equity).round_dp(ALLOCATION_SCALE))
+                }
+                _ => None,
+            };
+        }
+
+        holdings.sort_by_key(|v| std::cmp::Reverse(v.value));
+        let unpriced = holdings
+            .iter()
+            .filter(|v| v.price.is_none())
+            .map(|v| v.asset.clone())
+            .collect();
+
+        Ok(Self {
+            quote: quote.clone(),
+            equity,
+            holdings,
+            unpriced,
+        })
+    }
+}
+
+/// Exchange info of every priced symbol that pairs two of the account's
+/// assets, `quotes` and the bridge assets, the only symbols a route to one
+/// of `quotes` goes through.
+pub async fn fetch_normals(
+    client: &Client,
+    assets: &[UserAsset],
+    prices: &[SymbolPrice],
+    quotes: &[Asset],
+) -> Result<Vec<SymbolInfo>, Box<dyn Error>> {
+    let mut wanted: HashSet<Asset> = assets.iter().map(|v| v.asset.clone()).collect();
+    wanted.extend(quotes.iter().cloned());
+    wanted.extend(BRIDGE_ASSETS.iter().map(|v| v.to_string()));
+
+    let priced: HashSet<&str> = prices.iter().map(|v| v.symbol.as_str()).collect();
+    let normals = fetch_all_exchange_info(client)
+        .await?
+        .symbols
+        .into_iter()
+        .filter(|v| {
+            wanted.contains(&v.base_asset)
+                && wanted.contains(&v.quote_asset)
+                && priced.contains(v.symbol.as_str())
+        })
+        .collect();
+
+    Ok(normals)
+}
+
+/// Conversion rates between assets, from the last price of every symbol.
+struct Market {
+    // Asset to the assets it converts into, with the symbol and rate
+    edges: HashMap<Asset, Vec<(Asset, Symbol, Decimal)>>,
+}
+
+impl Market {
+    fn new(prices: &[SymbolPrice], normals: &[SymbolInfo]) -> Self {
+        let normals: HashMap<&str, &SymbolInfo> =
+            normals.iter().map(|v| (v.symbol.as_str(), v)).collect();
+
+        let mut edges: HashMap<Asset, Vec<(Asset, Symbol, Decimal)>> = HashMap::new();
+        for v in prices.iter() {
+            let Some(normal) = normals.get(v.symbol.as_str()) else {
+                continue;
+            };
+            let price = match Decimal::from_str(&v.price) {
+                Ok(v) if !v.is_zero() => v,
+                _ => continue,
+            };
+
+            let (base, quote) = (&normal.base_asset, &normal.quote_asset);
+            edges
+                .entry(base.clone())
+                .or_default()
+                .push((quote.clone(), v.symbol.clone(), price));
+            edges.entry(quote.clone()).or_default().push((
+                base.clone(),
+                v.symbol.clone(),
+                Decimal::ONE / price,
+            ));
+        }
+
+        Self { edges }
+    }
+
+    /// Price of `from` in `to` along the route with the fewest hops.
+    fn route(&self, from: &Asset, to: &Asset) -> Option<(Decimal, Vec<Symbol>)> {
+        if from == to {
+            return Some((Decimal::ONE, Vec::new()));
+        }
+
+        let mut visited = HashSet::from([from.clone()]);
+        let mut queue = VecDeque::from([(from.clone(), Decimal::ONE, Vec::new())]);
+        while let Some((asset, rate, route)) = queue.pop_front() {
+            for (next, symbol, price) in self.edges.get(&asset).into_iter().flatten() {
+                if !visited.insert(next.clone()) {
+                    continue;
+                }
+
+                let mut route = route.clone();
+                route.push(symbol.clone());
+                if next == to {
+                    return Some((rate * price, route));
+                }
+
+                queue.push_back((next.clone(), rate * price, route));
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::binance::filter::spot::tests::pair_norms;
+
+    fn asset(asset: &str, free: &str, locked: &str) -> UserAsset {
+        UserAsset {
+            asset: asset.into(),
+            free: free.into(),
+            locked: locked.into(),
+            freeze: "0".into(),
+            withdrawing: "0".into(),
+            ipoable: "0".into(),
+            btc_valuation: "0".into(),
+        }
+    }
+
+    fn price(symbol: &str, price: &str) -> SymbolPrice {
+        SymbolPrice {
+            symbol: symbol.into(),
+            price: price.into(),
+        }
+    }
+
+    #[test]
+    fn test_value() {
+        let assets = vec![
+            asset("USDT", "100", "0"),
+            asset("BTC", "0.001", "0.001"),
+            asset("ABC", "20", "0"),
+            asset("XYZ", "5", "0"),
+            asset("ZERO", "0", "0"),
+        ];
+        let prices = vec![
+            price("BTCUSDT", "50000"),
+            price("ABCBTC", "0.0002"),
+            price("ETHBTC", "0.05"),
+        ];
+        let normals = vec![
+            pair_norms("BTCUSDT", "BTC", "USDT"),
+            pair_norms("ABCBTC", "ABC", "BTC"),
+            pair_norms("ETHBTC", "ETH", "BTC"),
+        ];
+        let d = |v: &str| Decimal::from_str(v).unwrap();
+
+        let portfolio = Portfolio::value(&assets, &prices, &normals, &"USDT".into()).unwrap();
+        assert_eq!(portfolio.equity, d("400"));
+        assert_eq!(portfolio.unpriced, vec![String::from("XYZ")]);
+
+        let order: Vec<&str> = portfolio
+            .holdings
+            .iter()
+            .map(|v| v.asset.as_str())
+            .collect();
+        assert_eq!(order, vec!["ABC", "USDT", "BTC", "XYZ"]);
+
+        // Priced through BTC, with locked balance included
+        let abc = &portfolio.holdings[0];
+        assert_eq!(abc.value, Some(d("200")));
+        assert_eq!(
+            abc.route,
+            vec![String::from("ABCBTC"), String::from("BTCUSDT")]
+        );
+        assert_eq!(portfolio.holdings[2].total, d("0.002"));
+        assert_eq!(portfolio.holdings[2].allocation, Some(d("25")));
+
+        // Inverse pairs are used for a quote that is the base of a symbol
+        let assets = vec![asset("USDT", "100", "0")];
+        let portfolio = Portfolio::value(&assets, &prices, &normals, &"BTC".into()).unwrap();
+        assert_eq!(portfolio.equity, d("0.002"));
+        assert_eq!(portfolio.holdings[0].route, vec![String::from("BTCUSDT")]);
+    }
+
+    #[test]
+    fn test_value_by_exchange_info() {
+        let d = |v: &str| Decimal::from_str(v).unwrap();
+        let assets = vec![asset("ABC", "10", "0")];
+        let prices = vec![price("ABCTRY", "30"), price("USDTTRY", "30")];
+
+        // No bridge asset ends either symbol, the exchange info splits them
+        let normals = vec![
+            pair_norms("ABCTRY", "ABC", "TRY"),
+            pair_norms("USDTTRY", "USDT", "TRY"),
+        ];
+        let portfolio = Portfolio::value(&assets, &prices, &normals, &"USDT".into()).unwrap();
+        assert_eq!(portfolio.equity, d("10"));
+        assert_eq!(
+            portfolio.holdings[0].route,
+            vec![String::from("ABCTRY"), String::from("USDTTRY")]
+        );
+
+        // A priced symbol without exchange info is never guessed at
+        let portfolio = Portfolio::value(&assets, &prices, &[], &"USDT".into()).unwrap();
+        assert_eq!(portfolio.unpriced, vec![String::from("ABC")]);
+    }
+}
@@ -0,0 +1,725 @@
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use binance::prelude::Client;
+use binance::types::{Asset, OrderResponseFull, OrderSide, Symbol, SymbolInfo};
+use plot::types::{Decimal, Price, Quantity};
+use serde::{Deserialize, Serialize};
+
+use crate::extension::LockResultExt;
+use crate::retry;
+use crate::time::timestamp;
+
+use super::dry_run::SimulatedBalance;
+use super::filter::spot::account::{self, OrderContext};
+use super::filter::spot::exchange::{self, ExchangeLimits};
+use super::filter::spot::trading;
+use super::halt::Halts;
+
+const SECONDS_PER_DAY: u64 = 86_400;
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Limits applied to an account before any order is placed.
+///
+/// A missing limit is not enforced.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RiskLimits {
+    /// Largest base quantity of a symbol the account may hold after a buy.
+    pub max_position: Option<Decimal>,
+    /// Largest quote value of a single order.
+    pub max_order_notional: Option<Decimal>,
+    /// Realized loss in quote asset after which orders are refused until the next UTC day.
+    ///
+    /// Counted in memory from the orders this process placed, so a restart
+    /// starts the day over.
+    pub max_daily_loss: Option<Decimal>,
+    pub max_orders_per_minute: Option<usize>,
+}
+
+/// Limits for every account, keyed by the caller id used in the audit log.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RiskConfig {
+    #[serde(default)]
+    pub default: RiskLimits,
+    #[serde(default)]
+    pub accounts: HashMap<String, RiskLimits>,
+}
+
+impl RiskConfig {
+    /// Reads the configuration from the JSON file at `RISK_LIMITS_PATH`,
+    /// or no limits at all when it is unset.
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        match std::env::var("RISK_LIMITS_PATH") {
+            Ok(path) => Ok(serde_json::from_slice(&std::fs::read(path)?)?),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn limits(&self, caller: &str) -> &RiskLimits {
+        self.accounts.get(caller).unwrap_or(&self.default)
+    }
+}
+
+#[derive(Debug)]
+pub enum RiskError {
+    InsufficientBalance(String),
+    MaxPosition(String),
+    MaxOrderNotional(String),
+    MaxDailyLoss(String),
+    OrderRate(String),
+    Halted(String),
+    MaxSlippage(String),
+}
+
+impl std::error::Error for RiskError {}
+impl std::fmt::Display for RiskError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            Self::InsufficientBalance(e) => format!("INSUFFICIENT_BALANCE {}", e),
+            Self::MaxPosition(e) => format!("MAX_POSITION {}", e),
+            Self::MaxOrderNotional(e) => format!("MAX_ORDER_NOTIONAL {}", e),
+            Self::MaxDailyLoss(e) => format!("MAX_DAILY_LOSS {}", e),
+            Self::OrderRate(e) => format!("MAX_ORDERS_PER_MINUTE {}", e),
+            Self::Halted(e) => format!("TRADING_HALTED {}", e),
+            Self::MaxSlippage(e) => format!("MAX_SLIPPAGE {}", e),
+        };
+
+        write!(f, "RISK {}", message)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Holding {
+    base: Decimal,
+    cost: Decimal,
+}
+
+/// An order that passed the checks and is not settled yet.
+#[derive(Debug)]
+struct Pending {
+    side: OrderSide,
+    base_asset: Asset,
+    quote_asset: Asset,
+    /// Base bought at the checked price, or sold.
+    base: Decimal,
+    /// Quote spent by a buy, zero for a sell.
+    quote: Decimal,
+}
+
+#[derive(Debug, Default)]
+struct Account {
+    orders: VecDeque<Instant>,
+    day: u64,
+    realized: Decimal,
+    holdings: HashMap<Symbol, Holding>,
+    pending: HashMap<u64, Pending>,
+    next_reservation: u64,
+}
+
+impl Account {
+    /// Quote of `asset` held by unsettled buys.
+    fn pending_quote(&self, asset: &str) -> Decimal {
+        self.pending
+            .values()
+            .filter(|v| matches!(v.side, OrderSide::Buy) && v.quote_asset == asset)
+            .map(|v| v.quote)
+            .sum()
+    }
+
+    /// Base of `asset` that unsettled orders on `side` buy or sell.
+    fn pending_base(&self, side: OrderSide, asset: &str) -> Decimal {
+        let buy = matches!(side, OrderSide::Buy);
+
+        self.pending
+            .values()
+            .filter(|v| matches!(v.side, OrderSide::Buy) == buy && v.base_asset == asset)
+            .map(|v| v.base)
+            .sum()
+    }
+
+    fn roll_day(&mut self) {
+        let today = timestamp().as_secs() / SECONDS_PER_DAY;
+        if self.day != today {
+            self.day = today;
+            self.realized = Decimal::ZERO;
+        }
+    }
+
+    fn count_order(&mut self, max: Option<usize>) -> Result<(), RiskError> {
+        if let Some(max) = max {
+            let now = Instant::now();
+            while let Some(v) = self.orders.front() {
+                match now.duration_since(*v) > RATE_WINDOW {
+                    true => self.orders.pop_front(),
+                    false => break,
+                };
+            }
+
+            if self.orders.len() >= max {
+                return Err(RiskError::OrderRate(format!(
+                    "{} orders in the last minute",
+                    self.orders.len()
+                )));
+            }
+
+            self.orders.push_back(now);
+        }
+
+        Ok(())
+    }
+}
+
+fn filter_notional(limits: &RiskLimits, notional: &Decimal, asset: &str) -> Result<(), RiskError> {
+    if let Some(max) = limits.max_order_notional {
+        if *notional > max {
+            return Err(RiskError::MaxOrderNotional(format!(
+                "{} {} exceeds {}",
+                notional, asset, max
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// The share of an account's balance and limits an order holds from
+/// [`RiskManager::check`] until [`RiskManager::record`] settles it. Dropped
+/// unsettled, because the order failed, it releases them.
+#[derive(Debug)]
+#[must_use]
+pub struct Reservation {
+    caller: String,
+    id: u64,
+    accounts: Arc<Mutex<HashMap<String, Account>>>,
+    settled: bool,
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        if self.settled {
+            return;
+        }
+
+        let mut accounts = self.accounts.lock().ignore_poison();
+        if let Some(account) = accounts.get_mut(&self.caller) {
+            account.pending.remove(&self.id);
+        }
+    }
+}
+
+/// Pre-trade checks and the per-account state they need.
+///
+/// Realized profit and loss is tracked with average cost over the orders
+/// placed through this process only, and like the order rate it is kept in
+/// memory: a restart resets both, only halts are persisted.
+#[derive(Debug)]
+pub struct RiskManager {
+    config: RiskConfig,
+    halts: Halts,
+    accounts: Arc<Mutex<HashMap<String, Account>>>,
+}
+
+impl RiskManager {
+    pub fn new(config: RiskConfig, halts: Halts) -> Self {
+        Self {
+            config,
+            halts,
+            accounts: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn halts(&self) -> &Halts {
+        &self.halts
+    }
+
+    /// Fetches the account balance and checks an order against it and the
+    /// account limits. `quantity` is in quote asset for a buy and base asset
+    /// for a sell. `limits` come with the exchange info of `normal`, so only
+    /// the balance and, when a filter counts them, the open orders are
+    /// fetched. The returned reservation is settled by [`Self::record`].
+    #[allow(clippy::too_many_arguments)]
+    pub async fn pre_trade(
+        &self,
+        client: &Client,
+        caller: &str,
+        normal: &SymbolInfo,
+        limits: &ExchangeLimits,
+        side: OrderSide,
+        price: &Price,
+        quantity: &Quantity,
+    ) -> Result<Reservation, Box<dyn Error>> {
+        // Refuse a halted order before asking the exchange anything
+        self.halts.check(caller, &normal.symbol)?;
+        trading::filter(normal, trading::MARKET)?;
+
+        let (balance, held) = SimulatedBalance::fetch_with_held(client, normal).await?;
+        let reservation = self.check(caller, &normal.symbol, &balance, side, price, quantity)?;
+
+        let base_quantity = match side {
+            OrderSide::Buy if price.is_zero() => Decimal::ZERO,
+            OrderSide::Buy => quantity / price,
+            OrderSide::Sell => *quantity,
+        };
+        let mut context = OrderContext::market(side, held);
+        // The order and position count filters need the open orders, the
+        // ones of every symbol only for the exchange limits
+        if !limits.is_empty() {
+            open_orders(client, normal, None, &mut context).await?;
+        } else if account::counts_open_orders(normal) {
+            open_orders(client, normal, Some(&normal.symbol), &mut context).await?;
+        }
+        account::filter(normal, &base_quantity, &context)?;
+        exchange::filter(limits, &context)?;
+
+        Ok(reservation)
+    }
+
+    /// Checks an order and, if it passes, counts it towards the order rate
+    /// and reserves its share of `balance` until it is settled.
+    ///
+    /// Orders reserved but not settled are not in `balance` yet, so they are
+    /// held against it under the lock that admits this one.
+    pub fn check(
+        &self,
+        caller: &str,
+        symbol: &Symbol,
+        balance: &SimulatedBalance,
+        side: OrderSide,
+        price: &Price,
+        quantity: &Quantity,
+    ) -> Result<Reservation, RiskError> {
+        self.halts.check(caller, symbol)?;
+        let limits = self.config.limits(caller);
+
+        let mut accounts = self.accounts.lock().ignore_poison();
+        let account = accounts.entry(caller.to_string()).or_default();
+        account.roll_day();
+
+        let (base, notional) = match side {
+            OrderSide::Buy => {
+                let free = balance.quote - account.pending_quote(&balance.quote_asset);
+                if free < *quantity {
+                    return Err(RiskError::InsufficientBalance(format!(
+                        "{} {} free, {} required",
+                        balance.quote_asset, free, quantity
+                    )));
+                }
+
+                match price.is_zero() {
+                    true => (Decimal::ZERO, *quantity),
+                    false => (quantity / price, *quantity),
+                }
+            }
+            OrderSide::Sell => {
+                let free =
+                    balance.base - account.pending_base(OrderSide::Sell, &balance.base_asset);
+                if free < *quantity {
+                    return Err(RiskError::InsufficientBalance(format!(
+                        "{} {} free, {} required",
+                        balance.base_asset, free, quantity
+                    )));
+                }
+
+                (*quantity, quantity * price)
+            }
+        };
+
+        filter_notional(limits, &notional, &balance.quote_asset)?;
+
+        if let (OrderSide::Buy, Some(max)) = (side, limits.max_position) {
+            if price.is_zero() {
+                return Err(RiskError::MaxPosition("price is zero".into()));
+            }
+
+            let bought = account.pending_base(OrderSide::Buy, &balance.base_asset);
+            let position = balance.base + bought + base;
+            if position > max {
+                return Err(RiskError::MaxPosition(format!(
+                    "{} {} exceeds {}",
+                    position, balance.base_asset, max
+                )));
+            }
+        }
+
+        if let Some(max) = limits.max_daily_loss {
+            if -account.realized >= max {
+                return Err(RiskError::MaxDailyLoss(format!(
+                    "realized {} today, limit {}",
+                    account.realized, max
+                )));
+            }
+        }
+
+        account.count_order(limits.max_orders_per_minute)?;
+
+        let id = account.next_reservation;
+        account.next_reservation += 1;
+        account.pending.insert(
+            id,
+            Pending {
+                side,
+                base_asset: balance.base_asset.clone(),
+                quote_asset: balance.quote_asset.clone(),
+                base,
+                quote: match side {
+                    OrderSide::Buy => *quantity,
+                    OrderSide::Sell => Decimal::ZERO,
+                },
+            },
+        );
+
+        Ok(Reservation {
+            caller: caller.to_string(),
+            id,
+            accounts: self.accounts.clone(),
+            settled: false,
+        })
+    }
+
+    /// Checks a futures order of `notional` quote and, if it passes, counts
+    /// it towards the order rate.
+    ///
+    /// The position and daily loss limits follow spot holdings and are not
+    /// applied here, a leveraged order is only held to the order notional
+    /// and rate limits.
+    pub fn check_futures(
+        &self,
+        caller: &str,
+        symbol: &Symbol,
+        quote_asset: &str,
+        notional: &Decimal,
+    ) -> Result<(), RiskError> {
+        self.halts.check(caller, symbol)?;
+        let limits = self.config.limits(caller);
+
+        filter_notional(limits, notional, quote_asset)?;
+
+        let mut accounts = self.accounts.lock().ignore_poison();
+        let account = accounts.entry(caller.to_string()).or_default();
+        account.count_order(limits.max_orders_per_minute)
+    }
+
+    /// Settles the reservation of a placed order and books its fills into
+    /// the realized profit and loss.
+    pub fn record(&self, mut reservation: Reservation, order: &OrderResponseFull) {
+        let mut accounts = self.accounts.lock().ignore_poison();
+        let account = accounts.entry(reservation.caller.clone()).or_default();
+        account.pending.remove(&reservation.id);
+        reservation.settled = true;
+        account.roll_day();
+
+        let holding = account.holdings.entry(order.symbol.clone()).or_default();
+        for fill in order.fills.iter() {
+            let price = dec(&fill.price);
+            let qty = dec(&fill.qty);
+            let commission = dec(&fill.commission);
+
+            match order.side {
+                OrderSide::Buy => {
+                    holding.base += qty - commission;
+                    holding.cost += price * qty;
+                }
+                OrderSide::Sell => {
+                    let proceeds = price * qty - commission;
+                    let cost = match holding.base.is_zero() {
+                        true => proceeds,
+                        false => holding.cost * qty.min(holding.base) / holding.base,
+                    };
+
+                    account.realized += proceeds - cost;
+                    holding.base = (holding.base - qty).max(Decimal::ZERO);
+                    holding.cost = (holding.cost - cost).max(Decimal::ZERO);
+                }
+            }
+        }
+    }
+}
+
+fn dec(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap_or_default()
+}
+
+/// Counts the open orders on `symbol`, or on every symbol when unset, into
+/// `context`.
+async fn open_orders(
+    client: &Client,
+    normal: &SymbolInfo,
+    symbol: Option<&Symbol>,
+    context: &mut OrderContext,
+) -> Result<(), Box<dyn Error>> {
+    let orders = retry::read(|| client.spot_open_orders(symbol, None)).await?;
+    for order in orders.iter() {
+        let algo = order.order_type.starts_with("STOP_LOSS")
+            || order.order_type.starts_with("TAKE_PROFIT");
+
+        context.exchange_open_orders += 1;
+        context.exchange_open_algo_orders += u32::from(algo);
+        if order.symbol != normal.symbol {
+            continue;
+        }
+
+        context.open_orders += 1;
+        context.open_algo_orders += u32::from(algo);
+        if !Decimal::from_str(&order.iceberg_qty)?.is_zero() {
+            context.open_iceberg_orders += 1;
+        }
+        if matches!(order.side, OrderSide::Buy) {
+            context.open_buy_quantity +=
+                Decimal::from_str(&order.orig_qty)? - Decimal::from_str(&order.executed_qty)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balance(base: &str, quote: &str) -> SimulatedBalance {
+        SimulatedBalance {
+            base_asset: "ETH".into(),
+            base: dec(base),
+            quote_asset: "USDT".into(),
+            quote: dec(quote),
+        }
+    }
+
+    fn manager(limits: RiskLimits) -> RiskManager {
+        let mut config = RiskConfig::default();
+        config.accounts.insert("key:limited".into(), limits);
+
+        let path = std::env::temp_dir().join(format!("harmony-risk-{}.json", std::process::id()));
+        RiskManager::new(config, Halts::open(path).unwrap())
+    }
+
+    #[test]
+    fn test_check() {
+        let risk = manager(RiskLimits {
+            max_position: Some(dec("1")),
+            max_order_notional: Some(dec("1000")),
+            max_daily_loss: None,
+            max_orders_per_minute: Some(2),
+        });
+        let symbol = String::from("ETHUSDT");
+        let price = dec("2000");
+        let funded = balance("0.2", "5000");
+
+        let result = risk.check(
+            "key:limited",
+            &symbol,
+            &balance("0", "10"),
+            OrderSide::Buy,
+            &price,
+            &dec("20"),
+        );
+        assert!(matches!(result, Err(RiskError::InsufficientBalance(_))));
+
+        let result = risk.check(
+            "key:limited",
+            &symbol,
+            &funded,
+            OrderSide::Sell,
+            &price,
+            &dec("0.6"),
+        );
+        assert!(matches!(result, Err(RiskError::InsufficientBalance(_))));
+
+        let result = risk.check(
+            "key:limited",
+            &symbol,
+            &funded,
+            OrderSide::Buy,
+            &price,
+            &dec("1500"),
+        );
+        assert!(matches!(result, Err(RiskError::MaxOrderNotional(_))));
+
+        let result = risk.check(
+            "key:limited",
+            &symbol,
+            &balance("0.9", "5000"),
+            OrderSide::Buy,
+            &price,
+            &dec("400"),
+        );
+        assert!(matches!(result, Err(RiskError::MaxPosition(_))));
+
+        assert!(risk
+            .check(
+                "key:limited",
+                &symbol,
+                &funded,
+                OrderSide::Buy,
+                &price,
+                &dec("400")
+            )
+            .is_ok());
+        assert!(risk
+            .check(
+                "key:limited",
+                &symbol,
+                &funded,
+                OrderSide::Sell,
+                &price,
+                &dec("0.1")
+            )
+            .is_ok());
+
+        let result = risk.check(
+            "key:limited",
+            &symbol,
+            &funded,
+            OrderSide::Sell,
+            &price,
+            &dec("0.1"),
+        );
+        assert!(matches!(result, Err(RiskError::OrderRate(_))));
+
+        // Other accounts fall back to the default limits, which are empty
+        let result = risk.check(
+            "key:other",
+            &symbol,
+            &funded,
+            OrderSide::Buy,
+            &price,
+            &dec("4000"),
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_reservation() {
+        let risk = manager(RiskLimits {
+            max_position: Some(dec("0.8")),
+            ..Default::default()
+        });
+        let symbol = String::from("ETHUSDT");
+        let price = dec("2000");
+        let funded = balance("0.2", "1500");
+        let check = |side: OrderSide, quantity: &str| {
+            risk.check(
+                "key:limited",
+                &symbol,
+                &funded,
+                side,
+                &price,
+                &dec(quantity),
+            )
+        };
+
+        // Each fits the balance alone, not together before either settles
+        let buy = check(OrderSide::Buy, "1000").unwrap();
+        let result = check(OrderSide::Buy, "600");
+        assert!(matches!(result, Err(RiskError::InsufficientBalance(_))));
+
+        // The base being bought counts towards the position
+        let result = check(OrderSide::Buy, "400");
+        assert!(matches!(result, Err(RiskError::MaxPosition(_))));
+
+        let sell = check(OrderSide::Sell, "0.15").unwrap();
+        let result = check(OrderSide::Sell, "0.1");
+        assert!(matches!(result, Err(RiskError::InsufficientBalance(_))));
+
+        // A failed order releases what it held
+        drop(buy);
+        drop(sell);
+        assert!(check(OrderSide::Buy, "1200").is_ok());
+        assert!(check(OrderSide::Sell, "0.2").is_ok());
+    }
+
+    #[test]
+    fn test_daily_loss() {
+        let risk = manager(RiskLimits {
+            max_daily_loss: Some(dec("50")),
+            ..Default::default()
+        });
+        let symbol = String::from("ETHUSDT");
+        let funded = balance("10", "10000");
+
+        let order = |side: OrderSide, price: &str, qty: &str| {
+            let order = serde_json::json!({
+                "symbol": "ETHUSDT",
+                "orderId": 1,
+                "clientOrderId": "",
+                "transactTime": 0,
+                "price": "0",
+                "origQty": qty,
+                "executedQty": qty,
+                "cummulativeQuoteQty": "0",
+                "status": "FILLED",
+                "side": side,
+                "fills": [{ "price": price, "qty": qty, "commission": "0", "commissionAsset": "USDT", "tradeId": 1 }],
+            });
+
+            serde_json::from_value::<OrderResponseFull>(order).unwrap()
+        };
+
+        let book = |side: OrderSide, price: &str, qty: &str| {
+            let quantity = match side {
+                OrderSide::Buy => dec(price) * dec(qty),
+                OrderSide::Sell => dec(qty),
+            };
+            let reservation = risk
+                .check(
+                    "key:limited",
+                    &symbol,
+                    &funded,
+                    side,
+                    &dec(price),
+                    &quantity,
+                )
+                .unwrap();
+            risk.record(reservation, &order(side, price, qty));
+        };
+
+        book(OrderSide::Buy, "2000", "1");
+        book(OrderSide::Sell, "1980", "0.5");
+        assert!(risk
+            .check(
+                "key:limited",
+                &symbol,
+                &funded,
+                OrderSide::Sell,
+                &dec("1950"),
+                &dec("0.1")
+            )
+            .is_ok());
+
+        book(OrderSide::Sell, "1900", "0.5");
+        let result = risk.check(
+            "key:limited",
+            &symbol,
+            &funded,
+            OrderSide::Sell,
+            &dec("1900"),
+            &dec("0.1"),
+        );
+        assert!(matches!(result, Err(RiskError::MaxDailyLoss(_))));
+    }
+
+    #[test]
+    fn test_check_futures() {
+        let risk = manager(RiskLimits {
+            max_position: Some(dec("0.001")),
+            max_order_notional: Some(dec("1000")),
+            max_daily_loss: None,
+            max_orders_per_minute: Some(1),
+        });
+        let symbol = String::from("BTCUSDT");
+
+        let result = risk.check_futures("key:limited", &symbol, "USDT", &dec("1500"));
+        assert!(matches!(result, Err(RiskError::MaxOrderNotional(_))));
+
+        // The position limit follows spot holdings only
+        assert!(risk
+            .check_futures("key:limited", &symbol, "USDT", &dec("650"))
+            .is_ok());
+
+        let result = risk.check_futures("key:limited", &symbol, "USDT", &dec("650"));
+        assert!(matches!(result, Err(RiskError::OrderRate(_))));
+    }
+}
@@ -0,0 +1,222 @@
+use plot::strategy::grid::Grid;
+use plot::types::{Decimal, Price, QuoteQuantity};
+use serde::{Deserialize, Serialize};
+
+use super::indicator::{sqrt, Indicator};
+use super::klines::Candle;
+use super::BinanceSpotTest;
+
+/// Candles the average true range is taken over.
+const ATR_PERIOD: usize = 14;
+
+/// Grid step as a share of the average true range, so a level is crossed
+/// about once every other candle.
+const ATR_STEP_SHARE: Decimal = Decimal::from_parts(5, 0, 0, false, 1);
+
+/// Share of the lows below the support and of the highs above the resistance.
+const RANGE_PERCENTILE: Decimal = Decimal::from_parts(10, 0, 0, false, 2);
+
+/// Smallest margin a level must keep above the round trip commission.
+const MIN_STEP_MARGIN: Decimal = Decimal::from_parts(5, 0, 0, false, 4);
+
+/// Most levels a suggestion is split into.
+const MAX_LEVELS: u32 = 200;
+
+/// Decimal places of derived prices and percentages.
+const SUGGEST_SCALE: u32 = 8;
+
+/// What the recent candles of a symbol say about its range.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Volatility {
+    pub candles: usize,
+    pub atr: Decimal,
+    /// Standard deviation of the candle to candle returns, in percent.
+    pub realized: Decimal,
+    pub support: Price,
+    pub resistance: Price,
+}
+
+/// Grid bounds and level count for a symbol, checked against its filters.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GridSuggestion {
+    pub price: Price,
+    pub lower: Price,
+    pub upper: Price,
+    pub levels: u32,
+    pub step: Price,
+    /// Step relative to the lower bound, in percent.
+    pub step_percent: Decimal,
+    /// Smallest step in percent that still clears the round trip commission.
+    pub min_step_percent: Decimal,
+    pub investment: QuoteQuantity,
+    pub investment_per_level: QuoteQuantity,
+    pub volatility: Volatility,
+}
+
+impl GridSuggestion {
+    /// The suggestion as an evenly spaced [`Grid`].
+    ///
+    /// No `GridPercent` is suggested. The step comes from the average true
+    /// range, a price distance, and the level count is checked against the
+    /// symbol filters at that even spacing. A grid spaced by percent places
+    /// its levels elsewhere and would need its own check. `step_percent` is
+    /// still given for a caller who builds one anyway.
+    pub fn grid(&self) -> Grid {
+        Grid {
+            range: (self.lower, self.upper),
+            amount: self.levels,
+            investment: self.investment,
+        }
+    }
+}
+
+pub fn volatility(candles: &[Candle]) -> Result<Volatility, String> {
+    let atr = Indicator::Atr(ATR_PERIOD)
+        .compute(candles)
+        .pop()
+        .and_then(|v| v.values.last().copied().flatten())
+        .ok_or(format!("at least {} candles are needed", ATR_PERIOD))?;
+
+    let returns: Vec<Decimal> = candles
+        .windows(2)
+        .filter(|v| !v[0].close.is_zero())
+        .map(|v| (v[1].close - v[0].close) / v[0].close)
+        .collect();
+    let mean = returns.iter().sum::<Decimal>() / Decimal::from(returns.len().max(1));
+    let variance = returns
+        .iter()
+        .map(|v| (v - mean) * (v - mean))
+        .sum::<Decimal>()
+        / Decimal::from(returns.len().max(1));
+
+    let lows: Vec<Decimal> = candles.iter().map(|v| v.low).collect();
+    let highs: Vec<Decimal> = candles.iter().map(|v| v.high).collect();
+
+    Ok(Volatility {
+        candles: candles.len(),
+        atr,
+        realized: (sqrt(variance) * Decimal::ONE_HUNDRED).round_dp(SUGGEST_SCALE),
+        support: percentile(lows, RANGE_PERCENTILE),
+        resistance: percentile(highs, Decimal::ONE - RANGE_PERCENTILE),
+    })
+}
+
+/// Spreads `investment` over the recent range of the candles, with a step
+/// wide enough for the commission and levels large enough for the filters.
+pub fn suggest(
+    candles: &[Candle],
+    price: &Price,
+    investment: &QuoteQuantity,
+    commission: &Decimal,
+    agent: &BinanceSpotTest,
+) -> Result<GridSuggestion, String> {
+    let volatility = volatility(candles)?;
+
+    // The current price always lies inside the grid
+    let lower = volatility.support.min(*price);
+    let upper = volatility.resistance.max(*price);
+    let width = upper - lower;
+    if width <= Decimal::ZERO {
+        return Err("the candles have no range to place a grid in".into());
+    }
+
+    let min_step_percent = (commission * Decimal::TWO + MIN_STEP_MARGIN) * Decimal::ONE_HUNDRED;
+    let min_step = upper * min_step_percent / Decimal::ONE_HUNDRED;
+    let step = (volatility.atr * ATR_STEP_SHARE).max(min_step);
+
+    let mut levels = (width / step)
+        .floor()
+        .to_string()
+        .parse()
+        .unwrap_or(MAX_LEVELS);
+    levels = levels.clamp(1, MAX_LEVELS);
+
+    // Fewer, larger levels until a single level passes LOT_SIZE and NOTIONAL
+    loop {
+        let per_level = investment / Decimal::from(levels);
+        let step = width / Decimal::from(levels);
+        let round_trip = agent
+            .fill_buy(&upper, &per_level)
+            .and_then(|(_, base)| agent.fill_sell(&(upper + step), &base));
+
+        match (round_trip, levels) {
+            (Ok(_), _) => break,
+            (Err(e), 1) => return Err(format!("investment too small for a single level: {}", e)),
+            (Err(_), _) => levels -= 1,
+        }
+    }
+
+    let step = width / Decimal::from(levels);
+
+    Ok(GridSuggestion {
+        price: *price,
+        lower,
+        upper,
+        levels,
+        step: step.round_dp(SUGGEST_SCALE),
+        step_percent: (step / lower * Decimal::ONE_HUNDRED).round_dp(SUGGEST_SCALE),
+        min_step_percent: min_step_percent.round_dp(SUGGEST_SCALE),
+        investment: *investment,
+        investment_per_level: (investment / Decimal::from(levels)).round_dp(SUGGEST_SCALE),
+        volatility,
+    })
+}
+
+/// The value below which `share` of the values fall, nearest rank.
+fn percentile(mut values: Vec<Decimal>, share: Decimal) -> Decimal {
+    values.sort();
+
+    let last = values.len().saturating_sub(1);
+    let rank = (Decimal::from(last) * share)
+        .round()
+        .to_string()
+        .parse()
+        .unwrap_or(0);
+
+    values.get(rank).copied().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candles(closes: &[i64]) -> Vec<Candle> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, v)| Candle {
+                open_time: i as u128,
+                open: Decimal::from(*v),
+                high: Decimal::from(*v + 1),
+                low: Decimal::from(*v - 1),
+                close: Decimal::from(*v),
+                volume: Decimal::ONE,
+                close_time: i as u128,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_volatility() {
+        let closes: Vec<i64> = (0..30).map(|i| 100 + (i % 5) * 2).collect();
+        let result = volatility(&candles(&closes)).unwrap();
+
+        assert_eq!(result.support, Decimal::from(99));
+        assert_eq!(result.resistance, Decimal::from(109));
+        assert!(result.atr > Decimal::from(2));
+        assert!(result.realized > Decimal::ZERO);
+
+        assert!(volatility(&candles(&closes[..10])).is_err());
+    }
+
+    #[test]
+    fn test_percentile() {
+        let values: Vec<Decimal> = (1..=11).map(Decimal::from).collect();
+
+        assert_eq!(
+            percentile(values.clone(), RANGE_PERCENTILE),
+            Decimal::from(2)
+        );
+        assert_eq!(percentile(values, Decimal::ONE), Decimal::from(11));
+    }
+}
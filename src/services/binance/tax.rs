@@ -0,0 +1,167 @@
+use std::error::Error;
+use std::fmt::Write;
+
+use binance::prelude::Client;
+use binance::types::SymbolInfo;
+use serde::{Deserialize, Serialize};
+
+use super::history::TradeHistory;
+use super::pnl::{CostMethod, Disposal, PnlLedger};
+
+/// Header of [`to_csv`], one column per [`Disposal`] field but the sell time.
+const CSV_HEADER: &str = "symbol,quote_asset,quantity,acquired_date,acquired_trade_id,\
+disposed_date,disposed_trade_id,cost,proceeds,fees,gain";
+
+/// Which acquisitions a disposal is matched against first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LotMethod {
+    /// Oldest acquisition first.
+    #[default]
+    Fifo,
+    /// Newest acquisition first.
+    Lifo,
+    /// Highest unit cost first.
+    Hifo,
+}
+
+impl From<LotMethod> for CostMethod {
+    fn from(method: LotMethod) -> Self {
+        match method {
+            LotMethod::Fifo => CostMethod::Fifo,
+            LotMethod::Lifo => CostMethod::Lifo,
+            LotMethod::Hifo => CostMethod::Hifo,
+        }
+    }
+}
+
+/// Matches every sell of `caller` on `normal` against its buys, lot by lot,
+/// on the same ledger as the profit and loss.
+pub async fn fetch_disposals(
+    client: &Client,
+    history: &TradeHistory,
+    caller: &str,
+    normal: &SymbolInfo,
+    method: LotMethod,
+) -> Result<Vec<Disposal>, Box<dyn Error>> {
+    let ledger = PnlLedger::load(client, history, caller, normal, method.into()).await?;
+
+    Ok(ledger.into_disposals())
+}
+
+/// Renders disposals as CSV, one row each under a header line.
+pub fn to_csv(disposals: &[Disposal]) -> String {
+    let mut csv = String::from(CSV_HEADER);
+    csv.push('\n');
+
+    let optional = |v: Option<String>| v.unwrap_or_default();
+    for v in disposals.iter() {
+        let _ = writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            v.symbol,
+            v.quote_asset,
+            v.quantity.normalize(),
+            optional(v.acquired_date.clone()),
+            optional(v.acquired_trade_id.map(|v| v.to_string())),
+            v.disposed_date,
+            v.disposed_trade_id,
+            optional(v.cost.map(|v| v.normalize().to_string())),
+            v.proceeds.normalize(),
+            v.fees.normalize(),
+            optional(v.gain.map(|v| v.normalize().to_string())),
+        );
+    }
+
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use binance::types::Trade;
+    use plot::types::Decimal;
+
+    use super::*;
+
+    fn trade(id: i64, is_buyer: bool, price: &str, qty: &str, fee: &str) -> Trade {
+        let (p, q) = (
+            Decimal::from_str(price).unwrap(),
+            Decimal::from_str(qty).unwrap(),
+        );
+
+        Trade {
+            symbol: "ETHUSDT".into(),
+            id,
+            order_id: id,
+            price: price.into(),
+            qty: qty.into(),
+            quote_qty: (p * q).to_string(),
+            commission: fee.into(),
+            commission_asset: "USDT".into(),
+            time: 1_735_689_600_000 + id as u128,
+            is_buyer,
+            is_maker: false,
+            is_best_match: true,
+        }
+    }
+
+    fn disposals(method: LotMethod) -> Vec<Disposal> {
+        let mut ledger =
+            PnlLedger::new("ETHUSDT".into(), "ETH".into(), "USDT".into(), method.into());
+        ledger.ingest(&trade(1, true, "100", "1", "0")).unwrap();
+        ledger.ingest(&trade(2, true, "300", "1", "0")).unwrap();
+        ledger.ingest(&trade(3, true, "200", "1", "0")).unwrap();
+        ledger.ingest(&trade(4, false, "250", "1", "1")).unwrap();
+
+        ledger.disposals().to_vec()
+    }
+
+    #[test]
+    fn test_lot_methods() {
+        let d = |v: &str| Decimal::from_str(v).unwrap();
+
+        let fifo = disposals(LotMethod::Fifo);
+        assert_eq!(fifo[0].acquired_trade_id, Some(1));
+        assert_eq!(fifo[0].gain, Some(d("149")));
+        assert_eq!(fifo[0].fees, d("1"));
+
+        let lifo = disposals(LotMethod::Lifo);
+        assert_eq!(lifo[0].acquired_trade_id, Some(3));
+        assert_eq!(lifo[0].gain, Some(d("49")));
+
+        let hifo = disposals(LotMethod::Hifo);
+        assert_eq!(hifo[0].acquired_trade_id, Some(2));
+        assert_eq!(hifo[0].gain, Some(d("-51")));
+    }
+
+    #[test]
+    fn test_split_and_unmatched() {
+        let d = |v: &str| Decimal::from_str(v).unwrap();
+        let mut ledger = PnlLedger::new(
+            "ETHUSDT".into(),
+            "ETH".into(),
+            "USDT".into(),
+            CostMethod::Fifo,
+        );
+        ledger.ingest(&trade(1, true, "100", "1", "0")).unwrap();
+        ledger.ingest(&trade(2, false, "200", "3", "0")).unwrap();
+
+        let rows = ledger.disposals();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].quantity, d("1"));
+        assert_eq!(rows[1].quantity, d("2"));
+        assert_eq!(rows[1].acquired_trade_id, None);
+        assert_eq!(rows[1].gain, None);
+
+        let csv = to_csv(rows);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(CSV_HEADER));
+        assert_eq!(
+            lines.next(),
+            Some("ETHUSDT,USDT,1,2025-01-01,1,2025-01-01,2,100,200,0,100")
+        );
+        assert_eq!(lines.next(), Some("ETHUSDT,USDT,2,,,2025-01-01,2,,400,0,"));
+    }
+}
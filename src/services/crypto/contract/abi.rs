@@ -39,6 +39,16 @@ mod contract_default {
             }
         }
 
+        /// Runs a read-only contract call, retrying transient provider failures.
+        pub async fn contract_view<V, E, F, Fut>(call: F) -> ContractResult<V>
+        where
+            F: Fn() -> Fut,
+            Fut: std::future::Future<Output = Result<V, E>>,
+            E: std::error::Error,
+        {
+            Self::contract_call(crate::retry::read(call).await)
+        }
+
         pub fn address(&self) -> &Address {
             &self.0
         }
@@ -80,7 +80,8 @@ mod contract_uniswap_v3_pool {
     /// Uniswap V3 Pool Read Contract Functions
     impl Contract<CaseUniswapV3Pool> {
         pub async fn slot_0(&self) -> ContractResult<Slot0> {
-            let execute = Self::contract_call(self.1.slot_0().call().await)?;
+            let execute =
+                Self::contract_view(|| async move { self.1.slot_0().call().await }).await?;
 
             let (
                 sqrt_price_x96,
@@ -106,25 +107,33 @@ mod contract_uniswap_v3_pool {
         }
 
         pub async fn fee(&self) -> ContractResult<Uint24> {
-            let result = Self::contract_call(self.case().fee().call().await)?;
+            let result =
+                Self::contract_view(|| async move { self.case().fee().call().await }).await?;
 
             Ok(result)
         }
 
         pub async fn fee_growth_global_0x128(&self) -> ContractResult<Uint256> {
-            let result = Self::contract_call(self.case().fee_growth_global_0x128().call().await)?;
+            let result = Self::contract_view(|| async move {
+                self.case().fee_growth_global_0x128().call().await
+            })
+            .await?;
 
             Ok(result.to_string())
         }
 
         pub async fn fee_growth_global_1x128(&self) -> ContractResult<Uint256> {
-            let result = Self::contract_call(self.case().fee_growth_global_1x128().call().await)?;
+            let result = Self::contract_view(|| async move {
+                self.case().fee_growth_global_1x128().call().await
+            })
+            .await?;
 
             Ok(result.to_string())
         }
 
         pub async fn liquidity(&self) -> ContractResult<Uint128> {
-            let result = Self::contract_call(self.case().liquidity().call().await)?;
+            let result =
+                Self::contract_view(|| async move { self.case().liquidity().call().await }).await?;
 
             Ok(result)
         }
@@ -133,7 +142,8 @@ mod contract_uniswap_v3_pool {
             use ethers::types::H160;
             use ethers::utils::hex::ToHexExt;
 
-            let result: H160 = Self::contract_call(self.case().factory().call().await)?;
+            let result: H160 =
+                Self::contract_view(|| async move { self.case().factory().call().await }).await?;
 
             Ok(result.encode_hex_with_prefix())
         }
@@ -142,7 +152,8 @@ mod contract_uniswap_v3_pool {
             use ethers::types::H160;
             use ethers::utils::hex::ToHexExt;
 
-            let result: H160 = Self::contract_call(self.case().token_0().call().await)?;
+            let result: H160 =
+                Self::contract_view(|| async move { self.case().token_0().call().await }).await?;
 
             Ok(result.encode_hex_with_prefix())
         }
@@ -151,7 +162,8 @@ mod contract_uniswap_v3_pool {
             use ethers::types::H160;
             use ethers::utils::hex::ToHexExt;
 
-            let result: H160 = Self::contract_call(self.case().token_1().call().await)?;
+            let result: H160 =
+                Self::contract_view(|| async move { self.case().token_1().call().await }).await?;
 
             Ok(result.encode_hex_with_prefix())
         }
@@ -177,8 +189,10 @@ mod contract_uniswap_v3_factory {
             let address_0 = Self::to_address(address_0)?;
             let address_1 = Self::to_address(address_1)?;
 
-            let result: H160 =
-                Self::contract_call(self.1.get_pool(address_0, address_1, fee).call().await)?;
+            let result: H160 = Self::contract_view(|| async move {
+                self.1.get_pool(address_0, address_1, fee).call().await
+            })
+            .await?;
 
             Ok(result.encode_hex_with_prefix())
         }
@@ -7,3 +7,33 @@ pub fn timestamp() -> Duration {
 
     SystemTime::now().duration_since(earlier).expect(msg)
 }
+
+/// The UTC calendar date of a Unix timestamp in milliseconds, as `YYYY-MM-DD`.
+pub fn date(millis: u128) -> String {
+    // Days to civil date, from Howard Hinnant's chrono-compatible algorithms
+    let days = (millis / 86_400_000) as i64 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(951_782_400_000), "2000-02-29");
+        assert_eq!(date(1_735_689_599_999), "2024-12-31");
+        assert_eq!(date(1_735_689_600_000), "2025-01-01");
+    }
+}
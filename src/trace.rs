@@ -0,0 +1,98 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::time::timestamp;
+
+/// Longest identifier accepted from an incoming `X-Request-Id` header.
+const MAX_LENGTH: usize = 64;
+
+/// Binance limits `newClientOrderId` to 36 characters.
+const CLIENT_ORDER_ID_LENGTH: usize = 36;
+
+tokio::task_local! {
+    static REQUEST: Request;
+}
+
+struct Request {
+    id: String,
+    orders: AtomicU32,
+}
+
+/// Run `future` with `id` as the current request id.
+pub async fn scope<F: Future>(id: String, future: F) -> F::Output {
+    let request = Request {
+        id,
+        orders: AtomicU32::new(0),
+    };
+
+    REQUEST.scope(request, future).await
+}
+
+/// The id of the request being served, if any.
+pub fn current() -> Option<String> {
+    REQUEST.try_with(|r| r.id.clone()).ok()
+}
+
+/// Keep a caller supplied id when it is safe to log and to forward to the
+/// exchange, otherwise generate a new one.
+pub fn accept_or_generate(incoming: Option<&str>) -> String {
+    match incoming {
+        Some(v) if is_valid(v) => v.to_string(),
+        _ => generate(),
+    }
+}
+
+pub fn generate() -> String {
+    static SEQUENCE: AtomicU32 = AtomicU32::new(0);
+
+    let sequence = SEQUENCE.fetch_add(1, Ordering::Relaxed) & 0xffff;
+
+    format!("{:x}{:04x}", timestamp().as_micros(), sequence)
+}
+
+/// A Binance `newClientOrderId` derived from the current request id, unique
+/// for every order placed while serving the request.
+pub fn client_order_id() -> Option<String> {
+    REQUEST
+        .try_with(|r| order_id(&r.id, r.orders.fetch_add(1, Ordering::Relaxed)))
+        .ok()
+}
+
+/// `request_id` cut short enough for the `-sequence` suffix to fit the
+/// client order id limit, however long the sequence grows.
+fn order_id(request_id: &str, sequence: u32) -> String {
+    let suffix = format!("-{}", sequence);
+    let prefix: String = request_id
+        .chars()
+        .take(CLIENT_ORDER_ID_LENGTH - suffix.len())
+        .collect();
+
+    format!("{}{}", prefix, suffix)
+}
+
+fn is_valid(id: &str) -> bool {
+    // Binance accepts `^[\.A-Z\:/a-z0-9_-]{1,36}$` as client order id
+    !id.is_empty()
+        && id.len() <= MAX_LENGTH
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | ':' | '/' | '_' | '-'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_id() {
+        let request_id = "a".repeat(MAX_LENGTH);
+
+        assert_eq!(order_id("abc", 0), "abc-0");
+        for sequence in [0, 99_999, 100_000, u32::MAX] {
+            let id = order_id(&request_id, sequence);
+            assert_eq!(id.len(), CLIENT_ORDER_ID_LENGTH);
+            assert!(id.ends_with(&format!("-{}", sequence)));
+            assert!(is_valid(&id));
+        }
+    }
+}
@@ -0,0 +1,215 @@
+#![cfg(feature = "service-binance")]
+
+mod common;
+
+use std::str::FromStr;
+
+use axum::http::{Method, StatusCode};
+use plot::types::Decimal;
+use serde_json::json;
+
+use common::{admin, app, credentials, get, mock, post, with};
+
+/// A decimal of a reply, whether it is written as text or a number.
+fn decimal(value: &serde_json::Value) -> Decimal {
+    let text = match value.as_str() {
+        Some(v) => v.to_string(),
+        None => value.to_string(),
+    };
+
+    Decimal::from_str(&text).unwrap()
+}
+
+#[tokio::test]
+async fn test_futures_normal_and_funding() {
+    mock().add_futures_symbol("FUNDUSDT", "FUND", "USDT", "100");
+    mock().add_funding_rates(
+        "FUNDUSDT",
+        &[
+            (1700000000000, "0.00010000"),
+            (1700028800000, "0.00030000"),
+            (1700057600000, "-0.00010000"),
+        ],
+    );
+    let app = app().await;
+
+    let reply = get(&app, "/v1/binance/futures/normal?symbol=FUNDUSDT").await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(reply.body["data"]["contractType"], "PERPETUAL");
+    assert_eq!(reply.body["data"]["quantityPrecision"], 3);
+
+    let reply = get(&app, "/v1/binance/futures/normal?symbol=NONEUSDT").await;
+    assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+
+    let reply = get(&app, "/v1/binance/futures/funding?symbol=FUNDUSDT").await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(reply.body["data"]["rates"].as_array().unwrap().len(), 3);
+    assert_eq!(
+        decimal(&reply.body["data"]["total_rate"]),
+        Decimal::new(3, 4)
+    );
+
+    let uri = "/v1/binance/futures/funding?symbol=FUNDUSDT&start_time=1700028800000";
+    let reply = get(&app, uri).await;
+    assert_eq!(reply.body["data"]["rates"].as_array().unwrap().len(), 2);
+
+    let uri = "/v1/binance/futures/funding?symbol=FUNDUSDT&limit=5000";
+    let reply = get(&app, uri).await;
+    assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_futures_leverage_and_margin_type() {
+    mock().add_futures_symbol("LEVUSDT", "LEV", "USDT", "100");
+    let app = app().await;
+
+    let payload = with(
+        credentials("key-lev"),
+        json!({ "symbol": "LEVUSDT", "leverage": 10 }),
+    );
+    let reply = post(&app, "/v1/binance/futures/account/leverage", payload).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(reply.body["data"]["leverage"], 10);
+
+    let payload = with(
+        credentials("key-lev"),
+        json!({ "symbol": "LEVUSDT", "leverage": 126 }),
+    );
+    let reply = post(&app, "/v1/binance/futures/account/leverage", payload).await;
+    assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+
+    let payload = with(
+        credentials("key-lev"),
+        json!({ "symbol": "LEVUSDT", "margin_type": "ISOLATED" }),
+    );
+    let reply = post(&app, "/v1/binance/futures/account/margin", payload.clone()).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(reply.body["data"]["changed"], true);
+
+    // Asking for the current margin type again is not an error
+    let reply = post(&app, "/v1/binance/futures/account/margin", payload).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(reply.body["data"]["changed"], false);
+
+    let payload = with(credentials("key-lev"), json!({ "symbol": "LEVUSDT" }));
+    let reply = post(&app, "/v1/binance/futures/account/position", payload).await;
+    assert_eq!(
+        decimal(&reply.body["data"][0]["leverage"]),
+        Decimal::from(10)
+    );
+    assert_eq!(reply.body["data"][0]["marginType"], "isolated");
+}
+
+#[tokio::test]
+async fn test_futures_short_hedge() {
+    mock().add_futures_symbol("HEDGEUSDT", "HEDGE", "USDT", "1000");
+    let app = app().await;
+
+    let sell = with(
+        credentials("key-hedge"),
+        json!({ "symbol": "HEDGEUSDT", "base_quantity": "0.2509" }),
+    );
+    let reply = post(&app, "/v1/binance/futures/order/sell", sell).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(
+        decimal(&reply.body["data"]["executedQty"]),
+        Decimal::new(25, 2)
+    );
+    assert_eq!(
+        decimal(&mock().orders("HEDGEUSDT")[0]["quantity"]),
+        Decimal::new(25, 2)
+    );
+
+    // 50 quote is short of the minimum notional, unless closing the short
+    let buy = with(
+        credentials("key-hedge"),
+        json!({ "symbol": "HEDGEUSDT", "quote_quantity": "50" }),
+    );
+    let reply = post(&app, "/v1/binance/futures/order/buy", buy.clone()).await;
+    assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+    assert!(reply.body["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("FILTER MIN_NOTIONAL"));
+
+    let buy = with(buy, json!({ "reduce_only": true }));
+    let reply = post(
+        &app,
+        "/v1/binance/futures/order/buy",
+        with(buy.clone(), json!({ "dry_run": true })),
+    )
+    .await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(decimal(&reply.body["data"]["quantity"]), Decimal::new(5, 2));
+    assert_eq!(mock().orders("HEDGEUSDT").len(), 1);
+
+    let reply = post(&app, "/v1/binance/futures/order/buy", buy).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(mock().orders("HEDGEUSDT")[1]["reduceOnly"], "true");
+
+    let payload = credentials("key-hedge");
+    let reply = post(&app, "/v1/binance/futures/account/position", payload).await;
+    let positions = reply.body["data"].as_array().unwrap();
+    assert_eq!(positions.len(), 1);
+    assert_eq!(positions[0]["symbol"], "HEDGEUSDT");
+    assert_eq!(decimal(&positions[0]["positionAmt"]), Decimal::new(-2, 1));
+    assert_eq!(decimal(&positions[0]["entryPrice"]), Decimal::from(1000));
+}
+
+#[tokio::test]
+async fn test_futures_order_refused_while_halted() {
+    mock().add_futures_symbol("FHALTUSDT", "FHALT", "USDT", "1000");
+    let app = app().await;
+
+    let halt = json!({ "symbol": "FHALTUSDT", "reason": "funding spike" });
+    let reply = admin(&app, Method::POST, "/v1/binance/spot/halt", Some(halt)).await;
+    assert_eq!(reply.status, StatusCode::OK);
+
+    let sell = with(
+        credentials("key-fhalt"),
+        json!({ "symbol": "FHALTUSDT", "base_quantity": "1" }),
+    );
+    let reply = post(&app, "/v1/binance/futures/order/sell", sell).await;
+    assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+    assert!(reply.body["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("RISK TRADING_HALTED"));
+    assert!(mock().orders("FHALTUSDT").is_empty());
+}
+
+#[tokio::test]
+async fn test_futures_grid_order_refused_while_halted() {
+    let closes: Vec<String> = (0..40)
+        .map(|i| format!("{}.00000000", 90 + (i % 11) * 2))
+        .collect();
+    let closes: Vec<&str> = closes.iter().map(String::as_str).collect();
+    mock().add_symbol("FGRIDUSDT", "FGRID", "USDT", "100.00000000");
+    mock().add_futures_symbol("FGRIDUSDT", "FGRID", "USDT", "100.00000000");
+    mock().set_closes("FGRIDUSDT", &closes);
+    let app = app().await;
+
+    // The spot grid of the same pair stands in for a futures grid
+    let payload = json!({ "symbol": "FGRIDUSDT", "investment": "1000" });
+    let reply = post(&app, "/v1/binance/spot/plot/suggest", payload).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    let positions = reply.body["data"]["positions"].clone();
+
+    let halt = json!({ "symbol": "FGRIDUSDT", "reason": "grid test" });
+    let reply = admin(&app, Method::POST, "/v1/binance/spot/halt", Some(halt)).await;
+    assert_eq!(reply.status, StatusCode::OK);
+
+    mock().set_price("FGRIDUSDT", "93.00000000");
+    let payload = with(
+        credentials("key-fgrid"),
+        json!({ "symbol": "FGRIDUSDT", "positions": positions }),
+    );
+    let reply = post(&app, "/v1/binance/futures/order", payload).await;
+    assert_eq!(reply.status, StatusCode::FORBIDDEN);
+    assert!(reply.body["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("RISK TRADING_HALTED"));
+    assert_eq!(reply.body["data"]["positions"], positions);
+    assert!(mock().orders("FGRIDUSDT").is_empty());
+}
@@ -0,0 +1,1168 @@
+#![cfg(feature = "service-binance")]
+
+mod common;
+
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+
+use common::{
+    admin, app, credentials, get, mock, post, post_with_headers, post_with_request_id, with,
+};
+
+/// Polls an execution until it stops running.
+async fn settled(app: &axum::Router, status: &serde_json::Value) -> common::Reply {
+    let uri = "/v1/binance/spot/order/execution/status";
+    let mut reply = post(app, uri, status.clone()).await;
+    for _ in 0..50 {
+        if reply.body["data"]["status"] != "running" {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        reply = post(app, uri, status.clone()).await;
+    }
+
+    reply
+}
+
+#[tokio::test]
+async fn test_price() {
+    mock().add_symbol("PRICEUSDT", "PRICE", "USDT", "12.34000000");
+    let app = app().await;
+
+    let reply = get(&app, "/v1/binance/spot/price?symbol=PRICEUSDT").await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(reply.body["data"][0]["price"], "12.34000000");
+    assert!(reply.body["request_id"].is_string());
+
+    mock().set_price("PRICEUSDT", "12.50000000");
+    let reply = get(&app, "/v1/binance/spot/price?symbol=PRICEUSDT").await;
+    assert_eq!(reply.body["data"][0]["price"], "12.50000000");
+}
+
+#[tokio::test]
+async fn test_normal() {
+    mock().add_symbol("NORMALUSDT", "NORMAL", "USDT", "1.00000000");
+    let app = app().await;
+
+    let reply = get(&app, "/v1/binance/spot/normal?symbol=NORMALUSDT").await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(reply.body["data"]["symbols"][0]["symbol"], "NORMALUSDT");
+    assert_eq!(reply.body["data"]["symbols"][0]["baseAsset"], "NORMAL");
+}
+
+#[tokio::test]
+async fn test_batch_price_and_normal() {
+    mock().add_symbol("BATCHAUSDT", "BATCHA", "USDT", "1.00000000");
+    mock().add_symbol("BATCHBUSDT", "BATCHB", "USDT", "2.00000000");
+    mock().add_symbol("BATCHABTC", "BATCHA", "BTC", "0.00001000");
+    mock().add_symbol("BATCHBBNB", "BATCHB", "BNB", "0.00200000");
+    mock().add_symbol("BATCHATUSD", "BATCHA", "TUSD", "1.00000000");
+    let app = app().await;
+
+    let reply = get(&app, "/v1/binance/spot/price?symbols=BATCHBUSDT,BATCHABTC").await;
+    assert_eq!(reply.status, StatusCode::OK);
+    let prices = reply.body["data"].as_array().unwrap();
+    assert_eq!(prices.len(), 2);
+    assert_eq!(prices[0]["symbol"], "BATCHBUSDT");
+    assert_eq!(prices[1]["price"], "0.00001000");
+
+    let reply = get(&app, "/v1/binance/spot/price?symbols=BATCHBBNB&quote=USDT").await;
+    let symbols: Vec<&str> = reply.body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v["symbol"].as_str().unwrap())
+        .collect();
+    assert_eq!(symbols[0], "BATCHBBNB");
+    assert!(symbols.contains(&"BATCHAUSDT"));
+    assert!(symbols.iter().skip(1).all(|v| v.ends_with("USDT")));
+
+    // Selected by the quote asset, not by how the symbol ends
+    let reply = get(&app, "/v1/binance/spot/price?quote=USD").await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert!(!reply.body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|v| v["symbol"] == "BATCHATUSD"));
+
+    let reply = get(&app, "/v1/binance/spot/price?symbols=BATCHCUSDT").await;
+    assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+
+    let reply = get(
+        &app,
+        "/v1/binance/spot/normal?symbols=BATCHAUSDT,BATCHABTC,BATCHBBNB",
+    )
+    .await;
+    assert_eq!(reply.status, StatusCode::OK);
+    let symbols: Vec<&str> = reply.body["data"]["symbols"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v["symbol"].as_str().unwrap())
+        .collect();
+    assert_eq!(symbols, vec!["BATCHAUSDT", "BATCHABTC", "BATCHBBNB"]);
+
+    // Every pair of the quote, out of a single exchange info request
+    let reply = get(&app, "/v1/binance/spot/normal?symbols=BATCHBBNB&quote=USDT").await;
+    assert_eq!(reply.status, StatusCode::OK);
+    let normals = reply.body["data"]["symbols"].as_array().unwrap();
+    assert_eq!(normals[0]["symbol"], "BATCHBBNB");
+    assert!(normals.iter().any(|v| v["symbol"] == "BATCHBUSDT"));
+    assert!(normals.iter().skip(1).all(|v| v["quoteAsset"] == "USDT"));
+
+    let reply = get(&app, "/v1/binance/spot/normal").await;
+    assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_filter_check() {
+    use std::str::FromStr;
+
+    use plot::types::Decimal;
+
+    let dec = |v: &serde_json::Value| Decimal::from_str(v.as_str().unwrap()).unwrap();
+
+    mock().add_symbol("CHECKUSDT", "CHECK", "USDT", "100.00000000");
+    let app = app().await;
+
+    let reply = get(
+        &app,
+        "/v1/binance/spot/filter/check?symbol=CHECKUSDT&base_quantity=0.04567",
+    )
+    .await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(dec(&reply.body["data"]["price"]), Decimal::ONE_HUNDRED);
+    assert_eq!(
+        dec(&reply.body["data"]["corrected_quantity"]),
+        Decimal::new(456, 4)
+    );
+    assert_eq!(dec(&reply.body["data"]["min_quantity"]), Decimal::new(5, 2));
+    assert_eq!(reply.body["data"]["passed"], false);
+    let notional = &reply.body["data"]["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|v| v["filter"] == "NOTIONAL")
+        .unwrap();
+    assert_eq!(notional["passed"], false);
+
+    let reply = get(
+        &app,
+        "/v1/binance/spot/filter/check?symbol=CHECKUSDT&price=200&quote_quantity=10",
+    )
+    .await;
+    assert_eq!(reply.body["data"]["kind"], "quote");
+    assert_eq!(reply.body["data"]["passed"], true);
+
+    let reply = get(&app, "/v1/binance/spot/filter/check?symbol=CHECKUSDT").await;
+    assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_deprecated_path_still_served() {
+    mock().add_symbol("LEGACYUSDT", "LEGACY", "USDT", "2.00000000");
+    let app = app().await;
+
+    let reply = get(&app, "/binance/spot/price?symbol=LEGACYUSDT").await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(reply.body["data"][0]["price"], "2.00000000");
+}
+
+#[tokio::test]
+async fn test_buy_forwards_request_id_as_client_order_id() {
+    mock().add_symbol("BUYUSDT", "BUY", "USDT", "100.00000000");
+    mock().set_balance("key-buy", "USDT", "25.00000000");
+    mock().script_fill(
+        "BUYUSDT",
+        &[
+            ("100.00000000", "0.05000000"),
+            ("100.10000000", "0.04990000"),
+        ],
+    );
+    let app = app().await;
+
+    let payload = with(
+        credentials("key-buy"),
+        json!({ "symbol": "BUYUSDT", "quote_quantity": "10" }),
+    );
+    let reply =
+        post_with_request_id(&app, "/v1/binance/spot/order/buy", payload, "trace-buy-1").await;
+
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(reply.request_id.as_deref(), Some("trace-buy-1"));
+    assert_eq!(reply.body["request_id"], "trace-buy-1");
+    assert_eq!(reply.body["data"]["fills"].as_array().unwrap().len(), 2);
+
+    let orders = mock().orders("BUYUSDT");
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0]["side"], "BUY");
+    assert_eq!(orders[0]["quoteOrderQty"], "10");
+    assert_eq!(orders[0]["newClientOrderId"], "trace-buy-1-0");
+}
+
+#[tokio::test]
+async fn test_audit_export_requires_admin_and_pages() {
+    mock().add_symbol("AUDITUSDT", "AUDIT", "USDT", "100.00000000");
+    mock().set_balance("key-audit", "USDT", "100.00000000");
+    let app = app().await;
+
+    let buy = with(
+        credentials("key-audit"),
+        json!({ "symbol": "AUDITUSDT", "quote_quantity": "10" }),
+    );
+    for _ in 0..2 {
+        let reply = post(&app, "/v1/binance/spot/order/buy", buy.clone()).await;
+        assert_eq!(reply.status, StatusCode::OK);
+    }
+
+    let reply = get(&app, "/audit").await;
+    assert_eq!(reply.status, StatusCode::FORBIDDEN);
+    assert!(reply.body["data"].is_null());
+
+    let reply = admin(&app, Method::GET, "/audit?limit=1", None).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(reply.body["data"]["records"].as_array().unwrap().len(), 1);
+    assert_eq!(reply.body["data"]["verification"]["verified"], true);
+    assert_eq!(reply.body["data"]["next"], 1);
+
+    let reply = admin(&app, Method::GET, "/audit?from=1&limit=1", None).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(reply.body["data"]["records"][0]["sequence"], 1);
+    assert_eq!(reply.body["data"]["verification"]["verified"], true);
+}
+
+#[tokio::test]
+async fn test_buy_sends_corrected_quantity() {
+    mock().add_symbol("CORRECTUSDT", "CORRECT", "USDT", "100.00000000");
+    mock().set_balance("key-correct", "USDT", "100.00000000");
+    let app = app().await;
+
+    let payload = with(
+        credentials("key-correct"),
+        json!({ "symbol": "CORRECTUSDT", "quote_quantity": "10.123456789" }),
+    );
+    let reply = post(&app, "/v1/binance/spot/order/buy", payload).await;
+    assert_eq!(reply.status, StatusCode::OK);
+
+    // Truncated to the quote asset precision, as a dry run reports it
+    let orders = mock().orders("CORRECTUSDT");
+    assert_eq!(orders[0]["quoteOrderQty"], "10.12345678");
+}
+
+#[tokio::test]
+async fn test_sell_exchange_error() {
+    mock().add_symbol("REJECTUSDT", "REJECT", "USDT", "100.00000000");
+    mock().script_error(
+        "REJECTUSDT",
+        400,
+        -2010,
+        "Account has insufficient balance for requested action.",
+    );
+    mock().set_balance("key-reject", "REJECT", "5.00000000");
+    let app = app().await;
+
+    let payload = with(
+        credentials("key-reject"),
+        json!({ "symbol": "REJECTUSDT", "base_quantity": "1" }),
+    );
+    let reply = post(&app, "/v1/binance/spot/order/sell", payload).await;
+
+    assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+    assert_eq!(reply.body["ok"], false);
+    assert!(reply.body["message"]
+        .as_str()
+        .unwrap()
+        .contains("insufficient balance"));
+    assert!(mock().orders("REJECTUSDT").is_empty());
+}
+
+#[tokio::test]
+async fn test_buy_rejected_without_balance() {
+    mock().add_symbol("POORUSDT", "POOR", "USDT", "100.00000000");
+    mock().set_balance("key-poor", "USDT", "5.00000000");
+    let app = app().await;
+
+    let payload = with(
+        credentials("key-poor"),
+        json!({ "symbol": "POORUSDT", "quote_quantity": "10" }),
+    );
+    let reply = post(&app, "/v1/binance/spot/order/buy", payload).await;
+
+    assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+    assert!(reply.body["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("RISK INSUFFICIENT_BALANCE"));
+    assert!(mock().orders("POORUSDT").is_empty());
+}
+
+#[tokio::test]
+async fn test_sell_dry_run_does_not_reach_exchange() {
+    mock().add_symbol("DRYUSDT", "DRY", "USDT", "100.00000000");
+    mock().set_balance("key-dry", "DRY", "2.00000000");
+    mock().set_balance("key-dry", "USDT", "0.00000000");
+    let app = app().await;
+
+    let payload = with(
+        credentials("key-dry"),
+        json!({ "symbol": "DRYUSDT", "base_quantity": "0.50005", "dry_run": true }),
+    );
+    let reply = post(&app, "/v1/binance/spot/order/sell", payload).await;
+
+    assert_eq!(reply.status, StatusCode::OK);
+    let report = &reply.body["data"];
+    assert_eq!(report["orders"][0]["corrected_quantity"], "0.50000000");
+    assert_eq!(report["balance_before"]["base"], "2.00000000");
+    assert_eq!(report["balance_after"]["base"], "1.50000000");
+    assert!(mock().orders("DRYUSDT").is_empty());
+}
+
+#[tokio::test]
+async fn test_klines_with_indicators() {
+    mock().set_closes(
+        "KLINEUSDT",
+        &["1.00000000", "2.00000000", "3.00000000", "4.00000000"],
+    );
+    let app = app().await;
+
+    let reply = get(
+        &app,
+        "/v1/binance/spot/klines?symbol=KLINEUSDT&interval=1m&indicators=sma:3,rsi:2",
+    )
+    .await;
+    assert_eq!(reply.status, StatusCode::OK);
+
+    let data = &reply.body["data"];
+    assert_eq!(data["candles"].as_array().unwrap().len(), 4);
+    assert_eq!(data["candles"][3]["close"], "4.00000000");
+    assert_eq!(data["indicators"][0]["name"], "sma:3");
+    assert_eq!(data["indicators"][0]["values"][1], json!(null));
+    assert_eq!(data["indicators"][1]["name"], "rsi:2");
+
+    let reply = get(
+        &app,
+        "/v1/binance/spot/klines?symbol=KLINEUSDT&interval=1m&indicators=macd:12",
+    )
+    .await;
+    assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_grid_suggestion() {
+    let closes: Vec<String> = (0..40)
+        .map(|i| format!("{}.00000000", 90 + (i % 11) * 2))
+        .collect();
+    let closes: Vec<&str> = closes.iter().map(String::as_str).collect();
+    mock().add_symbol("SUGGESTUSDT", "SUGGEST", "USDT", "100.00000000");
+    mock().set_closes("SUGGESTUSDT", &closes);
+    let app = app().await;
+
+    let payload = json!({ "symbol": "SUGGESTUSDT", "investment": "1000" });
+    let reply = post(&app, "/v1/binance/spot/plot/suggest", payload).await;
+    assert_eq!(reply.status, StatusCode::OK);
+
+    let suggestion = &reply.body["data"]["suggestion"];
+    assert_eq!(suggestion["lower"], "92.00000000");
+    assert_eq!(suggestion["upper"], "108.00000000");
+    assert!(suggestion["levels"].as_u64().unwrap() >= 1);
+
+    // Too little to clear the 5 USDT minimum notional
+    let payload = json!({ "symbol": "SUGGESTUSDT", "investment": "1" });
+    let reply = post(&app, "/v1/binance/spot/plot/suggest", payload).await;
+    assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_depth_and_max_slippage() {
+    use std::str::FromStr;
+
+    use plot::types::Decimal;
+
+    mock().add_symbol("DEPTHUSDT", "DEPTH", "USDT", "100.00000000");
+    mock().set_balance("key-depth", "USDT", "1000.00000000");
+    mock().set_depth(
+        "DEPTHUSDT",
+        &[("99.00000000", "1.00000000")],
+        &[
+            ("100.00000000", "1.00000000"),
+            ("110.00000000", "5.00000000"),
+        ],
+    );
+    let app = app().await;
+
+    let reply = get(
+        &app,
+        "/v1/binance/spot/depth?symbol=DEPTHUSDT&side=BUY&quantity=210",
+    )
+    .await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(
+        reply.body["data"]["book"]["asks"][1]["price"],
+        "110.00000000"
+    );
+    let impact = reply.body["data"]["estimate"]["impact"].as_str().unwrap();
+    assert_eq!(Decimal::from_str(impact).unwrap(), Decimal::from(5));
+    assert_eq!(reply.body["data"]["estimate"]["filled"], true);
+
+    let payload = with(
+        credentials("key-depth"),
+        json!({ "symbol": "DEPTHUSDT", "quote_quantity": "210", "max_slippage": "1" }),
+    );
+    let reply = post(&app, "/v1/binance/spot/order/buy", payload).await;
+    assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+    assert!(reply.body["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("RISK MAX_SLIPPAGE"));
+    assert!(mock().orders("DEPTHUSDT").is_empty());
+
+    let payload = with(
+        credentials("key-depth"),
+        json!({ "symbol": "DEPTHUSDT", "quote_quantity": "50", "max_slippage": "1" }),
+    );
+    let reply = post(&app, "/v1/binance/spot/order/buy", payload).await;
+    assert_eq!(reply.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_order_info_and_trades() {
+    mock().add_symbol("TRADEUSDT", "TRADE", "USDT", "10.00000000");
+    mock().set_balance("key-trade", "TRADE", "3.00000000");
+    let app = app().await;
+
+    let payload = with(
+        credentials("key-trade"),
+        json!({ "symbol": "TRADEUSDT", "base_quantity": "3" }),
+    );
+    let reply = post(&app, "/v1/binance/spot/order/sell", payload).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    let order_id = reply.body["data"]["orderId"].as_i64().unwrap();
+
+    let payload = with(
+        credentials("key-trade"),
+        json!({ "symbol": "TRADEUSDT", "order_id": order_id }),
+    );
+    let reply = post(&app, "/v1/binance/spot/order/info", payload).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(reply.body["data"]["order"]["orderId"], order_id);
+    assert_eq!(reply.body["data"]["trades"][0]["qty"], "3");
+
+    let payload = with(credentials("key-trade"), json!({ "symbol": "TRADEUSDT" }));
+    let reply = post(&app, "/v1/binance/spot/order/trades", payload).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(reply.body["data"].as_array().unwrap().len(), 1);
+    assert_eq!(reply.body["data"][0]["isBuyer"], false);
+}
+
+#[tokio::test]
+async fn test_trade_history_sync() {
+    mock().add_symbol("SYNCUSDT", "SYNC", "USDT", "10.00000000");
+    mock().set_balance("key-sync", "USDT", "1000.00000000");
+    let app = app().await;
+
+    let buy = with(
+        credentials("key-sync"),
+        json!({ "symbol": "SYNCUSDT", "quote_quantity": "10" }),
+    );
+    for _ in 0..2 {
+        let reply = post(&app, "/v1/binance/spot/order/buy", buy.clone()).await;
+        assert_eq!(reply.status, StatusCode::OK);
+    }
+
+    let payload = with(credentials("key-sync"), json!({ "symbols": ["SYNCUSDT"] }));
+    let reply = post(&app, "/v1/binance/spot/order/sync", payload.clone()).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(reply.body["data"][0]["added"], 2);
+
+    let reply = post(&app, "/v1/binance/spot/order/sync", payload).await;
+    assert_eq!(reply.body["data"][0]["added"], 0);
+    assert_eq!(reply.body["data"][0]["total"], 2);
+    let last_id = reply.body["data"][0]["last_id"].as_i64().unwrap();
+
+    let reply = post(&app, "/v1/binance/spot/order/buy", buy).await;
+    assert_eq!(reply.status, StatusCode::OK);
+
+    let payload = with(
+        credentials("key-sync"),
+        json!({ "symbol": "SYNCUSDT", "cached_only": true }),
+    );
+    let reply = post(&app, "/v1/binance/spot/order/history", payload).await;
+    assert_eq!(reply.body["data"].as_array().unwrap().len(), 2);
+
+    let payload = with(
+        credentials("key-sync"),
+        json!({ "symbol": "SYNCUSDT", "from_id": last_id + 1 }),
+    );
+    let reply = post(&app, "/v1/binance/spot/order/history", payload).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(reply.body["data"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_account_asset_and_commission() {
+    mock().add_symbol("FEEUSDT", "FEE", "USDT", "1.00000000");
+    mock().set_balance("key-account", "FEE", "42.00000000");
+    let app = app().await;
+
+    let reply = post(
+        &app,
+        "/v1/binance/spot/account/asset",
+        credentials("key-account"),
+    )
+    .await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(reply.body["data"][0]["asset"], "FEE");
+    assert_eq!(reply.body["data"][0]["free"], "42.00000000");
+
+    let payload = with(credentials("key-account"), json!({ "symbol": "FEEUSDT" }));
+    let reply = post(&app, "/v1/binance/spot/account/commission", payload).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(
+        reply.body["data"]["standardCommission"]["taker"],
+        "0.00100000"
+    );
+}
+
+#[tokio::test]
+async fn test_account_portfolio() {
+    mock().set_price("PFBTC", "0.00100000");
+    mock().set_price("BTCUSDT", "50000.00000000");
+    mock().set_balance("key-portfolio", "PF", "1000.00000000");
+    mock().set_balance("key-portfolio", "USDT", "50000.00000000");
+    mock().set_balance("key-portfolio", "NOPAIR", "7.00000000");
+    let app = app().await;
+
+    let reply = post(
+        &app,
+        "/v1/binance/spot/account/portfolio",
+        credentials("key-portfolio"),
+    )
+    .await;
+    assert_eq!(reply.status, StatusCode::OK);
+
+    let data = &reply.body["data"];
+    assert_eq!(data["quote"], "USDT");
+    assert_eq!(data["equity"], "100000.00000000");
+    assert_eq!(data["unpriced"], json!(["NOPAIR"]));
+    assert_eq!(data["holdings"][0]["asset"], "PF");
+    assert_eq!(data["holdings"][0]["route"], json!(["PFBTC", "BTCUSDT"]));
+    assert_eq!(data["holdings"][0]["allocation"], "50");
+}
+
+#[tokio::test]
+async fn test_account_pnl() {
+    use std::str::FromStr;
+
+    use plot::types::Decimal;
+
+    mock().add_symbol("PNLUSDT", "PNL", "USDT", "10.00000000");
+    mock().set_balance("key-pnl", "USDT", "1000.00000000");
+    mock().set_balance("key-pnl", "PNL", "2.00000000");
+    mock().script_fill("PNLUSDT", &[("10.00000000", "2.00000000")]);
+    mock().script_fill("PNLUSDT", &[("15.00000000", "1.00000000")]);
+    let app = app().await;
+
+    let buy = with(
+        credentials("key-pnl"),
+        json!({ "symbol": "PNLUSDT", "quote_quantity": "20" }),
+    );
+    assert_eq!(
+        post(&app, "/v1/binance/spot/order/buy", buy).await.status,
+        StatusCode::OK
+    );
+
+    let sell = with(
+        credentials("key-pnl"),
+        json!({ "symbol": "PNLUSDT", "base_quantity": "1" }),
+    );
+    assert_eq!(
+        post(&app, "/v1/binance/spot/order/sell", sell).await.status,
+        StatusCode::OK
+    );
+
+    mock().set_price("PNLUSDT", "12.00000000");
+    let payload = with(credentials("key-pnl"), json!({ "symbol": "PNLUSDT" }));
+    let reply = post(&app, "/v1/binance/spot/account/pnl", payload).await;
+    assert_eq!(reply.status, StatusCode::OK);
+
+    let data = &reply.body["data"];
+    let d = |v: &serde_json::Value| Decimal::from_str(v.as_str().unwrap()).unwrap();
+    assert_eq!(data["method"], "fifo");
+    assert_eq!(data["trades"], 2);
+    assert_eq!(d(&data["position"]), Decimal::from(1));
+    assert_eq!(d(&data["realized"]), Decimal::from(5));
+    assert_eq!(d(&data["unrealized"]), Decimal::from(2));
+    assert_eq!(data["daily"][0]["date"], "2023-11-14");
+}
+
+#[tokio::test]
+async fn test_account_tax_lots() {
+    mock().add_symbol("TAXUSDT", "TAX", "USDT", "10.00000000");
+    mock().set_balance("key-tax", "USDT", "1000.00000000");
+    mock().set_balance("key-tax", "TAX", "3.00000000");
+    mock().script_fill("TAXUSDT", &[("10.00000000", "1.00000000")]);
+    mock().script_fill("TAXUSDT", &[("20.00000000", "1.00000000")]);
+    mock().script_fill("TAXUSDT", &[("25.00000000", "1.00000000")]);
+    let app = app().await;
+
+    let orders = [
+        (
+            "buy",
+            json!({ "symbol": "TAXUSDT", "quote_quantity": "10" }),
+        ),
+        (
+            "buy",
+            json!({ "symbol": "TAXUSDT", "quote_quantity": "20" }),
+        ),
+        ("sell", json!({ "symbol": "TAXUSDT", "base_quantity": "1" })),
+    ];
+    for (side, order) in orders {
+        let uri = format!("/v1/binance/spot/order/{}", side);
+        let reply = post(&app, &uri, with(credentials("key-tax"), order)).await;
+        assert_eq!(reply.status, StatusCode::OK);
+    }
+
+    let payload = with(
+        credentials("key-tax"),
+        json!({ "symbols": ["TAXUSDT"], "method": "hifo" }),
+    );
+    let reply = post(&app, "/v1/binance/spot/account/tax", payload).await;
+    assert_eq!(reply.status, StatusCode::OK);
+
+    let mut lines = reply.text.lines();
+    assert!(lines
+        .next()
+        .unwrap()
+        .starts_with("symbol,quote_asset,quantity"));
+
+    let row: Vec<&str> = lines.next().unwrap().split(',').collect();
+    assert_eq!(row[0], "TAXUSDT");
+    assert_eq!(row[2], "1");
+    assert_eq!(row[7], "20");
+    assert_eq!(row[10], "5");
+    assert_eq!(lines.next(), None);
+}
+
+#[tokio::test]
+async fn test_unknown_symbol() {
+    let app = app().await;
+
+    let reply = get(&app, "/v1/binance/spot/normal?symbol=MISSINGUSDT").await;
+    assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+    assert_eq!(reply.body["ok"], false);
+}
+
+#[tokio::test]
+async fn test_foreign_endpoint_refused() {
+    let app = app().await;
+
+    let payload = json!({
+        "api_key": "key-foreign",
+        "secret_key": "secret",
+        "endpoint": "http://169.254.169.254",
+        "symbol": "ETHUSDT",
+        "quote_quantity": "10",
+    });
+    let reply = post(&app, "/v1/binance/spot/order/buy", payload).await;
+    assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+    assert!(reply.body["message"]
+        .as_str()
+        .unwrap()
+        .contains("is not allowed"));
+}
+
+#[tokio::test]
+async fn test_halt_requires_admin_token() {
+    let app = app().await;
+
+    let reply = get(&app, "/v1/binance/spot/halt").await;
+    assert_eq!(reply.status, StatusCode::FORBIDDEN);
+    assert_eq!(reply.body["ok"], false);
+}
+
+#[tokio::test]
+async fn test_halt_cancel_flatten_and_resume() {
+    mock().add_symbol("HALTUSDT", "HALT", "USDT", "10.00000000");
+    mock().set_balance("key-halt", "HALT", "2.00000000");
+    mock().set_balance("key-halt", "USDT", "100.00000000");
+    mock().add_open_order("key-halt", "HALTUSDT", "BUY", "1.00000000");
+    let app = app().await;
+
+    let payload = with(
+        credentials("key-halt"),
+        json!({ "symbol": "HALTUSDT", "reason": "runaway", "cancel_orders": true, "flatten": true }),
+    );
+    let reply = admin(&app, Method::POST, "/v1/binance/spot/halt", Some(payload)).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(reply.body["data"]["cancelled"][0]["status"], "CANCELED");
+    assert_eq!(reply.body["data"]["flattened"]["side"], "SELL");
+    assert!(mock().open_orders("HALTUSDT").is_empty());
+    assert_eq!(mock().orders("HALTUSDT")[0]["quantity"], "2.00000000");
+
+    let account = reply.body["data"]["halt"]["account"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert!(account.starts_with("key:"));
+
+    let buy = with(
+        credentials("key-halt"),
+        json!({ "symbol": "HALTUSDT", "quote_quantity": "10" }),
+    );
+    let reply = post(&app, "/v1/binance/spot/order/buy", buy.clone()).await;
+    assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+    assert!(reply.body["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("RISK TRADING_HALTED"));
+    assert_eq!(mock().orders("HALTUSDT").len(), 1);
+
+    let reply = admin(&app, Method::GET, "/v1/binance/spot/halt", None).await;
+    let halts = reply.body["data"].as_array().unwrap();
+    assert!(halts.iter().any(|v| v["symbol"] == "HALTUSDT"));
+
+    let uri = format!("/v1/binance/spot/halt?account={}&symbol=HALTUSDT", account);
+    let reply = admin(&app, Method::DELETE, &uri, None).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(reply.body["data"][0]["reason"], "runaway");
+
+    let reply = post(&app, "/v1/binance/spot/order/buy", buy).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(mock().orders("HALTUSDT").len(), 2);
+}
+
+#[tokio::test]
+async fn test_grid_order_refused_while_halted() {
+    let closes: Vec<String> = (0..40)
+        .map(|i| format!("{}.00000000", 90 + (i % 11) * 2))
+        .collect();
+    let closes: Vec<&str> = closes.iter().map(String::as_str).collect();
+    mock().add_symbol("TRAPUSDT", "TRAP", "USDT", "100.00000000");
+    mock().set_closes("TRAPUSDT", &closes);
+    mock().set_balance("key-trap", "USDT", "1000.00000000");
+    let app = app().await;
+
+    let payload = json!({ "symbol": "TRAPUSDT", "investment": "1000" });
+    let reply = post(&app, "/v1/binance/spot/plot/suggest", payload).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    let positions = reply.body["data"]["positions"].clone();
+
+    let halt = json!({ "symbol": "TRAPUSDT", "reason": "grid test" });
+    let reply = admin(&app, Method::POST, "/v1/binance/spot/halt", Some(halt)).await;
+    assert_eq!(reply.status, StatusCode::OK);
+
+    // Near the bottom of the grid, where the positions above want to buy
+    mock().set_price("TRAPUSDT", "93.00000000");
+    let payload = with(
+        credentials("key-trap"),
+        json!({ "symbol": "TRAPUSDT", "positions": positions }),
+    );
+    let reply = post(&app, "/v1/binance/spot/order", payload).await;
+    assert_eq!(reply.status, StatusCode::FORBIDDEN);
+    assert_eq!(reply.body["ok"], false);
+    assert!(reply.body["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("RISK TRADING_HALTED"));
+    assert_eq!(reply.body["data"]["positions"], positions);
+    assert!(mock().orders("TRAPUSDT").is_empty());
+}
+
+#[tokio::test]
+async fn test_dust_refused_while_halted() {
+    mock().add_symbol("DUSTHUSDT", "DUSTH", "USDT", "10.00000000");
+    mock().set_balance("key-dusthalt", "USDT", "100.00000000");
+    mock().set_balance("key-dusthalt", "DUSTH", "0.10000000");
+    let app = app().await;
+
+    let halt = with(
+        credentials("key-dusthalt"),
+        json!({ "reason": "dust test" }),
+    );
+    let reply = admin(&app, Method::POST, "/v1/binance/spot/halt", Some(halt)).await;
+    assert_eq!(reply.status, StatusCode::OK);
+
+    let payload = with(credentials("key-dusthalt"), json!({ "convert": true }));
+    let reply = post(&app, "/v1/binance/spot/account/dust", payload).await;
+    assert_eq!(reply.status, StatusCode::FORBIDDEN);
+    assert!(reply.body["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("RISK TRADING_HALTED"));
+
+    // The balance is still dust, nothing was converted
+    let payload = with(credentials("key-dusthalt"), json!({}));
+    let reply = post(&app, "/v1/binance/spot/account/dust", payload).await;
+    assert_eq!(
+        reply.body["data"]["report"]["balances"][0]["asset"],
+        "DUSTH"
+    );
+}
+
+#[tokio::test]
+async fn test_grid_order_refused_without_balance() {
+    let closes: Vec<String> = (0..40)
+        .map(|i| format!("{}.00000000", 90 + (i % 11) * 2))
+        .collect();
+    let closes: Vec<&str> = closes.iter().map(String::as_str).collect();
+    mock().add_symbol("EMPTYUSDT", "EMPTY", "USDT", "100.00000000");
+    mock().set_closes("EMPTYUSDT", &closes);
+    let app = app().await;
+
+    let payload = json!({ "symbol": "EMPTYUSDT", "investment": "1000" });
+    let reply = post(&app, "/v1/binance/spot/plot/suggest", payload).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    let positions = reply.body["data"]["positions"].clone();
+
+    // The first position to buy is refused, not skipped
+    mock().set_price("EMPTYUSDT", "93.00000000");
+    let payload = with(
+        credentials("key-empty"),
+        json!({ "symbol": "EMPTYUSDT", "positions": positions }),
+    );
+    let reply = post(&app, "/v1/binance/spot/order", payload).await;
+    assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+    assert!(reply.body["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("RISK INSUFFICIENT_BALANCE"));
+    assert_eq!(reply.body["data"]["positions"], positions);
+    assert!(mock().orders("EMPTYUSDT").is_empty());
+}
+
+#[tokio::test]
+async fn test_track_grid() {
+    let closes: Vec<String> = (0..40)
+        .map(|i| format!("{}.00000000", 90 + (i % 11) * 2))
+        .collect();
+    let closes: Vec<&str> = closes.iter().map(String::as_str).collect();
+    mock().add_symbol("TRACKUSDT", "TRACK", "USDT", "100.00000000");
+    mock().set_closes("TRACKUSDT", &closes);
+    let app = app().await;
+
+    let payload = json!({ "symbol": "TRACKUSDT", "investment": "1000" });
+    let reply = post(&app, "/v1/binance/spot/plot/suggest", payload).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    let positions = reply.body["data"]["positions"].clone();
+
+    // Down through the grid and back up, simulated without any order
+    let payload = json!({
+        "symbol": "TRACKUSDT",
+        "positions": positions,
+        "prices": ["93.00000000", "107.00000000"],
+    });
+    let reply = post(&app, "/v1/binance/spot/track", payload.clone()).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert!(!reply.body["data"]["trades"].as_array().unwrap().is_empty());
+    assert_eq!(
+        reply.body["data"]["positions"].as_array().unwrap().len(),
+        positions.as_array().unwrap().len()
+    );
+    assert!(mock().orders("TRACKUSDT").is_empty());
+
+    let payload = with(payload, json!({ "symbol": "NONEUSDT" }));
+    let reply = post(&app, "/v1/binance/spot/track", payload).await;
+    assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_idempotent_grid_order() {
+    let closes: Vec<String> = (0..40)
+        .map(|i| format!("{}.00000000", 90 + (i % 11) * 2))
+        .collect();
+    let closes: Vec<&str> = closes.iter().map(String::as_str).collect();
+    mock().add_symbol("IGRIDUSDT", "IGRID", "USDT", "100.00000000");
+    mock().set_closes("IGRIDUSDT", &closes);
+    mock().set_balance("key-igrid", "USDT", "1000.00000000");
+    let app = app().await;
+
+    let payload = json!({ "symbol": "IGRIDUSDT", "investment": "1000" });
+    let reply = post(&app, "/v1/binance/spot/plot/suggest", payload).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    let positions = reply.body["data"]["positions"].clone();
+
+    mock().set_price("IGRIDUSDT", "93.00000000");
+    let payload = with(
+        credentials("key-igrid"),
+        json!({ "symbol": "IGRIDUSDT", "positions": positions }),
+    );
+    let headers = [("idempotency-key", "grid-1")];
+
+    let first = post_with_headers(&app, "/v1/binance/spot/order", payload.clone(), &headers).await;
+    assert_eq!(first.status, StatusCode::OK);
+    let placed = mock().orders("IGRIDUSDT").len();
+    assert!(placed > 0);
+
+    // A retry replays the positions instead of trading them again
+    let retry = post_with_headers(&app, "/v1/binance/spot/order", payload.clone(), &headers).await;
+    assert_eq!(retry.status, StatusCode::OK);
+    assert_eq!(retry.body["data"], first.body["data"]);
+    assert_eq!(mock().orders("IGRIDUSDT").len(), placed);
+
+    // A fresh process finds the orders by their key instead of trading again
+    let restarted = common::app().await;
+    let retry = post_with_headers(&restarted, "/v1/binance/spot/order", payload, &headers).await;
+    assert_eq!(retry.status, StatusCode::OK);
+    assert_eq!(
+        retry.body["data"]["placed"].as_array().unwrap().len(),
+        placed
+    );
+    assert!(retry.body["data"]["order"].as_array().unwrap().is_empty());
+    assert_eq!(mock().orders("IGRIDUSDT").len(), placed);
+}
+
+#[tokio::test]
+async fn test_idempotent_buy() {
+    mock().add_symbol("IDEMUSDT", "IDEM", "USDT", "100.00000000");
+    mock().set_balance("key-idem", "USDT", "100.00000000");
+    let app = app().await;
+
+    let payload = with(
+        credentials("key-idem"),
+        json!({ "symbol": "IDEMUSDT", "quote_quantity": "10" }),
+    );
+    let headers = [("idempotency-key", "order-1")];
+
+    let first = post_with_headers(
+        &app,
+        "/v1/binance/spot/order/buy",
+        payload.clone(),
+        &headers,
+    )
+    .await;
+    assert_eq!(first.status, StatusCode::OK);
+    let client_order_id = first.body["data"]["clientOrderId"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert!(client_order_id.starts_with("idem-"));
+
+    // A retry is answered with the stored outcome
+    let retry = post_with_headers(
+        &app,
+        "/v1/binance/spot/order/buy",
+        payload.clone(),
+        &headers,
+    )
+    .await;
+    assert_eq!(retry.status, StatusCode::OK);
+    assert_eq!(retry.body["data"], first.body["data"]);
+    assert_eq!(mock().orders("IDEMUSDT").len(), 1);
+
+    // A fresh process has no stored outcome and asks the exchange instead
+    let restarted = common::app().await;
+    let retry = post_with_headers(
+        &restarted,
+        "/v1/binance/spot/order/buy",
+        payload.clone(),
+        &headers,
+    )
+    .await;
+    assert_eq!(retry.status, StatusCode::OK);
+    assert_eq!(
+        retry.body["data"]["clientOrderId"],
+        client_order_id.as_str()
+    );
+    assert_eq!(retry.body["data"]["status"], "FILLED");
+    assert_eq!(mock().orders("IDEMUSDT").len(), 1);
+
+    // The same key with another payload is refused
+    let other = with(payload, json!({ "quote_quantity": "20" }));
+    let reply = post_with_headers(&app, "/v1/binance/spot/order/buy", other, &headers).await;
+    assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+    assert!(reply.body["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("IDEMPOTENCY CONFLICT"));
+    assert_eq!(mock().orders("IDEMUSDT").len(), 1);
+}
+
+#[tokio::test]
+async fn test_max_num_orders_filter() {
+    mock().add_symbol("OPENUSDT", "OPEN", "USDT", "10.00000000");
+    mock().set_balance("key-open", "USDT", "1000.00000000");
+    for _ in 0..199 {
+        mock().add_open_order("key-open", "OPENUSDT", "BUY", "1.00000000");
+    }
+    let app = app().await;
+
+    let payload = with(
+        credentials("key-open"),
+        json!({ "symbol": "OPENUSDT", "quote_quantity": "10" }),
+    );
+    let reply = post(&app, "/v1/binance/spot/order/buy", payload.clone()).await;
+    assert_eq!(reply.status, StatusCode::OK);
+
+    mock().add_open_order("key-open", "OPENUSDT", "SELL", "1.00000000");
+    let reply = post(&app, "/v1/binance/spot/order/buy", payload).await;
+    assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+    assert!(reply.body["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("FILTER MAX_NUM_ORDERS"));
+}
+
+#[tokio::test]
+async fn test_order_refused_while_symbol_in_break() {
+    mock().add_symbol("BREAKUSDT", "BREAK", "USDT", "10.00000000");
+    mock().set_balance("key-break", "USDT", "1000.00000000");
+    mock().set_status("BREAKUSDT", "BREAK");
+    let app = app().await;
+
+    for dry_run in [true, false] {
+        let payload = with(
+            credentials("key-break"),
+            json!({ "symbol": "BREAKUSDT", "quote_quantity": "10", "dry_run": dry_run }),
+        );
+        let reply = post(&app, "/v1/binance/spot/order/buy", payload).await;
+        assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+        assert!(reply.body["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("FILTER STATUS"));
+    }
+    assert!(mock().orders("BREAKUSDT").is_empty());
+}
+
+#[tokio::test]
+async fn test_dust_sweep() {
+    mock().add_symbol("DUSTAUSDT", "DUSTA", "USDT", "10.00000000");
+    mock().add_symbol("DUSTBUSDT", "DUSTB", "USDT", "10.00000000");
+    mock().set_balance("key-dust", "USDT", "100.00000000");
+    mock().set_balance("key-dust", "DUSTA", "0.10000000");
+    mock().set_balance("key-dust", "DUSTB", "2.00000000");
+    let app = app().await;
+
+    let payload = with(
+        credentials("key-dust"),
+        json!({ "convert": true, "dry_run": true }),
+    );
+    let reply = post(&app, "/v1/binance/spot/account/dust", payload).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(reply.body["data"]["converting"], json!(["DUSTA"]));
+    assert_eq!(
+        reply.body["data"]["report"]["balances"][0]["symbol"],
+        "DUSTAUSDT"
+    );
+    assert!(reply.body["data"]["transfer"].is_null());
+
+    // Only dust may be converted
+    let payload = with(
+        credentials("key-dust"),
+        json!({ "convert": true, "assets": ["DUSTB"] }),
+    );
+    let reply = post(&app, "/v1/binance/spot/account/dust", payload).await;
+    assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+
+    let payload = with(credentials("key-dust"), json!({ "convert": true }));
+    let reply = post(&app, "/v1/binance/spot/account/dust", payload).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(
+        reply.body["data"]["transfer"]["transferResult"][0]["fromAsset"],
+        "DUSTA"
+    );
+
+    let payload = with(credentials("key-dust"), json!({}));
+    let reply = post(&app, "/v1/binance/spot/account/dust", payload).await;
+    assert_eq!(reply.body["data"]["report"]["balances"], json!([]));
+}
+
+#[tokio::test]
+async fn test_read_retried_after_server_error() {
+    mock().add_symbol("RETRYUSDT", "RETRY", "USDT", "3.00000000");
+    mock().script_error(
+        "RETRYUSDT",
+        503,
+        -1001,
+        "Internal error; unable to process your request.",
+    );
+    let app = app().await;
+
+    let reply = get(&app, "/v1/binance/spot/price?symbol=RETRYUSDT").await;
+    assert_eq!(reply.status, StatusCode::OK);
+    assert_eq!(reply.body["data"][0]["price"], "3.00000000");
+
+    // A banned client must stop asking at once
+    mock().script_error("RETRYUSDT", 418, -1003, "Way too many requests; IP banned.");
+    let reply = get(&app, "/v1/binance/spot/price?symbol=RETRYUSDT").await;
+    assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_twap_and_iceberg_execution() {
+    use std::str::FromStr;
+
+    use plot::types::Decimal;
+
+    mock().add_symbol("TWAPUSDT", "TWAP", "USDT", "10.00000000");
+    mock().set_balance("key-twap", "USDT", "1000.00000000");
+    mock().set_balance("key-twap", "TWAP", "10.00000000");
+    let app = app().await;
+
+    let payload = with(
+        credentials("key-twap"),
+        json!({
+            "symbol": "TWAPUSDT",
+            "side": "BUY",
+            "quantity": "100",
+            "schedule": { "algorithm": "twap", "slices": 4, "interval_secs": 0 },
+        }),
+    );
+    let reply = post(&app, "/v1/binance/spot/order/execution", payload).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    let id = reply.body["data"]["id"].as_str().unwrap().to_string();
+
+    let status = with(credentials("key-twap"), json!({ "id": id }));
+    let reply = settled(&app, &status).await;
+    assert_eq!(reply.body["data"]["status"], "completed");
+    assert_eq!(reply.body["data"]["children"].as_array().unwrap().len(), 4);
+    let orders = mock().orders("TWAPUSDT");
+    assert_eq!(orders.len(), 4);
+    let quantity = |v: &serde_json::Value| Decimal::from_str(v.as_str().unwrap()).unwrap();
+    assert!(orders
+        .iter()
+        .all(|v| quantity(&v["quoteOrderQty"]) == Decimal::from(25)));
+    assert!(orders
+        .iter()
+        .all(|v| v["newClientOrderId"].as_str().unwrap().starts_with(&id)));
+
+    // Another key does not see the execution
+    let other = with(credentials("key-other"), json!({ "id": id }));
+    let reply = post(&app, "/v1/binance/spot/order/execution/status", other).await;
+    assert_eq!(reply.status, StatusCode::BAD_REQUEST);
+
+    let payload = with(
+        credentials("key-twap"),
+        json!({
+            "symbol": "TWAPUSDT",
+            "side": "SELL",
+            "quantity": "6",
+            "schedule": { "algorithm": "iceberg", "visible": "2", "interval_secs": 60 },
+        }),
+    );
+    let reply = post(&app, "/v1/binance/spot/order/execution", payload).await;
+    assert_eq!(reply.status, StatusCode::OK);
+    let id = reply.body["data"]["id"].as_str().unwrap().to_string();
+
+    let status = with(credentials("key-twap"), json!({ "id": id }));
+    for _ in 0..50 {
+        if mock().orders("TWAPUSDT").len() > 4 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    let reply = post(
+        &app,
+        "/v1/binance/spot/order/execution/cancel",
+        status.clone(),
+    )
+    .await;
+    assert_eq!(reply.status, StatusCode::OK);
+
+    let reply = settled(&app, &status).await;
+    assert_eq!(reply.body["data"]["status"], "cancelled");
+    assert_eq!(reply.body["data"]["children"].as_array().unwrap().len(), 1);
+    let order = &mock().orders("TWAPUSDT")[4];
+    assert_eq!(quantity(&order["quantity"]), Decimal::TWO);
+}
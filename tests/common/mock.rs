@@ -0,0 +1,1004 @@
+//! In-process mock of the Binance REST endpoints used by harmony.
+//!
+//! A single server is shared by every test in the binary. Tests keep out of
+//! each other's way by using their own symbols and api keys.
+
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde_json::{json, Value};
+
+type Shared = Arc<Mutex<MockState>>;
+
+#[derive(Default)]
+struct MockState {
+    symbols: HashMap<String, Value>,
+    prices: HashMap<String, String>,
+    balances: HashMap<String, Vec<Value>>,
+    fills: HashMap<String, VecDeque<Vec<(String, String)>>>,
+    errors: HashMap<String, VecDeque<(u16, i64, String)>>,
+    orders: Vec<Value>,
+    open_orders: Vec<Value>,
+    trades: HashMap<String, Vec<Value>>,
+    depths: HashMap<String, Value>,
+    klines: HashMap<String, Vec<Value>>,
+    futures_symbols: HashMap<String, Value>,
+    positions: HashMap<(String, String), Value>,
+    funding_rates: HashMap<String, Vec<Value>>,
+    next_id: i64,
+}
+
+pub struct MockBinance {
+    pub url: String,
+    state: Shared,
+}
+
+/// The mock server of this test binary, started on first use.
+pub fn mock() -> &'static MockBinance {
+    static MOCK: OnceLock<MockBinance> = OnceLock::new();
+
+    MOCK.get_or_init(|| {
+        let state = Shared::default();
+        let router = router(state.clone());
+        let (sender, receiver) = std::sync::mpsc::channel::<SocketAddr>();
+
+        // Own runtime, so the server outlives the runtime of any single test
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+
+            runtime.block_on(async move {
+                let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+                sender.send(listener.local_addr().unwrap()).unwrap();
+                axum::serve(listener, router).await.unwrap();
+            });
+        });
+
+        let address = receiver.recv().unwrap();
+
+        MockBinance {
+            url: format!("http://{}", address),
+            state,
+        }
+    })
+}
+
+impl MockBinance {
+    fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// List `symbol` with the filters of the ETHUSDT spot market.
+    pub fn add_symbol(&self, symbol: &str, base: &str, quote: &str, price: &str) {
+        let mut info: Value = serde_json::from_str(SYMBOL_INFO).unwrap();
+        info["symbol"] = json!(symbol);
+        info["baseAsset"] = json!(base);
+        info["quoteAsset"] = json!(quote);
+
+        let mut state = self.state();
+        state.symbols.insert(symbol.into(), info);
+        state.prices.insert(symbol.into(), price.into());
+    }
+
+    /// List the USDⓈ-M contract `symbol` with the filters of the BTCUSDT
+    /// perpetual.
+    pub fn add_futures_symbol(&self, symbol: &str, base: &str, quote: &str, price: &str) {
+        let mut info: Value = serde_json::from_str(FUTURES_SYMBOL_INFO).unwrap();
+        info["symbol"] = json!(symbol);
+        info["pair"] = json!(symbol);
+        info["baseAsset"] = json!(base);
+        info["quoteAsset"] = json!(quote);
+        info["marginAsset"] = json!(quote);
+
+        let mut state = self.state();
+        state.futures_symbols.insert(symbol.into(), info);
+        state.prices.insert(symbol.into(), price.into());
+    }
+
+    /// Record past funding of `symbol` as `(funding time, rate)` pairs.
+    pub fn add_funding_rates(&self, symbol: &str, rates: &[(u64, &str)]) {
+        let mut state = self.state();
+        let price = state.prices.get(symbol).cloned().unwrap_or_default();
+        let history = state.funding_rates.entry(symbol.into()).or_default();
+        for (time, rate) in rates {
+            history.push(json!({
+                "symbol": symbol,
+                "fundingRate": rate,
+                "fundingTime": time,
+                "markPrice": price,
+            }));
+        }
+    }
+
+    /// Set the trading `status` of a listed symbol, e.g. `BREAK` or `HALT`.
+    pub fn set_status(&self, symbol: &str, status: &str) {
+        if let Some(info) = self.state().symbols.get_mut(symbol) {
+            info["status"] = json!(status);
+        }
+    }
+
+    pub fn set_price(&self, symbol: &str, price: &str) {
+        self.state().prices.insert(symbol.into(), price.into());
+    }
+
+    pub fn set_balance(&self, api_key: &str, asset: &str, free: &str) {
+        let mut state = self.state();
+        let balances = state.balances.entry(api_key.into()).or_default();
+        balances.retain(|v| v["asset"] != asset);
+        balances.push(json!({
+            "asset": asset,
+            "free": free,
+            "locked": "0",
+            "freeze": "0",
+            "withdrawing": "0",
+            "ipoable": "0",
+            "btcValuation": "0",
+        }));
+    }
+
+    /// Rest these `(price, qty)` levels on the book of `symbol`, best first.
+    pub fn set_depth(&self, symbol: &str, bids: &[(&str, &str)], asks: &[(&str, &str)]) {
+        self.state().depths.insert(
+            symbol.into(),
+            json!({ "lastUpdateId": 1, "bids": bids, "asks": asks }),
+        );
+    }
+
+    /// Serve one-minute candles of `symbol` closing at these prices.
+    pub fn set_closes(&self, symbol: &str, closes: &[&str]) {
+        let klines = closes
+            .iter()
+            .enumerate()
+            .map(|(i, close)| {
+                let open_time = 1700000000000u64 + i as u64 * 60_000;
+                json!([
+                    open_time,
+                    close,
+                    close,
+                    close,
+                    close,
+                    "1.00000000",
+                    open_time + 59_999,
+                    close,
+                    1,
+                    "0",
+                    "0",
+                    "0"
+                ])
+            })
+            .collect();
+
+        self.state().klines.insert(symbol.into(), klines);
+    }
+
+    /// Fill the next order on `symbol` with these `(price, qty)` parts
+    /// instead of a single fill at the current price.
+    pub fn script_fill(&self, symbol: &str, fills: &[(&str, &str)]) {
+        let fills = fills
+            .iter()
+            .map(|(p, q)| (p.to_string(), q.to_string()))
+            .collect();
+
+        self.state()
+            .fills
+            .entry(symbol.into())
+            .or_default()
+            .push_back(fills);
+    }
+
+    /// Fail the next request about `symbol` with a Binance error body.
+    pub fn script_error(&self, symbol: &str, status: u16, code: i64, msg: &str) {
+        self.state()
+            .errors
+            .entry(symbol.into())
+            .or_default()
+            .push_back((status, code, msg.into()));
+    }
+
+    /// Rest a limit order of `api_key` on the book, returning its order id.
+    pub fn add_open_order(&self, api_key: &str, symbol: &str, side: &str, qty: &str) -> i64 {
+        let mut state = self.state();
+        state.next_id += 1;
+        let order_id = state.next_id;
+
+        state.open_orders.push(json!({
+            "apiKey": api_key,
+            "symbol": symbol,
+            "orderId": order_id,
+            "orderListId": -1,
+            "clientOrderId": format!("mock{}", order_id),
+            "price": "1.00000000",
+            "origQty": qty,
+            "executedQty": "0.00000000",
+            "cummulativeQuoteQty": "0.00000000",
+            "status": "NEW",
+            "timeInForce": "GTC",
+            "type": "LIMIT",
+            "side": side,
+            "stopPrice": "0.00000000",
+            "icebergQty": "0.00000000",
+            "time": 1700000000000u64,
+            "updateTime": 1700000000000u64,
+            "isWorking": true,
+            "workingTime": 1700000000000u64,
+            "origQuoteOrderQty": "0.00000000",
+            "selfTradePreventionMode": "NONE",
+        }));
+
+        order_id
+    }
+
+    /// Orders of `symbol` still resting on the book.
+    pub fn open_orders(&self, symbol: &str) -> Vec<Value> {
+        self.state()
+            .open_orders
+            .iter()
+            .filter(|v| v["symbol"] == symbol)
+            .cloned()
+            .collect()
+    }
+
+    /// Orders placed on `symbol`, as the parameters they were sent with.
+    pub fn orders(&self, symbol: &str) -> Vec<Value> {
+        self.state()
+            .orders
+            .iter()
+            .filter(|v| v["symbol"] == symbol)
+            .cloned()
+            .collect()
+    }
+}
+
+fn router(state: Shared) -> Router {
+    Router::new()
+        .route("/api/v3/exchangeInfo", get(exchange_info))
+        .route("/api/v3/ticker/price", get(ticker_price))
+        .route("/api/v3/depth", get(depth))
+        .route("/api/v3/klines", get(klines))
+        .route("/api/v3/order", post(order_new).get(order_query))
+        .route(
+            "/api/v3/openOrders",
+            get(open_orders).delete(cancel_open_orders),
+        )
+        .route("/api/v3/myTrades", get(my_trades))
+        .route("/api/v3/account", get(account))
+        .route("/api/v3/account/commission", get(commission))
+        .route("/sapi/v3/asset/getUserAsset", post(user_asset))
+        .route("/sapi/v1/asset/dust", post(dust_transfer))
+        .route("/fapi/v1/exchangeInfo", get(futures_exchange_info))
+        .route("/fapi/v1/ticker/price", get(ticker_price))
+        .route("/fapi/v1/fundingRate", get(funding_rate))
+        .route("/fapi/v1/leverage", post(leverage))
+        .route("/fapi/v1/marginType", post(margin_type))
+        .route("/fapi/v2/positionRisk", get(position_risk))
+        .route("/fapi/v1/order", post(futures_order_new))
+        .with_state(state)
+}
+
+/// Signed endpoints may send their parameters in the query or the body.
+fn params(uri: &Uri, body: &str) -> HashMap<String, String> {
+    let query = uri.query().unwrap_or_default();
+
+    query
+        .split('&')
+        .chain(body.split('&'))
+        .filter_map(|v| v.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn api_key(headers: &HeaderMap) -> String {
+    headers
+        .get("x-mbx-apikey")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn error(status: u16, code: i64, msg: &str) -> Response {
+    let status = StatusCode::from_u16(status).unwrap_or(StatusCode::BAD_REQUEST);
+
+    (status, Json(json!({ "code": code, "msg": msg }))).into_response()
+}
+
+fn scripted_error(state: &mut MockState, symbol: Option<&String>) -> Option<Response> {
+    let (status, code, msg) = state.errors.get_mut(symbol?)?.pop_front()?;
+
+    Some(error(status, code, &msg))
+}
+
+fn unknown_symbol() -> Response {
+    error(400, -1121, "Invalid symbol.")
+}
+
+async fn exchange_info(State(s): State<Shared>, uri: Uri) -> Response {
+    let p = params(&uri, "");
+    let mut state = s.lock().unwrap();
+    if let Some(e) = scripted_error(&mut state, p.get("symbol")) {
+        return e;
+    }
+
+    let symbols: Vec<Value> = match p.get("symbol") {
+        Some(v) => match state.symbols.get(v) {
+            Some(info) => vec![info.clone()],
+            None => return unknown_symbol(),
+        },
+        None => state.symbols.values().cloned().collect(),
+    };
+
+    Json(json!({
+        "timezone": "UTC",
+        "serverTime": 1700000000000u64,
+        "rateLimits": [],
+        "exchangeFilters": [],
+        "symbols": symbols,
+    }))
+    .into_response()
+}
+
+async fn depth(State(s): State<Shared>, uri: Uri) -> Response {
+    let p = params(&uri, "");
+    let mut state = s.lock().unwrap();
+    if let Some(e) = scripted_error(&mut state, p.get("symbol")) {
+        return e;
+    }
+
+    let symbol = p.get("symbol").cloned().unwrap_or_default();
+    match state.depths.get(&symbol) {
+        Some(v) => Json(v.clone()).into_response(),
+        None => unknown_symbol(),
+    }
+}
+
+async fn klines(State(s): State<Shared>, uri: Uri) -> Response {
+    let p = params(&uri, "");
+    let mut state = s.lock().unwrap();
+    if let Some(e) = scripted_error(&mut state, p.get("symbol")) {
+        return e;
+    }
+
+    let symbol = p.get("symbol").cloned().unwrap_or_default();
+    let limit: usize = p.get("limit").and_then(|v| v.parse().ok()).unwrap_or(500);
+    match state.klines.get(&symbol) {
+        Some(v) => Json(v.iter().take(limit).collect::<Vec<_>>()).into_response(),
+        None => unknown_symbol(),
+    }
+}
+
+async fn ticker_price(State(s): State<Shared>, uri: Uri) -> Response {
+    let p = params(&uri, "");
+    let mut state = s.lock().unwrap();
+    if let Some(e) = scripted_error(&mut state, p.get("symbol")) {
+        return e;
+    }
+
+    match p.get("symbol") {
+        Some(symbol) => match state.prices.get(symbol) {
+            Some(price) => Json(json!({ "symbol": symbol, "price": price })).into_response(),
+            None => unknown_symbol(),
+        },
+        None => {
+            let prices: Vec<Value> = state
+                .prices
+                .iter()
+                .map(|(symbol, price)| json!({ "symbol": symbol, "price": price }))
+                .collect();
+
+            Json(prices).into_response()
+        }
+    }
+}
+
+async fn order_new(
+    State(s): State<Shared>,
+    uri: Uri,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    use std::str::FromStr;
+
+    use plot::types::Decimal;
+
+    let p = params(&uri, &body);
+    let mut state = s.lock().unwrap();
+    if let Some(e) = scripted_error(&mut state, p.get("symbol")) {
+        return e;
+    }
+
+    let symbol = p.get("symbol").cloned().unwrap_or_default();
+    let Some(price) = state.prices.get(&symbol).cloned() else {
+        return unknown_symbol();
+    };
+    let side = p.get("side").cloned().unwrap_or_default();
+
+    let fills = match state.fills.get_mut(&symbol).and_then(|v| v.pop_front()) {
+        Some(v) => v,
+        None => {
+            let price_dec = Decimal::from_str(&price).unwrap();
+            let qty = match (p.get("quantity"), p.get("quoteOrderQty")) {
+                (Some(q), _) => Decimal::from_str(q).unwrap(),
+                (None, Some(q)) => (Decimal::from_str(q).unwrap() / price_dec).round_dp(8),
+                (None, None) => {
+                    return error(400, -1102, "Mandatory parameter 'quantity' was not sent.")
+                }
+            };
+
+            vec![(price, qty.to_string())]
+        }
+    };
+
+    state.next_id += 1;
+    let order_id = state.next_id;
+    let client_order_id = p
+        .get("newClientOrderId")
+        .cloned()
+        .unwrap_or(format!("mock{}", order_id));
+
+    let mut executed = Decimal::ZERO;
+    let mut cummulative = Decimal::ZERO;
+    let mut fills_json = Vec::new();
+    for (i, (price, qty)) in fills.iter().enumerate() {
+        let price_dec = Decimal::from_str(price).unwrap();
+        let qty_dec = Decimal::from_str(qty).unwrap();
+        executed += qty_dec;
+        cummulative += price_dec * qty_dec;
+
+        let trade_id = order_id * 100 + i as i64;
+        fills_json.push(json!({
+            "price": price,
+            "qty": qty,
+            "commission": "0",
+            "commissionAsset": "BNB",
+            "tradeId": trade_id,
+        }));
+
+        state.trades.entry(symbol.clone()).or_default().push(json!({
+            "symbol": symbol,
+            "id": trade_id,
+            "orderId": order_id,
+            "orderListId": -1,
+            "price": price,
+            "qty": qty,
+            "quoteQty": (price_dec * qty_dec).to_string(),
+            "commission": "0",
+            "commissionAsset": "BNB",
+            "time": 1700000000000u64 + trade_id as u64,
+            "isBuyer": side == "BUY",
+            "isMaker": false,
+            "isBestMatch": true,
+        }));
+    }
+
+    let mut placed = json!(p);
+    placed["apiKey"] = json!(api_key(&headers));
+    placed["orderId"] = json!(order_id);
+    state.orders.push(placed);
+
+    Json(json!({
+        "symbol": symbol,
+        "orderId": order_id,
+        "orderListId": -1,
+        "clientOrderId": client_order_id,
+        "transactTime": 1700000000000u64,
+        "price": "0.00000000",
+        "origQty": executed.to_string(),
+        "executedQty": executed.to_string(),
+        "cummulativeQuoteQty": cummulative.to_string(),
+        "status": "FILLED",
+        "timeInForce": "GTC",
+        "type": "MARKET",
+        "side": side,
+        "workingTime": 1700000000000u64,
+        "selfTradePreventionMode": "NONE",
+        "fills": fills_json,
+    }))
+    .into_response()
+}
+
+async fn order_query(State(s): State<Shared>, uri: Uri) -> Response {
+    let p = params(&uri, "");
+    let mut state = s.lock().unwrap();
+    if let Some(e) = scripted_error(&mut state, p.get("symbol")) {
+        return e;
+    }
+
+    let order_id: Option<i64> = p.get("orderId").and_then(|v| v.parse().ok());
+    let client_order_id = p.get("origClientOrderId");
+    let Some(order) = state
+        .orders
+        .iter()
+        .find(|v| match (order_id, client_order_id) {
+            (Some(id), _) => v["orderId"] == id,
+            (None, Some(id)) => v["newClientOrderId"] == id.as_str(),
+            (None, None) => false,
+        })
+    else {
+        return error(400, -2013, "Order does not exist.");
+    };
+    let order_id = order["orderId"].clone();
+
+    let qty = order
+        .get("quantity")
+        .or(order.get("quoteOrderQty"))
+        .cloned()
+        .unwrap_or(json!("0"));
+
+    Json(json!({
+        "symbol": order["symbol"],
+        "orderId": order_id,
+        "orderListId": -1,
+        "clientOrderId": order.get("newClientOrderId").cloned().unwrap_or(json!("")),
+        "price": "0.00000000",
+        "origQty": qty,
+        "executedQty": qty,
+        "cummulativeQuoteQty": "0",
+        "status": "FILLED",
+        "timeInForce": "GTC",
+        "type": "MARKET",
+        "side": order["side"],
+        "stopPrice": "0.00000000",
+        "icebergQty": "0.00000000",
+        "time": 1700000000000u64,
+        "updateTime": 1700000000000u64,
+        "isWorking": true,
+        "workingTime": 1700000000000u64,
+        "origQuoteOrderQty": "0.00000000",
+        "selfTradePreventionMode": "NONE",
+    }))
+    .into_response()
+}
+
+async fn open_orders(
+    State(s): State<Shared>,
+    uri: Uri,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    let p = params(&uri, &body);
+    let mut state = s.lock().unwrap();
+    if let Some(e) = scripted_error(&mut state, p.get("symbol")) {
+        return e;
+    }
+
+    let api_key = api_key(&headers);
+    let orders: Vec<Value> = state
+        .open_orders
+        .iter()
+        .filter(|v| v["apiKey"] == api_key.as_str())
+        .filter(|v| p.get("symbol").is_none_or(|s| v["symbol"] == s.as_str()))
+        .cloned()
+        .collect();
+
+    Json(orders).into_response()
+}
+
+async fn cancel_open_orders(
+    State(s): State<Shared>,
+    uri: Uri,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    let p = params(&uri, &body);
+    let mut state = s.lock().unwrap();
+    if let Some(e) = scripted_error(&mut state, p.get("symbol")) {
+        return e;
+    }
+
+    let Some(symbol) = p.get("symbol").cloned() else {
+        return error(400, -1102, "Mandatory parameter 'symbol' was not sent.");
+    };
+
+    let api_key = api_key(&headers);
+    let (mut cancelled, open): (Vec<Value>, Vec<Value>) = state
+        .open_orders
+        .drain(..)
+        .partition(|v| v["apiKey"] == api_key.as_str() && v["symbol"] == symbol.as_str());
+    state.open_orders = open;
+
+    if cancelled.is_empty() {
+        return error(400, -2011, "Unknown order sent.");
+    }
+
+    for order in cancelled.iter_mut() {
+        order["status"] = json!("CANCELED");
+    }
+
+    Json(cancelled).into_response()
+}
+
+async fn my_trades(State(s): State<Shared>, uri: Uri) -> Response {
+    let p = params(&uri, "");
+    let mut state = s.lock().unwrap();
+    if let Some(e) = scripted_error(&mut state, p.get("symbol")) {
+        return e;
+    }
+
+    let symbol = p.get("symbol").cloned().unwrap_or_default();
+    let order_id: Option<i64> = p.get("orderId").and_then(|v| v.parse().ok());
+    let from_id: Option<i64> = p.get("fromId").and_then(|v| v.parse().ok());
+    let limit: usize = p.get("limit").and_then(|v| v.parse().ok()).unwrap_or(500);
+
+    let trades: Vec<Value> = state
+        .trades
+        .get(&symbol)
+        .into_iter()
+        .flatten()
+        .filter(|v| order_id.is_none_or(|id| v["orderId"] == id))
+        .filter(|v| from_id.is_none_or(|id| v["id"].as_i64() >= Some(id)))
+        .take(limit)
+        .cloned()
+        .collect();
+
+    Json(trades).into_response()
+}
+
+async fn account(State(s): State<Shared>, headers: HeaderMap) -> Response {
+    let state = s.lock().unwrap();
+    let balances: Vec<Value> = state
+        .balances
+        .get(&api_key(&headers))
+        .into_iter()
+        .flatten()
+        .map(|v| json!({ "asset": v["asset"], "free": v["free"], "locked": v["locked"] }))
+        .collect();
+
+    Json(json!({
+        "makerCommission": 10,
+        "takerCommission": 10,
+        "buyerCommission": 0,
+        "sellerCommission": 0,
+        "canTrade": true,
+        "canWithdraw": true,
+        "canDeposit": true,
+        "updateTime": 1700000000000u64,
+        "accountType": "SPOT",
+        "balances": balances,
+        "permissions": ["SPOT"],
+    }))
+    .into_response()
+}
+
+async fn commission(State(s): State<Shared>, uri: Uri) -> Response {
+    let p = params(&uri, "");
+    let mut state = s.lock().unwrap();
+    if let Some(e) = scripted_error(&mut state, p.get("symbol")) {
+        return e;
+    }
+
+    let rates = json!({ "maker": "0.00100000", "taker": "0.00100000", "buyer": "0.00000000", "seller": "0.00000000" });
+
+    Json(json!({
+        "symbol": p.get("symbol"),
+        "standardCommission": rates,
+        "taxCommission": { "maker": "0", "taker": "0", "buyer": "0", "seller": "0" },
+        "discount": {
+            "enabledForAccount": true,
+            "enabledForSymbol": true,
+            "discountAsset": "BNB",
+            "discount": "0.75000000",
+        },
+    }))
+    .into_response()
+}
+
+async fn user_asset(
+    State(s): State<Shared>,
+    uri: Uri,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    let p = params(&uri, &body);
+    let state = s.lock().unwrap();
+    let assets: Vec<Value> = state
+        .balances
+        .get(&api_key(&headers))
+        .into_iter()
+        .flatten()
+        .filter(|v| p.get("asset").is_none_or(|a| v["asset"] == a.as_str()))
+        .cloned()
+        .collect();
+
+    Json(assets).into_response()
+}
+
+/// Converts every listed asset of the account into BNB at a flat 0.01 per unit.
+async fn dust_transfer(
+    State(s): State<Shared>,
+    uri: Uri,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    let query = uri.query().unwrap_or_default();
+    let assets: Vec<String> = query
+        .split('&')
+        .chain(body.split('&'))
+        .filter_map(|v| v.split_once('='))
+        .filter(|(k, _)| *k == "asset")
+        .map(|(_, v)| v.to_string())
+        .collect();
+    if assets.is_empty() {
+        return error(400, -1102, "Mandatory parameter 'asset' was not sent.");
+    }
+
+    let mut state = s.lock().unwrap();
+    let balances = state.balances.entry(api_key(&headers)).or_default();
+    let mut results = Vec::new();
+    for balance in balances.iter_mut() {
+        let Some(asset) = assets.iter().find(|v| balance["asset"] == v.as_str()) else {
+            continue;
+        };
+
+        results.push(json!({
+            "amount": balance["free"],
+            "fromAsset": asset,
+            "operateTime": 1700000000000u64,
+            "serviceChargeAmount": "0",
+            "tranId": 1,
+            "transferedAmount": "0.01000000",
+        }));
+        balance["free"] = json!("0");
+    }
+
+    Json(json!({
+        "totalServiceCharge": "0",
+        "totalTransfered": format!("{:.8}", 0.01 * results.len() as f64),
+        "transferResult": results,
+    }))
+    .into_response()
+}
+
+async fn futures_exchange_info(State(s): State<Shared>) -> Response {
+    let state = s.lock().unwrap();
+
+    Json(json!({
+        "timezone": "UTC",
+        "serverTime": 1700000000000u64,
+        "futuresType": "U_MARGINED",
+        "rateLimits": [],
+        "exchangeFilters": [],
+        "assets": [],
+        "symbols": state.futures_symbols.values().cloned().collect::<Vec<_>>(),
+    }))
+    .into_response()
+}
+
+async fn funding_rate(State(s): State<Shared>, uri: Uri) -> Response {
+    let p = params(&uri, "");
+    let state = s.lock().unwrap();
+
+    let number = |name: &str| p.get(name).and_then(|v| v.parse::<u64>().ok());
+    let (start, end) = (number("startTime"), number("endTime"));
+    let limit = number("limit").unwrap_or(100) as usize;
+
+    let symbol = p.get("symbol").cloned().unwrap_or_default();
+    let rates: Vec<Value> = state
+        .funding_rates
+        .get(&symbol)
+        .into_iter()
+        .flatten()
+        .filter(|v| {
+            let time = v["fundingTime"].as_u64().unwrap_or_default();
+            start.is_none_or(|s| time >= s) && end.is_none_or(|e| time <= e)
+        })
+        .take(limit)
+        .cloned()
+        .collect();
+
+    Json(rates).into_response()
+}
+
+/// The position of `api_key` in `symbol`, empty until it trades.
+fn position<'a>(state: &'a mut MockState, api_key: &str, symbol: &str) -> &'a mut Value {
+    let price = state.prices.get(symbol).cloned().unwrap_or_default();
+
+    state
+        .positions
+        .entry((api_key.into(), symbol.into()))
+        .or_insert_with(|| {
+            json!({
+                "symbol": symbol,
+                "positionSide": "BOTH",
+                "positionAmt": "0",
+                "entryPrice": "0",
+                "breakEvenPrice": "0",
+                "markPrice": price,
+                "unRealizedProfit": "0",
+                "liquidationPrice": "0",
+                "leverage": "20",
+                "maxNotionalValue": "1000000",
+                "marginType": "cross",
+                "isolatedMargin": "0",
+                "isAutoAddMargin": "false",
+                "notional": "0",
+                "isolatedWallet": "0",
+                "updateTime": 0,
+            })
+        })
+}
+
+async fn leverage(State(s): State<Shared>, uri: Uri, headers: HeaderMap, body: String) -> Response {
+    let p = params(&uri, &body);
+    let mut state = s.lock().unwrap();
+    let symbol = p.get("symbol").cloned().unwrap_or_default();
+    if !state.futures_symbols.contains_key(&symbol) {
+        return unknown_symbol();
+    }
+
+    let leverage = p.get("leverage").cloned().unwrap_or_default();
+    if !leverage.parse::<u8>().is_ok_and(|v| (1..=125).contains(&v)) {
+        return error(400, -4028, &format!("Leverage {} is not valid", leverage));
+    }
+
+    position(&mut state, &api_key(&headers), &symbol)["leverage"] = json!(leverage);
+
+    Json(json!({
+        "leverage": leverage.parse::<u8>().unwrap(),
+        "maxNotionalValue": "1000000",
+        "symbol": symbol,
+    }))
+    .into_response()
+}
+
+async fn margin_type(
+    State(s): State<Shared>,
+    uri: Uri,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    let p = params(&uri, &body);
+    let mut state = s.lock().unwrap();
+    let symbol = p.get("symbol").cloned().unwrap_or_default();
+    if !state.futures_symbols.contains_key(&symbol) {
+        return unknown_symbol();
+    }
+
+    let margin_type = match p.get("marginType").map(String::as_str) {
+        Some("ISOLATED") => "isolated",
+        Some("CROSSED") => "cross",
+        _ => return error(400, -1102, "Mandatory parameter 'marginType' was not sent."),
+    };
+
+    let position = position(&mut state, &api_key(&headers), &symbol);
+    if position["marginType"] == margin_type {
+        return error(400, -4046, "No need to change margin type.");
+    }
+    if position["positionAmt"] != "0" {
+        return error(
+            400,
+            -4048,
+            "Margin type cannot be changed if there exists position.",
+        );
+    }
+    position["marginType"] = json!(margin_type);
+
+    Json(json!({ "code": 200, "msg": "success" })).into_response()
+}
+
+async fn position_risk(State(s): State<Shared>, uri: Uri, headers: HeaderMap) -> Response {
+    let p = params(&uri, "");
+    let mut state = s.lock().unwrap();
+    let api_key = api_key(&headers);
+
+    let symbols: Vec<String> = match p.get("symbol") {
+        Some(v) if state.futures_symbols.contains_key(v) => vec![v.clone()],
+        Some(_) => return unknown_symbol(),
+        None => state.futures_symbols.keys().cloned().collect(),
+    };
+
+    let positions: Vec<Value> = symbols
+        .iter()
+        .map(|symbol| position(&mut state, &api_key, symbol).clone())
+        .collect();
+
+    Json(positions).into_response()
+}
+
+/// Fills a market order at the current price, in one way position mode.
+async fn futures_order_new(
+    State(s): State<Shared>,
+    uri: Uri,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    use std::str::FromStr;
+
+    use plot::types::Decimal;
+
+    let p = params(&uri, &body);
+    let mut state = s.lock().unwrap();
+    if let Some(e) = scripted_error(&mut state, p.get("symbol")) {
+        return e;
+    }
+
+    let symbol = p.get("symbol").cloned().unwrap_or_default();
+    let Some(price) = state.prices.get(&symbol).cloned() else {
+        return unknown_symbol();
+    };
+    let Some(quantity) = p.get("quantity") else {
+        return error(400, -1102, "Mandatory parameter 'quantity' was not sent.");
+    };
+    let side = p.get("side").cloned().unwrap_or_default();
+    let reduce_only = p.get("reduceOnly").is_some_and(|v| v == "true");
+
+    let price_dec = Decimal::from_str(&price).unwrap();
+    let quantity_dec = Decimal::from_str(quantity).unwrap();
+    let signed = match side.as_str() {
+        "BUY" => quantity_dec,
+        _ => -quantity_dec,
+    };
+
+    state.next_id += 1;
+    let order_id = state.next_id;
+    let api_key = api_key(&headers);
+
+    let position = position(&mut state, &api_key, &symbol);
+    let amount = Decimal::from_str(position["positionAmt"].as_str().unwrap()).unwrap();
+    let entry = Decimal::from_str(position["entryPrice"].as_str().unwrap()).unwrap();
+    let next = amount + signed;
+    if reduce_only && (amount.is_zero() || amount.is_sign_positive() == signed.is_sign_positive()) {
+        return error(400, -2022, "ReduceOnly Order is rejected.");
+    }
+
+    // Average cost when adding, the fill price when opened or flipped
+    let entry = if next.is_zero() {
+        Decimal::ZERO
+    } else if amount.is_zero() || next.is_sign_positive() != amount.is_sign_positive() {
+        price_dec
+    } else if next.abs() > amount.abs() {
+        (amount * entry + signed * price_dec) / next
+    } else {
+        entry
+    };
+    position["positionAmt"] = json!(next.normalize().to_string());
+    position["entryPrice"] = json!(entry.normalize().to_string());
+    position["notional"] = json!((next * price_dec).normalize().to_string());
+
+    let client_order_id = p
+        .get("newClientOrderId")
+        .cloned()
+        .unwrap_or(format!("mock{}", order_id));
+
+    let mut placed = json!(p);
+    placed["apiKey"] = json!(api_key);
+    placed["orderId"] = json!(order_id);
+    state.orders.push(placed);
+
+    Json(json!({
+        "symbol": symbol,
+        "orderId": order_id,
+        "clientOrderId": client_order_id,
+        "side": side,
+        "positionSide": "BOTH",
+        "status": "FILLED",
+        "type": "MARKET",
+        "origType": "MARKET",
+        "timeInForce": "GTC",
+        "price": "0",
+        "stopPrice": "0",
+        "origQty": quantity,
+        "executedQty": quantity,
+        "avgPrice": price,
+        "cumQty": quantity,
+        "cumQuote": (price_dec * quantity_dec).normalize().to_string(),
+        "reduceOnly": reduce_only,
+        "closePosition": false,
+        "workingType": "CONTRACT_PRICE",
+        "priceProtect": false,
+        "selfTradePreventionMode": "NONE",
+        "goodTillDate": 0,
+        "updateTime": 1700000000000u64,
+    }))
+    .into_response()
+}
+
+const SYMBOL_INFO: &str = r#"{"allowTrailingStop":true,"allowedSelfTradePreventionModes":["EXPIRE_TAKER","EXPIRE_MAKER","EXPIRE_BOTH"],"baseAsset":"ETH","baseAssetPrecision":8,"baseCommissionPrecision":8,"cancelReplaceAllowed":true,"defaultSelfTradePreventionMode":"EXPIRE_MAKER","filters":[{"filterType":"PRICE_FILTER","maxPrice":"1000000.00000000","minPrice":"0.01000000","tickSize":"0.01000000"},{"filterType":"LOT_SIZE","maxQty":"9000.00000000","minQty":"0.00010000","stepSize":"0.00010000"},{"filterType":"ICEBERG_PARTS","limit":10},{"filterType":"MARKET_LOT_SIZE","maxQty":"1701.08445000","minQty":"0.00000000","stepSize":"0.00000000"},{"filterType":"TRAILING_DELTA","maxTrailingAboveDelta":2000,"maxTrailingBelowDelta":2000,"minTrailingAboveDelta":10,"minTrailingBelowDelta":10},{"askMultiplierDown":"0.2","askMultiplierUp":"5","avgPriceMins":5,"bidMultiplierDown":"0.2","bidMultiplierUp":"5","filterType":"PERCENT_PRICE_BY_SIDE"},{"applyMaxToMarket":false,"applyMinToMarket":true,"avgPriceMins":5,"filterType":"NOTIONAL","maxNotional":"9000000.00000000","minNotional":"5.00000000"},{"filterType":"MAX_NUM_ORDERS","maxNumOrders":200},{"filterType":"MAX_NUM_ALGO_ORDERS","maxNumAlgoOrders":5}],"icebergAllowed":true,"isMarginTradingAllowed":true,"isSpotTradingAllowed":true,"ocoAllowed":true,"orderTypes":["LIMIT","LIMIT_MAKER","MARKET","STOP_LOSS_LIMIT","TAKE_PROFIT_LIMIT"],"otoAllowed":false,"permissionSets":[["SPOT","MARGIN"]],"permissions":[],"quoteAsset":"USDT","quoteAssetPrecision":8,"quoteCommissionPrecision":8,"quoteOrderQtyMarketAllowed":true,"quotePrecision":8,"status":"TRADING","symbol":"ETHUSDT"}"#;
+
+const FUTURES_SYMBOL_INFO: &str = r#"{"symbol":"BTCUSDT","pair":"BTCUSDT","contractType":"PERPETUAL","deliveryDate":4133404800000,"onboardDate":1569398400000,"status":"TRADING","maintMarginPercent":"2.5000","requiredMarginPercent":"5.0000","baseAsset":"BTC","quoteAsset":"USDT","marginAsset":"USDT","pricePrecision":2,"quantityPrecision":3,"baseAssetPrecision":8,"quotePrecision":8,"underlyingType":"COIN","underlyingSubType":["PoW"],"triggerProtect":"0.0500","liquidationFee":"0.012500","marketTakeBound":"0.05","maxMoveOrderLimit":10000,"filters":[{"minPrice":"556.80","maxPrice":"4529764","filterType":"PRICE_FILTER","tickSize":"0.10"},{"stepSize":"0.001","filterType":"LOT_SIZE","maxQty":"1000","minQty":"0.001"},{"stepSize":"0.001","filterType":"MARKET_LOT_SIZE","maxQty":"120","minQty":"0.001"},{"limit":200,"filterType":"MAX_NUM_ORDERS"},{"limit":10,"filterType":"MAX_NUM_ALGO_ORDERS"},{"notional":"100","filterType":"MIN_NOTIONAL"},{"multiplierDown":"0.9500","multiplierUp":"1.0500","multiplierDecimal":"4","filterType":"PERCENT_PRICE"}],"orderTypes":["LIMIT","MARKET","STOP","STOP_MARKET","TAKE_PROFIT","TAKE_PROFIT_MARKET","TRAILING_STOP_MARKET"],"timeInForce":["GTC","IOC","FOK","GTX","GTD"]}"#;
@@ -0,0 +1,131 @@
+//! Helpers shared by the test binaries, each of which uses only some of them.
+#![allow(dead_code)]
+
+pub mod mock;
+
+use std::sync::{Arc, Once};
+
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode};
+use axum::Router;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+pub use mock::mock;
+
+pub const ADMIN_TOKEN: &str = "test-admin-token";
+
+/// The harmony router, talking to the mock instead of Binance.
+pub async fn app() -> Router {
+    static ENVIRONMENT: Once = Once::new();
+
+    ENVIRONMENT.call_once(|| {
+        let temp = |name: &str| {
+            std::env::temp_dir().join(format!("harmony-{}-{}", std::process::id(), name))
+        };
+
+        std::env::set_var("BINANCE_ENDPOINT", &mock().url);
+        std::env::set_var("AUDIT_LOG_PATH", temp("audit.jsonl"));
+        std::env::set_var("HALT_STATE_PATH", temp("halt.json"));
+        std::env::set_var("TRADE_CACHE_PATH", temp("trades"));
+        std::env::set_var("ADMIN_TOKEN", ADMIN_TOKEN);
+    });
+
+    harmony::api::router(Arc::new(harmony::api::State::new().await))
+}
+
+/// Signed credentials of `api_key` on the mock.
+pub fn credentials(api_key: &str) -> Value {
+    json!({
+        "api_key": api_key,
+        "secret_key": "secret",
+        "endpoint": mock().url,
+    })
+}
+
+/// `base` with the fields of `extra` set over it.
+pub fn with(mut base: Value, extra: Value) -> Value {
+    for (k, v) in extra.as_object().unwrap() {
+        base[k] = v.clone();
+    }
+
+    base
+}
+
+pub struct Reply {
+    pub status: StatusCode,
+    pub request_id: Option<String>,
+    pub body: Value,
+    /// The raw body, for replies that are not JSON.
+    pub text: String,
+}
+
+pub async fn get(app: &Router, uri: &str) -> Reply {
+    let request = Request::get(uri).body(Body::empty()).unwrap();
+
+    send(app, request).await
+}
+
+pub async fn post(app: &Router, uri: &str, body: Value) -> Reply {
+    let request = Request::post(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+
+    send(app, request).await
+}
+
+pub async fn post_with_request_id(app: &Router, uri: &str, body: Value, id: &str) -> Reply {
+    post_with_headers(app, uri, body, &[("x-request-id", id)]).await
+}
+
+pub async fn post_with_headers(
+    app: &Router,
+    uri: &str,
+    body: Value,
+    headers: &[(&str, &str)],
+) -> Reply {
+    let mut request = Request::post(uri).header("content-type", "application/json");
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+
+    send(app, request.body(Body::from(body.to_string())).unwrap()).await
+}
+
+/// Request to an admin route, authorized with [`ADMIN_TOKEN`].
+pub async fn admin(app: &Router, method: Method, uri: &str, body: Option<Value>) -> Reply {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("authorization", format!("Bearer {}", ADMIN_TOKEN))
+        .header("content-type", "application/json")
+        .body(body.map_or(Body::empty(), |v| Body::from(v.to_string())))
+        .unwrap();
+
+    send(app, request).await
+}
+
+async fn send(app: &Router, request: Request<Body>) -> Reply {
+    let response = app.clone().oneshot(request).await.unwrap();
+
+    let status = response.status();
+    let request_id = response
+        .headers()
+        .get("x-request-id")
+        .and_then(|v| v.to_str().ok())
+        .map(String::from);
+
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+    let text = String::from_utf8_lossy(&bytes).into_owned();
+
+    Reply {
+        status,
+        request_id,
+        body,
+        text,
+    }
+}